@@ -0,0 +1,440 @@
+//! Compression of `RGBA8888` pictures into dxt blocks
+//!
+//! Every 4x4 block is compressed independently: two color endpoints are chosen
+//! so that the block colors lie as close as possible to the line between them, then
+//! each pixel gets the index of the nearest color of the palette the decoder will compute.
+
+use super::{DxtFormat, get_alpha_palette, get_color_palette};
+use rayon::prelude::*;
+
+/// Quality of the dxt compression
+#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub enum CompressionQuality {
+    /// Endpoints are taken from the bounding box of the block colors
+    Fast,
+    /// Endpoints are taken along the principal axis of the block colors
+    /// and refined by the least squares fit. Several times slower than [`CompressionQuality::Fast`]
+    #[default]
+    High,
+}
+
+/// Number of least squares refinement passes for [`CompressionQuality::High`]
+const REFINE_ITERATIONS: usize = 2;
+
+/// Number of power iterations used to find the principal axis of the block colors
+const POWER_ITERATIONS: usize = 8;
+
+/// Pixels of a single 4x4 block as `[r, g, b, a]` rows
+type Block = [[u8; 4]; 16];
+
+/// Color as `[r, g, b]` used for the endpoints search
+type Color = [f32; 3];
+
+/// Compresses the picture given its width, height and pixels
+///
+/// Pixels are expected in the format of `ABGR`, little endian, as returned from
+/// [`decompress_image`](super::decompress_image)
+///
+/// # Return value
+///
+/// Returns compressed blocks accepted by [`decompress_image`](super::decompress_image)
+pub fn compress_image(
+    width: usize,
+    height: usize,
+    pixels: &[u32],
+    format: DxtFormat,
+    quality: CompressionQuality,
+) -> Vec<u32> {
+    assert!(
+        pixels.len() >= width * height,
+        "corrupt data in compression, pixel array length mismatched"
+    );
+
+    // Size of the block in u32's
+    let block_size = format.block_bytes() / 4;
+
+    // The picture is divided into 4x4 blocks
+    let num_blocks_width = width.div_ceil(4);
+    let num_blocks_height = height.div_ceil(4);
+
+    let mut res = vec![0; num_blocks_width * num_blocks_height * block_size];
+
+    res.par_chunks_mut(block_size)
+        .enumerate()
+        .for_each(|(block_idx, out)| {
+            let x = (block_idx % num_blocks_width) * 4;
+            let y = (block_idx / num_blocks_width) * 4;
+
+            let block = read_block(pixels, x, y, width, height);
+
+            match format {
+                DxtFormat::Dxt1 => {
+                    let punch_through = block.iter().any(|pixel| pixel[3] < 128);
+                    out.copy_from_slice(&compress_colors(&block, format, quality, punch_through));
+                }
+                DxtFormat::Dxt3 => {
+                    out[..2].copy_from_slice(&compress_dxt3_alpha(&block));
+                    out[2..].copy_from_slice(&compress_colors(&block, format, quality, false));
+                }
+                DxtFormat::Dxt5 => {
+                    out[..2].copy_from_slice(&compress_dxt5_alpha(&block, quality));
+                    out[2..].copy_from_slice(&compress_colors(&block, format, quality, false));
+                }
+            }
+        });
+
+    res
+}
+
+/// Reads the block at `(x, y)`, pixels outside of the picture repeat the edge ones
+fn read_block(pixels: &[u32], x: usize, y: usize, width: usize, height: usize) -> Block {
+    let mut block = [[0; 4]; 16];
+
+    for (idx, pixel) in block.iter_mut().enumerate() {
+        let pixel_x = usize::min(x + idx % 4, width - 1);
+        let pixel_y = usize::min(y + idx / 4, height - 1);
+
+        *pixel = pixels[pixel_y * width + pixel_x].to_le_bytes();
+    }
+
+    block
+}
+
+/// Compresses the color part of the block
+///
+/// If `punch_through` is set, pixels with alpha below `128` are encoded
+/// as transparent using the 3-color mode of dxt1
+///
+/// # Return value
+///
+/// Returns endpoints and color indices as laid out in the block
+fn compress_colors(
+    block: &Block,
+    format: DxtFormat,
+    quality: CompressionQuality,
+    punch_through: bool,
+) -> [u32; 2] {
+    let colors = block
+        .iter()
+        .filter(|pixel| !punch_through || pixel[3] >= 128)
+        .map(|pixel| [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32])
+        .collect::<Vec<Color>>();
+
+    // The whole block is transparent
+    if colors.is_empty() {
+        return [0, u32::MAX];
+    }
+
+    let (min, max) = bounding_box(&colors);
+    let mut best = encode_colors(block, format, punch_through, min, max);
+
+    if quality == CompressionQuality::Fast {
+        return best.data;
+    }
+
+    let (mut start, mut end) = principal_endpoints(&colors);
+
+    for _ in 0..=REFINE_ITERATIONS {
+        let candidate = encode_colors(block, format, punch_through, start, end);
+        let refined = refine_endpoints(block, punch_through, &candidate);
+
+        if candidate.error < best.error {
+            best = candidate;
+        }
+
+        let Some(refined) = refined else {
+            break;
+        };
+
+        (start, end) = refined;
+    }
+
+    best.data
+}
+
+/// Endpoints encoded into the block along with the error they give
+struct EncodedColors {
+    data: [u32; 2],
+    /// Sum of squared distances from the block colors to the decoded ones
+    error: u32,
+    /// Palette entry of each pixel
+    indices: [u8; 16],
+    /// Whether the palette is in the 3-color mode
+    three_color: bool,
+}
+
+/// Quantizes the endpoints and picks the nearest palette entry for each pixel
+fn encode_colors(
+    block: &Block,
+    format: DxtFormat,
+    punch_through: bool,
+    start: Color,
+    end: Color,
+) -> EncodedColors {
+    let mut color0 = to_rgb565(start);
+    let mut color1 = to_rgb565(end);
+
+    // The order of endpoints selects the dxt1 palette mode: 4 colors if `color0 > color1`
+    // and 3 colors with transparent black otherwise
+    if punch_through == (color0 > color1) {
+        (color0, color1) = (color1, color0);
+    }
+
+    let three_color = format == DxtFormat::Dxt1 && color0 <= color1;
+
+    let endpoints = color0 as u32 | ((color1 as u32) << 16);
+    let palette = get_color_palette(endpoints, format).map(u32::to_le_bytes);
+
+    let mut indices = [0; 16];
+    let mut error = 0;
+
+    for (pixel, index) in block.iter().zip(&mut indices) {
+        if punch_through && pixel[3] < 128 {
+            *index = 3;
+            continue;
+        }
+
+        // The last entry of the 3-color palette is transparent
+        let count = if three_color { 3 } else { 4 };
+
+        let (best, distance) = (0..count)
+            .map(|idx| (idx, color_distance(pixel, &palette[idx])))
+            .min_by_key(|&(_, distance)| distance)
+            .expect("palette is not empty");
+
+        *index = best as u8;
+        error += distance;
+    }
+
+    let mut packed_indices = 0;
+
+    for (idx, &index) in indices.iter().enumerate() {
+        packed_indices |= (index as u32) << (idx * 2);
+    }
+
+    EncodedColors {
+        data: [endpoints, packed_indices],
+        error,
+        indices,
+        three_color,
+    }
+}
+
+/// Squared distance between two colors ignoring alpha
+fn color_distance(lhs: &[u8; 4], rhs: &[u8; 4]) -> u32 {
+    (0..3)
+        .map(|i| (lhs[i] as i32 - rhs[i] as i32).pow(2) as u32)
+        .sum()
+}
+
+/// Quantizes the color to RGB565
+fn to_rgb565(color: Color) -> u16 {
+    let quantize = |value: f32, max: f32| (value.clamp(0.0, 255.0) * max / 255.0).round() as u16;
+
+    (quantize(color[0], 31.0) << 11) | (quantize(color[1], 63.0) << 5) | quantize(color[2], 31.0)
+}
+
+/// Computes the corners of the bounding box of colors, inset to reduce the error
+/// from the colors lying close to the box edges
+fn bounding_box(colors: &[Color]) -> (Color, Color) {
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+
+    for color in colors {
+        for i in 0..3 {
+            min[i] = min[i].min(color[i]);
+            max[i] = max[i].max(color[i]);
+        }
+    }
+
+    for i in 0..3 {
+        let inset = (max[i] - min[i]) / 16.0;
+        min[i] += inset;
+        max[i] -= inset;
+    }
+
+    (min, max)
+}
+
+/// Finds the extreme colors projected on the principal axis of colors
+fn principal_endpoints(colors: &[Color]) -> (Color, Color) {
+    let count = colors.len() as f32;
+
+    let mut mean = [0.0; 3];
+
+    for color in colors {
+        for i in 0..3 {
+            mean[i] += color[i] / count;
+        }
+    }
+
+    let mut covariance = [[0.0_f32; 3]; 3];
+
+    for color in colors {
+        let offset = [0, 1, 2].map(|i| color[i] - mean[i]);
+
+        for i in 0..3 {
+            for j in 0..3 {
+                covariance[i][j] += offset[i] * offset[j];
+            }
+        }
+    }
+
+    // Power iteration converges to the eigenvector with the largest eigenvalue
+    let mut axis = [1.0_f32; 3];
+
+    for _ in 0..POWER_ITERATIONS {
+        let next = [0, 1, 2].map(|i| (0..3).map(|j| covariance[i][j] * axis[j]).sum::<f32>());
+        let length = next.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+        if length < f32::EPSILON {
+            break;
+        }
+
+        axis = next.map(|x| x / length);
+    }
+
+    let project = |color: &Color| (0..3).map(|i| (color[i] - mean[i]) * axis[i]).sum::<f32>();
+
+    let (min, max) = colors
+        .iter()
+        .map(project)
+        .fold((f32::MAX, f32::MIN), |(min, max), t| {
+            (min.min(t), max.max(t))
+        });
+
+    (
+        [0, 1, 2].map(|i| mean[i] + min * axis[i]),
+        [0, 1, 2].map(|i| mean[i] + max * axis[i]),
+    )
+}
+
+/// Finds endpoints minimizing the squared error for the chosen indices
+///
+/// Returns [`None`] if the system is degenerate, e.g. all pixels share one index
+fn refine_endpoints(
+    block: &Block,
+    punch_through: bool,
+    encoded: &EncodedColors,
+) -> Option<(Color, Color)> {
+    // Weights of the first endpoint for each palette entry
+    let weights: [f32; 4] = if encoded.three_color {
+        [1.0, 0.0, 0.5, 0.0]
+    } else {
+        [1.0, 0.0, 2.0 / 3.0, 1.0 / 3.0]
+    };
+
+    let mut alpha2 = 0.0;
+    let mut beta2 = 0.0;
+    let mut alpha_beta = 0.0;
+    let mut alpha_x = [0.0_f32; 3];
+    let mut beta_x = [0.0_f32; 3];
+
+    for (pixel, &index) in block.iter().zip(&encoded.indices) {
+        if (punch_through && pixel[3] < 128) || (encoded.three_color && index == 3) {
+            continue;
+        }
+
+        let alpha = weights[index as usize];
+        let beta = 1.0 - alpha;
+
+        alpha2 += alpha * alpha;
+        beta2 += beta * beta;
+        alpha_beta += alpha * beta;
+
+        for i in 0..3 {
+            alpha_x[i] += alpha * pixel[i] as f32;
+            beta_x[i] += beta * pixel[i] as f32;
+        }
+    }
+
+    let determinant = alpha2 * beta2 - alpha_beta * alpha_beta;
+
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let start = [0, 1, 2].map(|i| (alpha_x[i] * beta2 - beta_x[i] * alpha_beta) / determinant);
+    let end = [0, 1, 2].map(|i| (beta_x[i] * alpha2 - alpha_x[i] * alpha_beta) / determinant);
+
+    Some((start, end))
+}
+
+/// Compresses alpha of the block into 4 bits per pixel
+fn compress_dxt3_alpha(block: &Block) -> [u32; 2] {
+    let mut bytes = [0_u8; 8];
+
+    for (idx, pixel) in block.iter().enumerate() {
+        let alpha = ((pixel[3] as u32 * 15 + 127) / 255) as u8;
+        bytes[idx / 2] |= alpha << ((idx % 2) * 4);
+    }
+
+    [
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+    ]
+}
+
+/// Compresses alpha of the block into two endpoints and 3 bit indices
+fn compress_dxt5_alpha(block: &Block, quality: CompressionQuality) -> [u32; 2] {
+    let alphas = block.map(|pixel| pixel[3]);
+
+    let min = alphas.iter().copied().min().unwrap_or_default();
+    let max = alphas.iter().copied().max().unwrap_or_default();
+
+    // `alpha0 > alpha1` selects the palette of 8 interpolated values
+    let mut best = encode_dxt5_alpha(&alphas, max, min);
+
+    if quality == CompressionQuality::High {
+        // `alpha0 <= alpha1` selects 6 interpolated values along with exact 0 and 255,
+        // so the endpoints only have to cover the values in between
+        let inner = alphas
+            .iter()
+            .copied()
+            .filter(|&alpha| alpha != 0 && alpha != 255);
+        let inner_min = inner.clone().min().unwrap_or(min);
+        let inner_max = inner.max().unwrap_or(max);
+
+        let candidate = encode_dxt5_alpha(&alphas, inner_min, inner_max);
+
+        if candidate.1 < best.1 {
+            best = candidate;
+        }
+    }
+
+    best.0
+}
+
+/// Encodes the alpha part of dxt5 block with given endpoints
+///
+/// # Return value
+///
+/// Returns the encoded data and the sum of squared errors
+fn encode_dxt5_alpha(alphas: &[u8; 16], alpha0: u8, alpha1: u8) -> ([u32; 2], u32) {
+    let palette = get_alpha_palette(alpha0, alpha1);
+
+    let mut indices = 0_u64;
+    let mut error = 0;
+
+    for (idx, &alpha) in alphas.iter().enumerate() {
+        let (best, distance) = palette
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| (i, (alpha as i32 - value as i32).pow(2) as u32))
+            .min_by_key(|&(_, distance)| distance)
+            .expect("palette is not empty");
+
+        indices |= (best as u64) << (idx * 3);
+        error += distance;
+    }
+
+    let indices = indices.to_le_bytes();
+
+    (
+        [
+            u32::from_le_bytes([alpha0, alpha1, indices[0], indices[1]]),
+            u32::from_le_bytes([indices[2], indices[3], indices[4], indices[5]]),
+        ],
+        error,
+    )
+}
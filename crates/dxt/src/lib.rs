@@ -1,3 +1,7 @@
+mod compress;
+
+pub use compress::{CompressionQuality, compress_image};
+
 use rayon::prelude::*;
 use safe_transmute::{guard::SingleManyGuard, transmute_many, transmute_one_to_bytes};
 
@@ -9,6 +13,21 @@ pub enum DxtFormat {
     Dxt5,
 }
 
+impl DxtFormat {
+    /// Size of a single compressed 4x4 block in bytes
+    pub const fn block_bytes(self) -> usize {
+        match self {
+            DxtFormat::Dxt1 => 8,
+            DxtFormat::Dxt3 | DxtFormat::Dxt5 => 16,
+        }
+    }
+
+    /// Size of the compressed picture in bytes given its width and height
+    pub const fn compressed_size(self, width: usize, height: usize) -> usize {
+        width.div_ceil(4) * height.div_ceil(4) * self.block_bytes()
+    }
+}
+
 /// Wrapper for *mut u32 to send it across threads
 ///
 /// This is used to parllelize decompression and is only used
@@ -133,6 +152,27 @@ fn get_color_palette(data: u32, format: DxtFormat) -> [u32; 4] {
     palette
 }
 
+/// Computes dxt5 alpha palette given two alpha endpoints
+fn get_alpha_palette(alpha0: u8, alpha1: u8) -> [u8; 8] {
+    let (alpha0, alpha1) = (alpha0 as u32, alpha1 as u32);
+
+    let mut alpha_palette = [alpha0 as u8, alpha1 as u8, 0, 0, 0, 0, 0, 0];
+
+    if alpha0 > alpha1 {
+        for i in 0_u32..6 {
+            alpha_palette[(i + 2) as usize] = (((6 - i) * alpha0 + (i + 1) * alpha1) / 7) as u8;
+        }
+    } else {
+        for i in 0_u32..4 {
+            alpha_palette[(i + 2) as usize] = (((4 - i) * alpha0 + (i + 1) * alpha1) / 5) as u8;
+        }
+
+        alpha_palette[7] = 255;
+    }
+
+    alpha_palette
+}
+
 /// Decompresses one block of the dxt1 image
 ///
 /// # Format of the block:
@@ -193,8 +233,7 @@ unsafe fn decompress_dxt3_block(
     width: usize,
     height: usize,
 ) {
-    let first_num_byte_view = transmute_one_to_bytes(&data[0]);
-    let second_num_byte_view = transmute_one_to_bytes(&data[1]);
+    let alpha_bytes = [data[0].to_le_bytes(), data[1].to_le_bytes()].concat();
 
     let color_palette = get_color_palette(data[2], DxtFormat::Dxt3);
 
@@ -206,13 +245,9 @@ unsafe fn decompress_dxt3_block(
                 let color_idx = (colors_byte >> (j * 2)) & 3;
                 let mut color = color_palette[color_idx as usize];
 
+                // Each alpha value takes 4 bits, so one byte encodes 2 of them
                 let idx = i * 4 + j;
-
-                let byte = if idx < 4 {
-                    first_num_byte_view[idx]
-                } else {
-                    second_num_byte_view[idx % 4]
-                };
+                let byte = alpha_bytes[idx / 2];
 
                 let alpha = if idx % 2 == 0 {
                     (byte & 0xF) * 17
@@ -220,8 +255,8 @@ unsafe fn decompress_dxt3_block(
                     (byte >> 4) * 17
                 };
 
-                color &= u32::MAX << 8;
-                color |= alpha as u32;
+                color &= u32::MAX >> 8;
+                color |= (alpha as u32) << 24;
 
                 // Safety
                 //
@@ -256,50 +291,27 @@ unsafe fn decompress_dxt5_block(
     width: usize,
     height: usize,
 ) {
-    let first_num_bytes_view = transmute_one_to_bytes(&data[0]);
-    let second_num_bytes_view = transmute_one_to_bytes(&data[1]);
+    let alpha_bytes = [data[0].to_le_bytes(), data[1].to_le_bytes()].concat();
 
-    let alpha0 = first_num_bytes_view[0] as u32;
-    let alpha1 = first_num_bytes_view[1] as u32;
+    let alpha_palette = get_alpha_palette(alpha_bytes[0], alpha_bytes[1]);
 
-    let mut alpha_palette = [alpha0 as u8, alpha1 as u8, 0, 0, 0, 0, 0, 0];
-
-    if alpha0 > alpha1 {
-        for i in 0_u32..6 {
-            alpha_palette[(i + 2) as usize] = (((6 - i) * alpha0 + (i + 1) * alpha1) / 7) as u8;
-        }
-    } else {
-        for i in 0_u32..4 {
-            alpha_palette[(i + 2) as usize] = (((4 - i) * alpha0 + (i + 1) * alpha1) / 5) as u8;
-        }
-
-        alpha_palette[7] = 255;
-    }
+    // Alpha indices take 48 bits after the endpoints
+    let mut alpha_indices_bytes = [0; 8];
+    alpha_indices_bytes[..6].copy_from_slice(&alpha_bytes[2..]);
+    let alpha_indices = u64::from_le_bytes(alpha_indices_bytes);
 
     let color_palette = get_color_palette(data[2], DxtFormat::Dxt5);
 
-    let mut bits = 0;
-    let mut cur_byte = 0;
     let color_indices_byte_view = transmute_one_to_bytes(&data[3]);
     for (i, color_indices_byte) in color_indices_byte_view.iter().enumerate() {
         for j in 0..4 {
-            if bits < 3 {
-                let idx = 2 + (i * 4 + j) * 3 / 8;
-                if idx < 4 {
-                    cur_byte |= (first_num_bytes_view[idx] as u32) << bits;
-                } else {
-                    cur_byte |= (second_num_bytes_view[idx % 4] as u32) << bits;
-                }
-                bits += 8;
-            }
-
-            let alpha_idx = cur_byte & 7;
-
             if y + i < height && x + j < width {
                 // Each pixel index (in block) takes 2 bits, so one byte can encode 4 indices
                 let color_idx = (color_indices_byte >> (j * 2)) & 3;
                 let mut color = color_palette[color_idx as usize];
 
+                // Each alpha index takes 3 bits
+                let alpha_idx = (alpha_indices >> ((i * 4 + j) * 3)) & 7;
                 let alpha = alpha_palette[alpha_idx as usize];
 
                 color &= u32::MAX >> 8;
@@ -311,9 +323,6 @@ unsafe fn decompress_dxt5_block(
                 // so it is safe to write to the pointee
                 unsafe { out_pixels.0.add((y + i) * width + x + j).write(color) };
             }
-
-            cur_byte >>= 3;
-            bits -= 3;
         }
     }
 }
@@ -321,9 +330,178 @@ unsafe fn decompress_dxt5_block(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Read;
+    use std::{io::Read, ops::Range};
     use transmute_extra::transmute_vec_u8_to_vec_u32;
 
+    /// Smooth picture with partial blocks at the right and bottom edges
+    fn gradient(width: usize, height: usize) -> Vec<u32> {
+        (0..width * height)
+            .map(|idx| {
+                let (x, y) = (idx % width, idx / width);
+
+                u32::from_le_bytes([
+                    (x * 255 / width) as u8,
+                    (y * 255 / height) as u8,
+                    128,
+                    ((x + y) * 255 / (width + height)) as u8,
+                ])
+            })
+            .collect()
+    }
+
+    /// Maximum difference of the given channels
+    fn max_error(lhs: &[u32], rhs: &[u32], channels: Range<usize>) -> u8 {
+        lhs.iter()
+            .zip(rhs)
+            .flat_map(|(lhs, rhs)| {
+                let (lhs, rhs) = (lhs.to_le_bytes(), rhs.to_le_bytes());
+                channels.clone().map(move |i| lhs[i].abs_diff(rhs[i]))
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn squared_error(lhs: &[u32], rhs: &[u32]) -> u64 {
+        lhs.iter()
+            .zip(rhs)
+            .flat_map(|(lhs, rhs)| {
+                let (lhs, rhs) = (lhs.to_le_bytes(), rhs.to_le_bytes());
+                (0..4).map(move |i| (lhs[i].abs_diff(rhs[i]) as u64).pow(2))
+            })
+            .sum()
+    }
+
+    #[test]
+    fn round_trip() {
+        let (width, height) = (13, 10);
+
+        for format in [DxtFormat::Dxt1, DxtFormat::Dxt3, DxtFormat::Dxt5] {
+            let mut pixels = gradient(width, height);
+
+            // Transparent pixels are black in dxt1
+            if format == DxtFormat::Dxt1 {
+                pixels.iter_mut().for_each(|pixel| *pixel |= 0xFF << 24);
+            }
+
+            let mut errors = Vec::new();
+
+            for quality in [CompressionQuality::Fast, CompressionQuality::High] {
+                let compressed = compress_image(width, height, &pixels, format, quality);
+                assert_eq!(compressed.len() * 4, format.compressed_size(width, height));
+
+                let decompressed = decompress_image(width, height, &compressed, format);
+
+                // Colors of the gradient do not lie on a single line, which gives the error
+                assert!(
+                    max_error(&pixels, &decompressed, 0..3) <= 48,
+                    "{format:?} {quality:?}: color error is too big"
+                );
+
+                assert!(
+                    max_error(&pixels, &decompressed, 3..4) <= 8,
+                    "{format:?} {quality:?}: alpha error is too big"
+                );
+
+                errors.push(squared_error(&pixels, &decompressed));
+            }
+
+            assert!(
+                errors[1] <= errors[0],
+                "{format:?}: high quality is worse than fast"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_solid_color() {
+        let color = u32::from_le_bytes([255, 0, 255, 255]);
+        let pixels = vec![color; 8 * 8];
+
+        for format in [DxtFormat::Dxt1, DxtFormat::Dxt3, DxtFormat::Dxt5] {
+            let compressed = compress_image(8, 8, &pixels, format, CompressionQuality::default());
+            assert_eq!(decompress_image(8, 8, &compressed, format), pixels);
+        }
+    }
+
+    #[test]
+    fn dxt1_punch_through_alpha() {
+        let opaque = u32::from_le_bytes([200, 100, 50, 255]);
+        let transparent = u32::from_le_bytes([0, 0, 0, 0]);
+
+        let pixels = (0..16)
+            .map(|idx| if idx % 3 == 0 { transparent } else { opaque })
+            .collect::<Vec<_>>();
+
+        let compressed = compress_image(4, 4, &pixels, DxtFormat::Dxt1, CompressionQuality::High);
+        let decompressed = decompress_image(4, 4, &compressed, DxtFormat::Dxt1);
+
+        for (pixel, result) in pixels.iter().zip(&decompressed) {
+            let (pixel, result) = (pixel.to_le_bytes(), result.to_le_bytes());
+
+            assert_eq!(pixel[3], result[3]);
+
+            if pixel[3] == 255 {
+                assert!((0..3).all(|i| pixel[i].abs_diff(result[i]) <= 8));
+            }
+        }
+    }
+
+    /// Block of two equal white endpoints, so only the alpha differs between the pixels
+    const WHITE_COLOR_BLOCK: u32 = 0x0000_FFFF_u32 | (0xFFFF << 16);
+
+    #[test]
+    fn decompress_dxt3_alpha() {
+        // Explicit alpha of the pixel `k` is `k`, two 4-bit values per byte
+        let alpha_bytes: [u8; 8] =
+            std::array::from_fn(|i| (2 * i as u8) | ((2 * i as u8 + 1) << 4));
+
+        let data = [
+            u32::from_le_bytes(alpha_bytes[..4].try_into().unwrap()),
+            u32::from_le_bytes(alpha_bytes[4..].try_into().unwrap()),
+            WHITE_COLOR_BLOCK,
+            0,
+        ];
+
+        let pixels = decompress_image(4, 4, &data, DxtFormat::Dxt3);
+
+        for (k, pixel) in pixels.iter().enumerate() {
+            assert_eq!(
+                pixel.to_le_bytes(),
+                [255, 255, 255, k as u8 * 17],
+                "pixel {k}"
+            );
+        }
+    }
+
+    #[test]
+    fn decompress_dxt5_alpha() {
+        // Alpha index of the pixel `k` is `k % 8`, 3 bits each after the two endpoints
+        let alpha_indices = (0..16).fold(0_u64, |indices, k| indices | ((k % 8) << (3 * k)));
+
+        let mut alpha_bytes = [0; 8];
+        alpha_bytes[0] = 255;
+        alpha_bytes[1] = 0;
+        alpha_bytes[2..].copy_from_slice(&alpha_indices.to_le_bytes()[..6]);
+
+        let data = [
+            u32::from_le_bytes(alpha_bytes[..4].try_into().unwrap()),
+            u32::from_le_bytes(alpha_bytes[4..].try_into().unwrap()),
+            WHITE_COLOR_BLOCK,
+            0,
+        ];
+
+        let pixels = decompress_image(4, 4, &data, DxtFormat::Dxt5);
+        let palette = [255, 0, 218, 182, 145, 109, 72, 36];
+
+        for (k, pixel) in pixels.iter().enumerate() {
+            assert_eq!(
+                pixel.to_le_bytes(),
+                [255, 255, 255, palette[k % 8]],
+                "pixel {k}"
+            );
+        }
+    }
+
     #[test]
     #[ignore = "no files provided"]
     fn test_decopmpress_dxt5() {
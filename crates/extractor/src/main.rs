@@ -1,13 +1,19 @@
 use extractor::package;
+use extractor::texture::TexEncodeFrame;
 use extractor::texture::TexExtractData;
+use extractor::texture::TexFormat;
+use extractor::texture::TexWriter;
 use extractor::texture::extract_data;
 
 use std::fs;
 use std::io;
 use std::io::BufWriter;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use image::AnimationDecoder;
 
 #[derive(Parser, Debug, Clone)]
 #[command(version, about, long_about=None)]
@@ -19,19 +25,74 @@ struct Cli {
 
 #[derive(Debug, Clone, Subcommand)]
 enum Command {
-    /// Extract scene.pkg contents
+    /// Extract scene.pkg contents or pack a directory into one
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Package {
-        path: PathBuf,
+        #[command(subcommand)]
+        command: Option<PackageCommand>,
+
+        #[arg(required = true)]
+        path: Option<PathBuf>,
 
         out_path: Option<PathBuf>,
     },
 
-    /// Convert .tex files
+    /// Convert .tex files to png or encode images into .tex files
     Texture {
         paths: Vec<PathBuf>,
 
-        #[arg(long, short)]
+        /// Store every mipmap of the decoded texture
+        #[arg(long, short, conflicts_with = "encode")]
         all_mipmaps: bool,
+
+        /// Encode png, jpeg or gif images into .tex files
+        #[arg(long, short)]
+        encode: bool,
+
+        /// Pixel format of the encoded texture
+        #[arg(long, value_enum, default_value_t = EncodeFormat::Dxt5, requires = "encode")]
+        format: EncodeFormat,
+
+        /// Compress encoded mipmaps with lz4
+        #[arg(long, requires = "encode")]
+        lz4: bool,
+
+        /// Store only the full size image without generated mipmaps
+        #[arg(long, requires = "encode")]
+        no_mipmaps: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum EncodeFormat {
+    Rgba8888,
+    Dxt1,
+    Dxt3,
+    Dxt5,
+    Rg88,
+    R8,
+}
+
+impl From<EncodeFormat> for TexFormat {
+    fn from(value: EncodeFormat) -> Self {
+        match value {
+            EncodeFormat::Rgba8888 => Self::Rgba8888,
+            EncodeFormat::Dxt1 => Self::Dxt1,
+            EncodeFormat::Dxt3 => Self::Dxt3,
+            EncodeFormat::Dxt5 => Self::Dxt5,
+            EncodeFormat::Rg88 => Self::Rg88,
+            EncodeFormat::R8 => Self::R8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum PackageCommand {
+    /// Pack directory contents into scene.pkg
+    Pack {
+        directory: PathBuf,
+
+        out_path: Option<PathBuf>,
     },
 }
 
@@ -39,7 +100,37 @@ fn main() -> anyhow::Result<()> {
     let args = Cli::parse();
 
     match args.command {
-        Command::Package { path, out_path } => {
+        Command::Package {
+            command:
+                Some(PackageCommand::Pack {
+                    directory,
+                    out_path,
+                }),
+            ..
+        } => {
+            let out_path = if let Some(out_path) = out_path {
+                out_path
+            } else {
+                "scene.pkg".into()
+            };
+
+            // the package from the previous run should not be packed into the new one
+            let mut writer = package::PackageWriter::new();
+            writer.add_directory_excluding(&directory, Some(&out_path))?;
+
+            let mut out = BufWriter::new(fs::File::create(out_path)?);
+            writer.write(&mut out)?;
+            out.flush()?;
+        }
+        Command::Package {
+            command: None,
+            path,
+            out_path,
+        } => {
+            let Some(path) = path else {
+                anyhow::bail!("path to scene.pkg is required");
+            };
+
             let fd = fs::File::open(path)?;
             let mut fd = io::BufReader::new(fd);
 
@@ -53,7 +144,25 @@ fn main() -> anyhow::Result<()> {
 
             reader.store_files(&out_path)?;
         }
-        Command::Texture { paths, all_mipmaps } => {
+        Command::Texture {
+            paths,
+            encode: true,
+            format,
+            lz4,
+            no_mipmaps,
+            ..
+        } => {
+            let writer = TexWriter::new(format.into())
+                .with_lz4_compression(lz4)
+                .with_mipmaps(!no_mipmaps);
+
+            for path in paths {
+                encode_texture(&writer, &path)?;
+            }
+        }
+        Command::Texture {
+            paths, all_mipmaps, ..
+        } => {
             for path in paths {
                 let fd = fs::File::open(&path)?;
                 let mut fd = io::BufReader::new(fd);
@@ -113,3 +222,48 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Encodes the image at `path` into `{file_stem}.tex`, gif images are stored as animated textures
+fn encode_texture(writer: &TexWriter, path: &Path) -> anyhow::Result<()> {
+    // the texture is stored next to the image
+    if path.file_name().is_none() {
+        anyhow::bail!("{} is not a path to an image", path.display());
+    }
+
+    let out_path = path.with_extension("tex");
+
+    if out_path == path {
+        anyhow::bail!("{} is already a .tex file", path.display());
+    }
+
+    let mut out = BufWriter::new(fs::File::create(out_path)?);
+
+    let is_gif = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gif"));
+
+    if is_gif {
+        let fd = io::BufReader::new(fs::File::open(path)?);
+        let frames = image::codecs::gif::GifDecoder::new(fd)?
+            .into_frames()
+            .map(|frame| {
+                let frame = frame?;
+                let duration = Duration::from(frame.delay());
+
+                Ok(TexEncodeFrame {
+                    image: frame.into_buffer(),
+                    duration,
+                })
+            })
+            .collect::<Result<Vec<_>, image::ImageError>>()?;
+
+        writer.write_gif(&frames, &mut out)?;
+    } else {
+        let image = image::open(path)?.into_rgba8();
+        writer.write_image(&image, &mut out)?;
+    }
+
+    out.flush()?;
+
+    Ok(())
+}
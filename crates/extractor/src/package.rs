@@ -1,6 +1,6 @@
-//! Functions and structs for extracting `scene.pkg` files.
+//! Functions and structs for extracting and packing `scene.pkg` files.
 //!
-//! For the module entry point reference [`PackageReader`]
+//! For the module entry points reference [`PackageReader`], [`PackageArchive`] and [`PackageWriter`]
 //!
 //! ```rust,ignore
 //! use extractor::package::*;
@@ -13,6 +13,15 @@
 //! path.push("assets");
 //!
 //! reader.store_files(&path).unwrap();
+//!
+//! let mut writer = PackageWriter::new();
+//! writer.add_directory(&path).unwrap();
+//!
+//! let mut fd = std::fs::File::create("repacked.pkg").unwrap();
+//! writer.write(&mut fd).unwrap();
+//!
+//! let archive = PackageArchive::map("repacked.pkg".as_ref()).unwrap();
+//! let scene_json: &[u8] = archive.get("scene.json").unwrap();
 //! ```
 //!
 //! # Format info
@@ -31,11 +40,13 @@
 //!         - `offset`: [`u32`]
 //!         - `size`: [`u32`]
 //! - The files contents themselves. Files appear in the same order as
-//!   their meta earlier
+//!   their meta earlier, `offset` is counted from the start of the contents
 
-use safe_transmute::to_bytes::transmute_one_to_bytes_mut;
+use memmap2::Mmap;
+use safe_transmute::to_bytes::{transmute_one_to_bytes, transmute_one_to_bytes_mut};
+use std::collections::HashMap;
 use std::fs::{self, File};
-use std::io::{self, Read, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
 /// Error that may occur while working with `scene.pkg` file
@@ -50,8 +61,22 @@ pub enum PackageExtractError {
     /// Failed to create a path read from scene.pkg
     #[error("failed to parse file path from scene.pkg")]
     Parse,
+
+    /// File path can not be stored in scene.pkg
+    #[error("file path '{0}' is not valid unicode")]
+    NonUnicodePath(PathBuf),
+
+    #[error("no file '{0}' in scene.pkg")]
+    NotFound(String),
+
+    /// File or the whole package exceeds 4GiB
+    #[error("file '{0}' is too large to be stored in scene.pkg")]
+    TooLarge(String),
 }
 
+/// Version written by [`PackageWriter::new`]
+pub const DEFAULT_PACKAGE_VERSION: &str = "PKGV0001";
+
 /// Info about a file contained in scene.pkg
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FileMeta {
@@ -120,26 +145,31 @@ pub struct PackageReader<'a, T: Read> {
     reader: Reader<'a, T>,
 }
 
-impl<'a, T: Read> PackageReader<'a, T> {
-    /// Reads meta info from the file and constructs Self
-    pub fn new(fd: &'a mut T) -> Result<Self, PackageExtractError> {
-        let mut reader = Reader(fd);
-        let version = reader.read_str()?;
-        let filecount = reader.read_int()?;
+impl<T: Read> Reader<'_, T> {
+    fn read_meta(&mut self) -> Result<PackageMeta, PackageExtractError> {
+        let version = self.read_str()?;
+        let filecount = self.read_int()?;
 
         let mut files = Vec::new();
         for _ in 0..filecount {
             files.push(FileMeta {
-                name: reader.read_str()?,
-                offset: reader.read_int()?,
-                size: reader.read_int()?,
+                name: self.read_str()?,
+                offset: self.read_int()?,
+                size: self.read_int()?,
             })
         }
 
-        Ok(Self {
-            meta: PackageMeta { files, version },
-            reader,
-        })
+        Ok(PackageMeta { files, version })
+    }
+}
+
+impl<'a, T: Read> PackageReader<'a, T> {
+    /// Reads meta info from the file and constructs Self
+    pub fn new(fd: &'a mut T) -> Result<Self, PackageExtractError> {
+        let mut reader = Reader(fd);
+        let meta = reader.read_meta()?;
+
+        Ok(Self { meta, reader })
     }
 
     pub fn meta(&self) -> &PackageMeta {
@@ -168,3 +198,458 @@ impl<'a, T: Read> PackageReader<'a, T> {
         Ok(())
     }
 }
+
+/// Random access reader of `scene.pkg` entries. Unlike [`PackageReader`] it does not
+/// require to read the whole package, entries can be opened one by one with [`PackageArchive::open`]
+#[derive(Debug)]
+pub struct PackageArchive<T> {
+    meta: PackageMeta,
+    /// Index of the file in `meta.files` by its name
+    index: HashMap<String, usize>,
+    /// Position in the package where files contents start
+    data_offset: u64,
+    src: T,
+}
+
+impl<T: Read + Seek> PackageArchive<T> {
+    /// Reads meta info from the package and constructs Self
+    pub fn new(mut src: T) -> Result<Self, PackageExtractError> {
+        let meta = Reader(&mut src).read_meta()?;
+        let data_offset = src.stream_position()?;
+
+        let index = meta
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| (file.name.clone(), i))
+            .collect();
+
+        Ok(Self {
+            meta,
+            index,
+            data_offset,
+            src,
+        })
+    }
+
+    /// Opens the entry as a [`Read`] + [`Seek`] view into the package
+    pub fn open(&mut self, name: &str) -> Result<PackageEntry<'_, T>, PackageExtractError> {
+        let file = self
+            .entry(name)
+            .ok_or_else(|| PackageExtractError::NotFound(name.to_owned()))?;
+
+        let start = self.data_offset + file.offset as u64;
+        let size = file.size as u64;
+
+        self.src.seek(SeekFrom::Start(start))?;
+
+        Ok(PackageEntry {
+            src: &mut self.src,
+            start,
+            size,
+            position: 0,
+        })
+    }
+
+    /// Reads contents of the entry
+    pub fn read(&mut self, name: &str) -> Result<Vec<u8>, PackageExtractError> {
+        let mut entry = self.open(name)?;
+
+        let mut buf = Vec::with_capacity(entry.size as usize);
+        entry.read_to_end(&mut buf)?;
+
+        Ok(buf)
+    }
+}
+
+impl<T> PackageArchive<T> {
+    pub fn meta(&self) -> &PackageMeta {
+        &self.meta
+    }
+
+    /// Lists all entries in the order they are stored
+    pub fn entries(&self) -> &[FileMeta] {
+        &self.meta.files
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&FileMeta> {
+        self.index.get(name).map(|&i| &self.meta.files[i])
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index.contains_key(name)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.src
+    }
+}
+
+impl<T: AsRef<[u8]>> PackageArchive<io::Cursor<T>> {
+    /// Borrows contents of the entry without copying
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        let file = self.entry(name)?;
+
+        let start = usize::try_from(self.data_offset).ok()? + file.offset as usize;
+        let end = start + file.size as usize;
+
+        self.src.get_ref().as_ref().get(start..end)
+    }
+}
+
+impl PackageArchive<io::Cursor<Mmap>> {
+    /// Maps `scene.pkg` into memory, so that entries can be borrowed with [`PackageArchive::get`]
+    pub fn map(path: &Path) -> Result<Self, PackageExtractError> {
+        let file = File::open(path)?;
+
+        // Safety
+        //
+        // Packages are not expected to be modified while used. Otherwise, entries
+        // contents may change, but the archive does not rely on them being valid
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        Self::new(io::Cursor::new(mmap))
+    }
+}
+
+/// Package mapped into memory by [`PackageArchive::map`]
+pub type MappedPackageArchive = PackageArchive<io::Cursor<Mmap>>;
+
+/// View of a single [`PackageArchive`] entry
+#[derive(Debug)]
+pub struct PackageEntry<'a, T> {
+    src: &'a mut T,
+    /// Position of the entry in the package
+    start: u64,
+    size: u64,
+    /// Position relative to the entry start
+    position: u64,
+}
+
+impl<T> PackageEntry<'_, T> {
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl<T: Read> Read for PackageEntry<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.size.saturating_sub(self.position);
+        let len =
+            usize::try_from(remaining).map_or(buf.len(), |remaining| buf.len().min(remaining));
+
+        let count = self.src.read(&mut buf[..len])?;
+        self.position += count as u64;
+
+        Ok(count)
+    }
+}
+
+impl<T: Seek> Seek for PackageEntry<'_, T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )
+        })?;
+
+        self.src.seek(SeekFrom::Start(self.start + position))?;
+        self.position = position;
+
+        Ok(position)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Writer<'a, T: Write>(&'a mut T);
+
+impl<T: Write> Writer<'_, T> {
+    fn write_int(&mut self, value: u32) -> Result<(), PackageExtractError> {
+        self.0.write_all(transmute_one_to_bytes(&value))?;
+
+        Ok(())
+    }
+
+    fn write_str(&mut self, value: &str) -> Result<(), PackageExtractError> {
+        let size =
+            u32::try_from(value.len()).map_err(|_| PackageExtractError::TooLarge(value.into()))?;
+
+        self.write_int(size)?;
+        self.0.write_all(value.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+/// File to be stored in `scene.pkg`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackageFile {
+    /// Name of the file, path components are separated by `/`
+    pub name: String,
+    /// Contents of the file
+    pub data: Vec<u8>,
+}
+
+/// Writer for the `scene.pkg` files. Collects files and writes them with a [`PackageMeta`]
+/// readable by [`PackageReader`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackageWriter {
+    version: String,
+    files: Vec<PackageFile>,
+}
+
+impl PackageWriter {
+    /// Constructs an empty package with [`DEFAULT_PACKAGE_VERSION`]
+    pub fn new() -> Self {
+        Self::with_version(DEFAULT_PACKAGE_VERSION)
+    }
+
+    /// Constructs an empty package with the given version, e.g. one from [`PackageReader::meta`]
+    pub fn with_version(version: impl Into<String>) -> Self {
+        Self {
+            version: version.into(),
+            files: Vec::new(),
+        }
+    }
+
+    pub fn files(&self) -> &[PackageFile] {
+        &self.files
+    }
+
+    /// Adds an in-memory file
+    pub fn add_file(&mut self, name: impl Into<String>, data: Vec<u8>) -> &mut Self {
+        self.files.push(PackageFile {
+            name: name.into(),
+            data,
+        });
+
+        self
+    }
+
+    /// Adds in-memory files in the iteration order
+    pub fn add_files(&mut self, files: impl IntoIterator<Item = PackageFile>) -> &mut Self {
+        self.files.extend(files);
+        self
+    }
+
+    /// Reads all files in the `directory` recursively. Files are named by their path
+    /// relative to the `directory` and added in lexicographical order
+    pub fn add_directory(&mut self, directory: &Path) -> Result<&mut Self, PackageExtractError> {
+        self.add_directory_excluding(directory, None)
+    }
+
+    /// Same as [`PackageWriter::add_directory`] but skips the `excluded` file,
+    /// e.g. the package being written into the same directory
+    pub fn add_directory_excluding(
+        &mut self,
+        directory: &Path,
+        excluded: Option<&Path>,
+    ) -> Result<&mut Self, PackageExtractError> {
+        let mut paths = Vec::new();
+        collect_files(directory, &mut paths)?;
+        paths.sort();
+
+        // a missing file can not be in the directory
+        let excluded = excluded.and_then(|path| fs::canonicalize(path).ok());
+
+        for path in paths {
+            if let Some(excluded) = &excluded
+                && path.file_name() == excluded.file_name()
+                && fs::canonicalize(&path)? == *excluded
+            {
+                continue;
+            }
+
+            let relative = path
+                .strip_prefix(directory)
+                .map_err(|_| PackageExtractError::Parse)?;
+
+            let name = relative
+                .components()
+                .map(|component| component.as_os_str().to_str())
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| PackageExtractError::NonUnicodePath(relative.to_owned()))?
+                .join("/");
+
+            let data = fs::read(&path)?;
+            self.add_file(name, data);
+        }
+
+        Ok(self)
+    }
+
+    /// Computes meta info of the files added so far
+    pub fn meta(&self) -> Result<PackageMeta, PackageExtractError> {
+        let mut offset = 0_u32;
+
+        let files = self
+            .files
+            .iter()
+            .map(|file| {
+                let too_large = || PackageExtractError::TooLarge(file.name.clone());
+
+                let size = u32::try_from(file.data.len()).map_err(|_| too_large())?;
+                let meta = FileMeta {
+                    name: file.name.clone(),
+                    offset,
+                    size,
+                };
+
+                offset = offset.checked_add(size).ok_or_else(too_large)?;
+
+                Ok(meta)
+            })
+            .collect::<Result<Vec<_>, PackageExtractError>>()?;
+
+        Ok(PackageMeta {
+            files,
+            version: self.version.clone(),
+        })
+    }
+
+    /// Writes meta info followed by the files contents
+    pub fn write(&self, fd: &mut impl Write) -> Result<(), PackageExtractError> {
+        let meta = self.meta()?;
+        let mut writer = Writer(fd);
+
+        writer.write_str(&meta.version)?;
+        writer.write_int(meta.files.len() as u32)?;
+
+        for file in &meta.files {
+            writer.write_str(&file.name)?;
+            writer.write_int(file.offset)?;
+            writer.write_int(file.size)?;
+        }
+
+        for file in &self.files {
+            writer.0.write_all(&file.data)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for PackageWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collects paths of all files in the `directory` recursively.
+/// Symlinks to directories are skipped, so cyclic links can not recurse forever
+fn collect_files(directory: &Path, paths: &mut Vec<PathBuf>) -> Result<(), PackageExtractError> {
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            collect_files(&path, paths)?;
+        } else if !(file_type.is_symlink() && path.is_dir()) {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_read_package() {
+        let mut writer = PackageWriter::with_version("PKGV0019");
+        writer
+            .add_file("scene.json", b"{}".to_vec())
+            .add_file("materials/logo.tex", vec![1, 2, 3, 4, 5])
+            .add_file("empty", Vec::new());
+
+        let mut package = Vec::new();
+        writer.write(&mut package).unwrap();
+
+        let mut fd = package.as_slice();
+        let mut reader = PackageReader::new(&mut fd).unwrap();
+
+        assert_eq!(reader.meta(), &writer.meta().unwrap());
+        assert_eq!(reader.meta().files[1].offset, 2);
+        assert_eq!(reader.meta().files[2].offset, 7);
+
+        let directory = tempfile::tempdir().unwrap();
+        reader.store_files(directory.path()).unwrap();
+
+        let mut repacked = PackageWriter::with_version("PKGV0019");
+        repacked.add_directory(directory.path()).unwrap();
+
+        let mut files = writer.files().to_vec();
+        files.sort();
+
+        assert_eq!(repacked.files(), files);
+    }
+
+    #[test]
+    fn pack_directory_with_symlinks_and_output() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = directory.path();
+
+        fs::create_dir(root.join("materials")).unwrap();
+        fs::write(root.join("scene.json"), b"{}").unwrap();
+        fs::write(root.join("materials/logo.tex"), b"logo").unwrap();
+        fs::write(root.join("scene.pkg"), b"previous package").unwrap();
+        std::os::unix::fs::symlink(root, root.join("materials/cycle")).unwrap();
+
+        let mut writer = PackageWriter::new();
+        writer
+            .add_directory_excluding(root, Some(&root.join("scene.pkg")))
+            .unwrap();
+
+        let names = writer
+            .files()
+            .iter()
+            .map(|file| file.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["materials/logo.tex", "scene.json"]);
+    }
+
+    #[test]
+    fn random_access() {
+        let mut writer = PackageWriter::new();
+        writer
+            .add_file("scene.json", b"{}".to_vec())
+            .add_file("materials/logo.tex", b"logo data".to_vec());
+
+        let mut package = Vec::new();
+        writer.write(&mut package).unwrap();
+
+        let archive = PackageArchive::new(io::Cursor::new(package.as_slice())).unwrap();
+        assert_eq!(archive.entries().len(), 2);
+        assert_eq!(archive.get("materials/logo.tex"), Some(&b"logo data"[..]));
+        assert_eq!(archive.get("missing"), None);
+
+        let mut archive = PackageArchive::new(io::Cursor::new(package)).unwrap();
+        let mut entry = archive.open("materials/logo.tex").unwrap();
+
+        entry.seek(SeekFrom::Start(5)).unwrap();
+        let mut buf = String::new();
+        entry.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "data");
+
+        entry.seek(SeekFrom::End(-9)).unwrap();
+        let mut buf = [0; 4];
+        entry.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"logo");
+
+        assert_eq!(archive.read("scene.json").unwrap(), b"{}");
+        assert!(matches!(
+            archive.open("missing"),
+            Err(PackageExtractError::NotFound(_))
+        ));
+    }
+}
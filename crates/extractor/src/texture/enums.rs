@@ -33,6 +33,10 @@ pub enum TexExtractError {
     /// A function encountered some error while parsing input file
     #[error("corrupt data in the file: {about}")]
     Corrupt { about: String },
+
+    /// A function tried to write a value that does not fit into the `.tex` file fields
+    #[error("the texture is too large to be stored in the `.tex` file")]
+    TooLarge,
 }
 
 /// Format of the image stored in the texture
@@ -367,6 +371,19 @@ impl MipmapData {
             MipmapData::Raw(image) => image.as_bytes(),
         }
     }
+
+    /// Converts the data into an `RGBA8888` image.
+    ///
+    /// `R8` is treated as luminance and `RG88` as luminance with alpha.
+    /// `Raw` data is decoded as an embedded image file (png, jpeg, etc.)
+    pub fn into_rgba8(self) -> Result<image::RgbaImage, image::ImageError> {
+        Ok(match self {
+            MipmapData::R8(image) => image::DynamicImage::ImageLuma8(image).into_rgba8(),
+            MipmapData::Rg88(image) => image::DynamicImage::ImageLumaA8(image).into_rgba8(),
+            MipmapData::Rgba8888(image) => image,
+            MipmapData::Raw(bytes) => image::load_from_memory(&bytes)?.into_rgba8(),
+        })
+    }
 }
 
 /// Resulting data extracted from the `.tex` file
@@ -1,7 +1,7 @@
 //! Functions and structs for working the `.tex` files
 //!
 //! A good starting point is the [`TexReader`] struct or [`extract_data`] function
-//! that does all the job done itself
+//! that does all the job done itself. Pictures can be encoded back into `.tex` files with [`TexWriter`]
 //!
 //! # Example of extracting data via manual work with readers and storing it via png crate
 //!
@@ -81,16 +81,19 @@
 
 pub mod enums;
 pub mod stages;
+pub mod writer;
 
 use image::ImageBuffer;
 use std::ffi::CString;
 use std::io::Read;
+use std::time::Duration;
 use thiserror::Error;
 use tracing::{debug, instrument};
 use transmute_extra::transmute_vec_u8_to_vec_u32;
 
 pub use enums::*;
 pub use stages::*;
+pub use writer::*;
 
 /// Information about the header of the `.tex` file
 #[derive(Default, Debug, Copy, Clone, Hash, Eq, PartialEq, PartialOrd, Ord)]
@@ -154,42 +157,101 @@ impl TexMipmap {
             self.lz4_compressed = false;
         }
 
-        let dxt_format = match self.format {
-            MipmapFormat::CompressedDxt1 => dxt::DxtFormat::Dxt1,
-            MipmapFormat::CompressedDxt3 => dxt::DxtFormat::Dxt3,
-            MipmapFormat::CompressedDxt5 => dxt::DxtFormat::Dxt5,
-            _ => {
-                return Ok(
-                    DecompressedTexMipmap::try_from(self).expect("expecting decompressed mipmap")
-                );
-            }
+        let Some(format) = self.dxt_format() else {
+            return Ok(
+                DecompressedTexMipmap::try_from(self).expect("expecting decompressed mipmap")
+            );
         };
 
-        debug!(?dxt_format, "decompressing dxt texture");
-
-        let data = transmute_vec_u8_to_vec_u32(self.data)?;
-        let data =
-            dxt::decompress_image(self.width as usize, self.height as usize, &data, dxt_format);
-
-        let format = MipmapFormat::Rgba8888;
+        let dxt = DxtTexMipmap {
+            width: self.width as u32,
+            height: self.height as u32,
+            data: self.data,
+            format,
+        };
 
         Ok(DecompressedTexMipmap {
             width: self.width as u32,
             height: self.height as u32,
-            data: MipmapData::Rgba8888(
-                ImageBuffer::from_raw(
-                    self.width as u32,
-                    self.height as u32,
-                    transmute_extra::transmute_vec_u32_to_vec_u8(data),
-                )
-                .ok_or(TexExtractError::Corrupt {
-                    about: "width and height of the texture read from file are to big:\
-                                    unable to create sufficient buffer"
-                        .to_string(),
-                })?,
-            ),
+            data: MipmapData::Rgba8888(dxt.decompress()?),
             condition_json: self.condition_json,
+            format: MipmapFormat::Rgba8888,
+        })
+    }
+
+    /// Format of the mipmap if it is `DXT` compressed
+    pub fn dxt_format(&self) -> Option<dxt::DxtFormat> {
+        match self.format {
+            MipmapFormat::CompressedDxt1 => Some(dxt::DxtFormat::Dxt1),
+            MipmapFormat::CompressedDxt3 => Some(dxt::DxtFormat::Dxt3),
+            MipmapFormat::CompressedDxt5 => Some(dxt::DxtFormat::Dxt5),
+            _ => None,
+        }
+    }
+
+    /// Decompresses lz4 compression (if applied), keeping `DXT` blocks untouched
+    ///
+    /// Returns [`None`] if the mipmap is not `DXT` compressed
+    pub fn into_dxt(mut self) -> Result<Option<DxtTexMipmap>, TexExtractError> {
+        let Some(format) = self.dxt_format() else {
+            return Ok(None);
+        };
+
+        if self.lz4_compressed {
+            decompress_lz4(&mut self.data, self.decompressed_bytes_count)?;
+        }
+
+        Ok(Some(DxtTexMipmap {
+            width: self.width as u32,
+            height: self.height as u32,
+            data: self.data,
             format,
+        }))
+    }
+}
+
+/// `DXT` compressed mipmap, which can be uploaded to the GPU as is.
+/// This should be constructed from [`TexMipmap`] via a call to [`TexMipmap::into_dxt`]
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct DxtTexMipmap {
+    pub width: u32,
+    pub height: u32,
+    /// Buffer of compressed 4x4 blocks
+    pub data: Vec<u8>,
+    pub format: dxt::DxtFormat,
+}
+
+impl DxtTexMipmap {
+    /// Checks that the buffer contains all blocks of the mipmap
+    pub fn is_complete(&self) -> bool {
+        self.data.len()
+            == self
+                .format
+                .compressed_size(self.width as usize, self.height as usize)
+    }
+
+    /// Decompresses `DXT` blocks into `RGBA8888` pixels
+    #[instrument(skip_all)]
+    pub fn decompress(self) -> Result<image::RgbaImage, TexExtractError> {
+        debug!(format = ?self.format, "decompressing dxt texture");
+
+        let data = transmute_vec_u8_to_vec_u32(self.data)?;
+        let data = dxt::decompress_image(
+            self.width as usize,
+            self.height as usize,
+            &data,
+            self.format,
+        );
+
+        ImageBuffer::from_raw(
+            self.width,
+            self.height,
+            transmute_extra::transmute_vec_u32_to_vec_u8(data),
+        )
+        .ok_or(TexExtractError::Corrupt {
+            about: "width and height of the texture read from file are to big:\
+                    unable to create sufficient buffer"
+                .to_string(),
         })
     }
 }
@@ -279,6 +341,27 @@ impl TexImage {
 
         Ok(DecompressedTexImage { mipmaps })
     }
+
+    /// Format of the image if all its mipmaps are `DXT` compressed with the same format
+    pub fn dxt_format(&self) -> Option<dxt::DxtFormat> {
+        let format = self.mipmaps.first()?.dxt_format()?;
+
+        self.mipmaps
+            .iter()
+            .all(|mipmap| mipmap.dxt_format() == Some(format))
+            .then_some(format)
+    }
+
+    /// Decompresses lz4 compression of mipmaps contained in `self`, keeping `DXT` blocks untouched
+    ///
+    /// Returns [`None`] if the image is not `DXT` compressed, see [`TexImage::dxt_format`]
+    pub fn into_dxt(self) -> Result<Option<Vec<DxtTexMipmap>>, TexExtractError> {
+        if self.dxt_format().is_none() {
+            return Ok(None);
+        }
+
+        self.mipmaps.into_iter().map(TexMipmap::into_dxt).collect()
+    }
 }
 
 /// Buffer of decompressed mipmaps. This should ultimately be constructed via a call to [`TexImage::decompress`]
@@ -315,6 +398,88 @@ pub struct TexGifFrameMeta {
     pub height: f32,
 }
 
+impl TexGifFrameMeta {
+    /// Time for which the frame should be shown
+    pub fn duration(&self) -> Duration {
+        const MICROS_PER_UNIT: f32 = 10_000.0;
+        Duration::from_micros((MICROS_PER_UNIT * self.frame_time).round().max(0.0) as u64)
+    }
+
+    /// Location of the frame in the sprite sheet image
+    pub fn rect(&self) -> TexGifFrameRect {
+        let width = if self.width != 0.0 {
+            self.width
+        } else {
+            self.height_x
+        };
+
+        let height = if self.height != 0.0 {
+            self.height
+        } else {
+            self.width_y
+        };
+
+        // Negative sizes encode the orientation of the frame in the sprite sheet
+        let rotation = match (width < 0.0, height < 0.0) {
+            (false, false) => FrameRotation::None,
+            (false, true) => FrameRotation::Clockwise90,
+            (true, true) => FrameRotation::Clockwise180,
+            (true, false) => FrameRotation::Clockwise270,
+        };
+
+        TexGifFrameRect {
+            x: self.x.min(self.x + width) as u32,
+            y: self.y.min(self.y + height) as u32,
+            width: width.abs() as u32,
+            height: height.abs() as u32,
+            rotation,
+        }
+    }
+}
+
+/// Rotation to apply to a frame cut from the sprite sheet
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FrameRotation {
+    #[default]
+    None,
+    Clockwise90,
+    Clockwise180,
+    Clockwise270,
+}
+
+/// Rectangle of a single gif frame in the sprite sheet, see [`TexGifFrameMeta::rect`]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TexGifFrameRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub rotation: FrameRotation,
+}
+
+impl TexGifFrameRect {
+    /// Cuts this frame out of the sprite sheet
+    ///
+    /// Returns [`None`] if the rectangle does not fit into the `sheet`
+    pub fn cut(self, sheet: &image::RgbaImage) -> Option<image::RgbaImage> {
+        if self.x.checked_add(self.width)? > sheet.width()
+            || self.y.checked_add(self.height)? > sheet.height()
+        {
+            return None;
+        }
+
+        let frame =
+            image::imageops::crop_imm(sheet, self.x, self.y, self.width, self.height).to_image();
+
+        Some(match self.rotation {
+            FrameRotation::None => frame,
+            FrameRotation::Clockwise90 => image::imageops::rotate90(&frame),
+            FrameRotation::Clockwise180 => image::imageops::rotate180(&frame),
+            FrameRotation::Clockwise270 => image::imageops::rotate270(&frame),
+        })
+    }
+}
+
 /// The function used to just get all the data from the `.tex` file without extra hassle.
 ///
 /// `src` must be have a structure of `.tex` file
@@ -372,6 +537,94 @@ mod tests {
     use std::fs::File;
     use std::io::{BufReader, BufWriter};
 
+    #[test]
+    fn mipmap_data_into_rgba8() {
+        let gray = image::GrayImage::from_raw(2, 1, vec![10, 200]).unwrap();
+        let rgba = MipmapData::R8(gray).into_rgba8().unwrap();
+        assert_eq!(rgba.as_raw(), &[10, 10, 10, 255, 200, 200, 200, 255]);
+
+        let gray_alpha = image::GrayAlphaImage::from_raw(1, 1, vec![30, 40]).unwrap();
+        let rgba = MipmapData::Rg88(gray_alpha).into_rgba8().unwrap();
+        assert_eq!(rgba.as_raw(), &[30, 30, 30, 40]);
+
+        assert!(MipmapData::Raw(vec![1, 2, 3]).into_rgba8().is_err());
+    }
+
+    #[test]
+    fn dxt_mipmap_keeps_blocks() {
+        // single white 4x4 block
+        let block = vec![0xFF, 0xFF, 0, 0, 0, 0, 0, 0];
+
+        let mipmap = TexMipmap {
+            width: 4,
+            height: 4,
+            data: block.clone(),
+            format: MipmapFormat::CompressedDxt1,
+            ..Default::default()
+        };
+
+        let image = TexImage {
+            mipmaps: vec![mipmap.clone()],
+        };
+        assert_eq!(image.dxt_format(), Some(dxt::DxtFormat::Dxt1));
+
+        let dxt = image.into_dxt().unwrap().unwrap().remove(0);
+        assert!(dxt.is_complete());
+        assert_eq!(dxt.data, block);
+
+        let rgba = dxt.decompress().unwrap();
+        assert!(rgba.pixels().all(|pixel| pixel.0 == [255; 4]));
+
+        let rgba = TexMipmap {
+            format: MipmapFormat::Rgba8888,
+            data: vec![0; 4],
+            width: 1,
+            height: 1,
+            ..Default::default()
+        };
+        assert_eq!(rgba.clone().into_dxt().unwrap(), None);
+        assert_eq!(
+            TexImage {
+                mipmaps: vec![mipmap, rgba]
+            }
+            .dxt_format(),
+            None
+        );
+    }
+
+    #[test]
+    fn gif_frame_rect() {
+        let meta = TexGifFrameMeta {
+            frame_time: 5.0,
+            x: 64.0,
+            y: 0.0,
+            width: -64.0,
+            height: 32.0,
+            ..Default::default()
+        };
+
+        assert_eq!(meta.duration(), Duration::from_millis(50));
+
+        let rect = meta.rect();
+        assert_eq!((rect.x, rect.y, rect.width, rect.height), (0, 0, 64, 32));
+        assert_eq!(rect.rotation, FrameRotation::Clockwise270);
+
+        let sheet = image::RgbaImage::new(64, 32);
+        assert_eq!(rect.cut(&sheet).unwrap().dimensions(), (32, 64));
+
+        let meta = TexGifFrameMeta {
+            width: 0.0,
+            height_x: 16.0,
+            height: 0.0,
+            width_y: 16.0,
+            x: 60.0,
+            ..meta
+        };
+
+        assert_eq!(meta.rect().width, 16);
+        assert!(meta.rect().cut(&sheet).is_none());
+    }
+
     #[test]
     #[ignore = "no input files provided"]
     fn test_stages_up_to_images() {
@@ -516,4 +769,109 @@ mod tests {
             }
         }
     }
+
+    fn gradient(width: u32, height: u32) -> image::RgbaImage {
+        image::RgbaImage::from_fn(width, height, |x, y| {
+            image::Rgba([(x * 16) as u8, (y * 16) as u8, ((x + y) * 8) as u8, 255])
+        })
+    }
+
+    #[test]
+    fn encode_and_extract_rgba() {
+        let image = gradient(12, 8);
+
+        for lz4_compressed in [false, true] {
+            let mut tex = Vec::new();
+            TexWriter::new(TexFormat::Rgba8888)
+                .with_lz4_compression(lz4_compressed)
+                .write_image(&image, &mut tex)
+                .unwrap();
+
+            let TexExtractData::Image(images) = extract_data(&mut tex.as_slice()).unwrap() else {
+                panic!("expected an image");
+            };
+
+            let mipmaps = &images[0].mipmaps;
+            let sizes = mipmaps
+                .iter()
+                .map(|mipmap| (mipmap.width, mipmap.height))
+                .collect::<Vec<_>>();
+
+            assert_eq!(sizes, [(12, 8), (6, 4), (3, 2), (1, 1)]);
+            assert_eq!(mipmaps[0].data, MipmapData::Rgba8888(image.clone()));
+        }
+    }
+
+    #[test]
+    fn encode_and_extract_dxt() {
+        // colors of every block lie on a line, so that they survive the compression
+        let image = image::RgbaImage::from_fn(16, 16, |x, _| {
+            image::Rgba([(x * 16) as u8, (x * 8) as u8, 255 - (x * 16) as u8, 255])
+        });
+
+        let mut tex = Vec::new();
+        TexWriter::new(TexFormat::Dxt5)
+            .with_lz4_compression(true)
+            .write_image(&image, &mut tex)
+            .unwrap();
+
+        let mut src = tex.as_slice();
+        let mut reader = TexReader::new(&mut src)
+            .read_header()
+            .unwrap()
+            .read_image_container_meta()
+            .unwrap()
+            .read_images()
+            .unwrap();
+
+        let image_data = reader.take_images().unwrap().remove(0);
+        assert_eq!(image_data.dxt_format(), Some(dxt::DxtFormat::Dxt5));
+
+        let mipmaps = image_data.into_dxt().unwrap().unwrap();
+        assert_eq!(mipmaps.len(), 5);
+        assert!(mipmaps.iter().all(DxtTexMipmap::is_complete));
+
+        let decoded = mipmaps.into_iter().next().unwrap().decompress().unwrap();
+
+        for (expected, actual) in image.pixels().zip(decoded.pixels()) {
+            for (&a, &b) in expected.0.iter().zip(&actual.0) {
+                assert!(a.abs_diff(b) <= 16, "{expected:?} != {actual:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn encode_and_extract_gif() {
+        let frames = (0..3)
+            .map(|i| TexEncodeFrame {
+                image: image::RgbaImage::from_pixel(4, 2, image::Rgba([i * 80, 0, 0, 255])),
+                duration: Duration::from_millis(40 * (u64::from(i) + 1)),
+            })
+            .collect::<Vec<_>>();
+
+        let mut tex = Vec::new();
+        TexWriter::new(TexFormat::Rgba8888)
+            .with_mipmaps(false)
+            .write_gif(&frames, &mut tex)
+            .unwrap();
+
+        let TexExtractData::Gif {
+            frames: images,
+            frames_meta,
+        } = extract_data(&mut tex.as_slice()).unwrap()
+        else {
+            panic!("expected a gif");
+        };
+
+        assert_eq!(images.len(), 3);
+        assert_eq!(frames_meta.len(), 3);
+
+        for (i, (frame, meta)) in frames.iter().zip(&frames_meta).enumerate() {
+            assert_eq!(meta.image_id, i as i32);
+            assert_eq!(meta.duration(), frame.duration);
+
+            let sheet = images[i].mipmaps[0].data.clone().into_rgba8().unwrap();
+            assert_eq!(meta.rect().cut(&sheet).unwrap(), frame.image);
+        }
+    }
 }
@@ -0,0 +1,333 @@
+//! Encoding of ordinary pictures into `.tex` files
+//!
+//! The output has the same structure as the files read by [`TexReader`]:
+//! the image container is written with the version `TEXB0003` and raw pixels
+//! (no embedded [`FreeImageFormat`]), and the gif container with the version `TEXS0003`
+
+use super::*;
+use dxt::CompressionQuality;
+use image::{DynamicImage, RgbaImage, imageops::FilterType};
+use safe_transmute::transmute_one_to_bytes;
+use std::io::Write;
+use tracing::{debug, instrument};
+
+#[derive(Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+struct Writer<'a, T: Write>(&'a mut T);
+
+impl<T: Write> Writer<'_, T> {
+    fn write_int(&mut self, value: i32) -> Result<(), TexExtractError> {
+        self.0.write_all(transmute_one_to_bytes(&value))?;
+
+        Ok(())
+    }
+
+    fn write_float(&mut self, value: f32) -> Result<(), TexExtractError> {
+        self.0.write_all(transmute_one_to_bytes(&value))?;
+
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), TexExtractError> {
+        self.0.write_all(buf)?;
+
+        Ok(())
+    }
+
+    /// Writes 8-byte magic followed by the padding byte
+    fn write_magic(&mut self, magic: &str) -> Result<(), TexExtractError> {
+        debug_assert_eq!(magic.len(), 8);
+
+        self.write_bytes(magic.as_bytes())?;
+        self.write_bytes(&[0])
+    }
+
+    fn write_len(&mut self, len: usize) -> Result<(), TexExtractError> {
+        self.write_int(i32::try_from(len).map_err(|_| TexExtractError::TooLarge)?)
+    }
+}
+
+/// Single frame of an animated texture, see [`TexWriter::write_gif`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TexEncodeFrame {
+    pub image: RgbaImage,
+    /// Time for which the frame should be shown
+    pub duration: Duration,
+}
+
+/// Encoder of `RGBA8888` pictures into `.tex` files readable by [`TexReader`]
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct TexWriter {
+    format: TexFormat,
+    flags: TexFlags,
+    generate_mipmaps: bool,
+    lz4_compressed: bool,
+    quality: CompressionQuality,
+}
+
+impl TexWriter {
+    /// Constructs a writer that stores pixels in the given `format`
+    /// with generated mipmaps and without lz4 compression
+    pub fn new(format: TexFormat) -> Self {
+        Self {
+            format,
+            flags: TexFlags::NONE,
+            generate_mipmaps: true,
+            lz4_compressed: false,
+            quality: CompressionQuality::default(),
+        }
+    }
+
+    /// Sets the flags stored in the header. [`TexFlags::IS_GIF`] is managed by the writer itself
+    pub fn with_flags(mut self, flags: TexFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Whether to generate the full mipmap chain down to `1x1` or to store only the full size image
+    pub fn with_mipmaps(mut self, generate_mipmaps: bool) -> Self {
+        self.generate_mipmaps = generate_mipmaps;
+        self
+    }
+
+    /// Whether to compress mipmap data with lz4
+    pub fn with_lz4_compression(mut self, lz4_compressed: bool) -> Self {
+        self.lz4_compressed = lz4_compressed;
+        self
+    }
+
+    /// Quality of the compression for `DXT` formats
+    pub fn with_quality(mut self, quality: CompressionQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn format(&self) -> TexFormat {
+        self.format
+    }
+
+    /// Writes a single image into `dst`
+    #[instrument(skip_all)]
+    pub fn write_image(
+        &self,
+        image: &RgbaImage,
+        dst: &mut impl Write,
+    ) -> Result<(), TexExtractError> {
+        let mut writer = Writer(dst);
+
+        self.write_header(
+            &mut writer,
+            self.flags - TexFlags::IS_GIF,
+            image.width(),
+            image.height(),
+        )?;
+        self.write_images(&mut writer, std::iter::once(image))
+    }
+
+    /// Writes an animated texture into `dst`. Every frame is stored as a separate image
+    /// referenced by its frame meta
+    #[instrument(skip_all)]
+    pub fn write_gif(
+        &self,
+        frames: &[TexEncodeFrame],
+        dst: &mut impl Write,
+    ) -> Result<(), TexExtractError> {
+        if frames.is_empty() {
+            return Err(TexExtractError::Corrupt {
+                about: "animated texture has to contain at least one frame".to_string(),
+            });
+        }
+
+        let width = frames
+            .iter()
+            .map(|frame| frame.image.width())
+            .max()
+            .unwrap();
+        let height = frames
+            .iter()
+            .map(|frame| frame.image.height())
+            .max()
+            .unwrap();
+
+        let mut writer = Writer(dst);
+
+        self.write_header(&mut writer, self.flags | TexFlags::IS_GIF, width, height)?;
+        self.write_images(&mut writer, frames.iter().map(|frame| &frame.image))?;
+
+        writer.write_magic("TEXS0003")?;
+        writer.write_len(frames.len())?;
+        writer.write_len(width as usize)?;
+        writer.write_len(height as usize)?;
+
+        for (image_id, frame) in frames.iter().enumerate() {
+            let meta = TexGifFrameMeta {
+                image_id: image_id as i32,
+                frame_time: frame_time(frame.duration),
+                x: 0.0,
+                y: 0.0,
+                width: frame.image.width() as f32,
+                width_y: 0.0,
+                height_x: 0.0,
+                height: frame.image.height() as f32,
+            };
+
+            writer.write_int(meta.image_id)?;
+            writer.write_float(meta.frame_time)?;
+
+            for value in [
+                meta.x,
+                meta.y,
+                meta.width,
+                meta.width_y,
+                meta.height_x,
+                meta.height,
+            ] {
+                writer.write_float(value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_header<T: Write>(
+        &self,
+        writer: &mut Writer<'_, T>,
+        flags: TexFlags,
+        width: u32,
+        height: u32,
+    ) -> Result<(), TexExtractError> {
+        debug!(format = ?self.format, ?flags, width, height, "encoding image");
+
+        writer.write_magic("TEXV0005")?;
+        writer.write_magic("TEXI0001")?;
+
+        writer.write_int(self.format as i32)?;
+        writer.write_int(i32::from(flags.bits()))?;
+
+        // texture and image sizes
+        for _ in 0..2 {
+            writer.write_len(width as usize)?;
+            writer.write_len(height as usize)?;
+        }
+
+        // unk_int0
+        writer.write_int(0)
+    }
+
+    fn write_images<'i, T: Write>(
+        &self,
+        writer: &mut Writer<'_, T>,
+        images: impl ExactSizeIterator<Item = &'i RgbaImage>,
+    ) -> Result<(), TexExtractError> {
+        writer.write_magic("TEXB0003")?;
+        writer.write_len(images.len())?;
+        writer.write_int(FreeImageFormat::Unknown as i32)?;
+
+        for image in images {
+            let mipmaps = if self.generate_mipmaps {
+                generate_mipmaps(image)
+            } else {
+                vec![image.clone()]
+            };
+
+            writer.write_len(mipmaps.len())?;
+
+            for mipmap in mipmaps {
+                self.write_mipmap(writer, mipmap)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_mipmap<T: Write>(
+        &self,
+        writer: &mut Writer<'_, T>,
+        mipmap: RgbaImage,
+    ) -> Result<(), TexExtractError> {
+        let (width, height) = mipmap.dimensions();
+        let data = self.encode_pixels(mipmap)?;
+
+        writer.write_len(width as usize)?;
+        writer.write_len(height as usize)?;
+
+        if self.lz4_compressed {
+            let compressed = lz4::block::compress(&data, None, false)?;
+
+            writer.write_int(1)?;
+            writer.write_len(data.len())?;
+            writer.write_len(compressed.len())?;
+            writer.write_bytes(&compressed)
+        } else {
+            writer.write_int(0)?;
+            writer.write_len(data.len())?;
+            writer.write_len(data.len())?;
+            writer.write_bytes(&data)
+        }
+    }
+
+    /// Converts `RGBA8888` pixels into the bytes of the writer's format
+    fn encode_pixels(&self, image: RgbaImage) -> Result<Vec<u8>, TexExtractError> {
+        let format = match self.format {
+            TexFormat::Rgba8888 => return Ok(image.into_raw()),
+            TexFormat::Rg88 => {
+                return Ok(DynamicImage::ImageRgba8(image)
+                    .into_luma_alpha8()
+                    .into_raw());
+            }
+            TexFormat::R8 => return Ok(DynamicImage::ImageRgba8(image).into_luma8().into_raw()),
+            TexFormat::Dxt1 => dxt::DxtFormat::Dxt1,
+            TexFormat::Dxt3 => dxt::DxtFormat::Dxt3,
+            TexFormat::Dxt5 => dxt::DxtFormat::Dxt5,
+        };
+
+        let (width, height) = image.dimensions();
+        let pixels = transmute_vec_u8_to_vec_u32(image.into_raw())?;
+
+        let blocks = dxt::compress_image(
+            width as usize,
+            height as usize,
+            &pixels,
+            format,
+            self.quality,
+        );
+
+        Ok(transmute_extra::transmute_vec_u32_to_vec_u8(blocks))
+    }
+}
+
+impl Default for TexWriter {
+    fn default() -> Self {
+        Self::new(TexFormat::default())
+    }
+}
+
+/// Builds the mipmap chain starting from the full size `image` down to `1x1`
+fn generate_mipmaps(image: &RgbaImage) -> Vec<RgbaImage> {
+    let mut mipmaps = vec![image.clone()];
+
+    loop {
+        let last = mipmaps.last().unwrap();
+        let (width, height) = last.dimensions();
+
+        if width <= 1 && height <= 1 {
+            break;
+        }
+
+        let next = image::imageops::resize(
+            last,
+            (width / 2).max(1),
+            (height / 2).max(1),
+            FilterType::Triangle,
+        );
+
+        mipmaps.push(next);
+    }
+
+    mipmaps
+}
+
+/// Converts `duration` into units of 10ms, see [`TexGifFrameMeta::frame_time`]
+fn frame_time(duration: Duration) -> f32 {
+    const MICROS_PER_UNIT: f32 = 10_000.0;
+    duration.as_micros() as f32 / MICROS_PER_UNIT
+}
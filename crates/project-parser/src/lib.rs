@@ -1,10 +1,17 @@
 use std::io::{self, Read};
 use std::str::FromStr;
-use std::{env::home_dir, fs::File, path::PathBuf};
+use std::{
+    env::home_dir,
+    fs::File,
+    path::{Path, PathBuf},
+};
 
 use chumsky::Parser;
 
 pub mod library_folders;
+pub mod project;
+pub mod scene;
+pub mod workshop;
 
 const WALPAPER_ENGINE_STEAM_ID: usize = 431960;
 
@@ -25,7 +32,35 @@ pub enum LocateError {
 
 pub type LocateResult<T> = Result<T, LocateError>;
 
+/// Paths to all steam library folders listed in `libraryfolders.vdf`
+pub fn locate_library_folders() -> LocateResult<Vec<PathBuf>> {
+    Ok(read_library_folders()?
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect())
+}
+
 pub fn locate_we_assets() -> LocateResult<PathBuf> {
+    let we_installation_path = read_library_folders()?
+        .into_iter()
+        .find_map(|(path, apps_ids)| apps_ids.contains(&WALPAPER_ENGINE_STEAM_ID).then_some(path));
+
+    let Some(we_installation_path) = we_installation_path else {
+        return Err(LocateError::NotFound);
+    };
+
+    Ok(workshop_content_directory(&we_installation_path))
+}
+
+/// Directory with Wallpaper Engine workshop items in the given steam library folder
+pub fn workshop_content_directory(library_folder: &Path) -> PathBuf {
+    library_folder.join(format!(
+        "steamapps/workshop/content/{WALPAPER_ENGINE_STEAM_ID}"
+    ))
+}
+
+/// Reads paths and installed app ids of steam library folders
+fn read_library_folders() -> LocateResult<Vec<(PathBuf, Vec<usize>)>> {
     let library_folders = home_dir()
         .ok_or(LocateError::HomeDirError)?
         .join(".steam/steam/steamapps/libraryfolders.vdf");
@@ -41,23 +76,15 @@ pub fn locate_we_assets() -> LocateResult<PathBuf> {
         .into_result()
         .map_err(|_| LocateError::ParseError)?;
 
-    let mut we_installation_path = None;
-
-    for library_folder in library_folders.iter() {
-        for app_id in library_folder.apps_ids.iter() {
-            if app_id == &WALPAPER_ENGINE_STEAM_ID {
-                we_installation_path = Some(&library_folder.path);
-            }
-        }
-    }
-
-    let Some(we_installation_path) = we_installation_path else {
-        return Err(LocateError::NotFound);
-    };
-
-    Ok(PathBuf::from_str(we_installation_path)
-        .expect("infallible")
-        .join("steamapps/workshop/content/431960"))
+    Ok(library_folders
+        .into_iter()
+        .map(|folder| {
+            (
+                PathBuf::from_str(&folder.path).expect("infallible"),
+                folder.apps_ids,
+            )
+        })
+        .collect())
 }
 
 #[cfg(test)]
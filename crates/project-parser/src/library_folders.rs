@@ -152,7 +152,7 @@ mod tests {
 
         let res = apps().parse(&input).unwrap();
 
-        let gt = vec![];
+        let gt: Vec<usize> = vec![];
 
         assert_eq!(res, gt);
     }
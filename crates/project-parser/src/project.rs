@@ -0,0 +1,169 @@
+//! Wallpaper Engine `project.json` description
+
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub const PROJECT_FILE_NAME: &str = "project.json";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProjectError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// Kind of the wallpaper stored in the project
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectType {
+    #[serde(alias = "Scene")]
+    Scene,
+    #[serde(alias = "Video")]
+    Video,
+    #[serde(alias = "Web")]
+    Web,
+    #[serde(alias = "Application")]
+    Application,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// Parsed `project.json` file
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Project {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, rename = "type")]
+    pub ty: ProjectType,
+    /// Main file of the project relative to the project directory
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+    /// Preview image relative to the project directory
+    #[serde(default)]
+    pub preview: Option<PathBuf>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub general: ProjectGeneral,
+}
+
+/// The `general` section of the `project.json`
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct ProjectGeneral {
+    /// User-configurable properties by their names
+    #[serde(default)]
+    pub properties: BTreeMap<String, ProjectProperty>,
+}
+
+/// Kind of the user-configurable property
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectPropertyType {
+    Bool,
+    Color,
+    Slider,
+    Combo,
+    #[serde(rename = "textinput")]
+    TextInput,
+    Text,
+    File,
+    Directory,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// User-configurable property of the project, e.g. the scheme color
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct ProjectProperty {
+    /// Label shown to the user, can be a localization key
+    #[serde(default)]
+    pub text: String,
+    #[serde(default, rename = "type")]
+    pub ty: ProjectPropertyType,
+    /// Position of the property in the settings list
+    #[serde(default)]
+    pub order: Option<i64>,
+    /// Current value which format depends on [`ProjectProperty::ty`]
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+impl Project {
+    pub fn from_json(json: &str) -> Result<Self, ProjectError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Reads `project.json` from the project directory
+    pub fn read(directory: &Path) -> Result<Self, ProjectError> {
+        let json = fs::read_to_string(directory.join(PROJECT_FILE_NAME))?;
+        Self::from_json(&json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scene_project() {
+        let project = Project::read(Path::new("tests/scene_project")).unwrap();
+
+        assert_eq!(project.title, "Test scene");
+        assert_eq!(project.ty, ProjectType::Scene);
+        assert_eq!(project.file.as_deref(), Some(Path::new("scene.json")));
+        assert_eq!(project.preview.as_deref(), Some(Path::new("preview.jpg")));
+        assert_eq!(project.tags, ["Anime"]);
+    }
+
+    #[test]
+    fn parse_general_properties() {
+        let project = Project::from_json(
+            r#"{
+                "title": "Colors",
+                "type": "scene",
+                "general": {
+                    "properties": {
+                        "schemecolor": {
+                            "order": 0,
+                            "text": "ui_browse_properties_scheme_color",
+                            "type": "color",
+                            "value": "0.5 0.25 1"
+                        },
+                        "speed": { "text": "Speed", "type": "slider", "value": 2.5 },
+                        "custom": { "type": "unheard", "value": null }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let properties = &project.general.properties;
+        assert_eq!(properties.len(), 3);
+
+        let color = &properties["schemecolor"];
+        assert_eq!(color.ty, ProjectPropertyType::Color);
+        assert_eq!(color.order, Some(0));
+        assert_eq!(color.value, "0.5 0.25 1");
+
+        assert_eq!(properties["speed"].ty, ProjectPropertyType::Slider);
+        assert_eq!(properties["speed"].value, 2.5);
+        assert_eq!(properties["custom"].ty, ProjectPropertyType::Unknown);
+    }
+
+    #[test]
+    fn parse_capitalized_type() {
+        let project = Project::from_json(r#"{ "type": "Video", "file": "a.mp4" }"#).unwrap();
+        assert_eq!(project.ty, ProjectType::Video);
+
+        let project = Project::from_json(r#"{ "type": "preset" }"#).unwrap();
+        assert_eq!(project.ty, ProjectType::Unknown);
+    }
+}
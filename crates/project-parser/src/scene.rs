@@ -0,0 +1,280 @@
+//! Wallpaper Engine `scene.json` description
+//!
+//! Only the part of the format needed to lay out image layers is modeled.
+//! Vectors are stored by Wallpaper Engine as space separated strings
+//! (e.g. `"960 540 0"`) and almost every property can be either a plain value
+//! or an object `{ "user": ..., "value": ... }` bound to a user property.
+
+use crate::project::ProjectError;
+use serde::{Deserialize, Deserializer, de};
+use std::{fmt, path::PathBuf};
+
+/// Vector stored as a string of space separated numbers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vector<const N: usize>(pub [f32; N]);
+
+pub type Vector2 = Vector<2>;
+pub type Vector3 = Vector<3>;
+
+impl<const N: usize> Vector<N> {
+    pub const ZERO: Self = Self([0.0; N]);
+    pub const ONE: Self = Self([1.0; N]);
+
+    pub const fn splat(value: f32) -> Self {
+        Self([value; N])
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Vector<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct VectorVisitor<const N: usize>;
+
+        impl<const N: usize> de::Visitor<'_> for VectorVisitor<N> {
+            type Value = Vector<N>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "a string of {N} space separated numbers")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                let mut result = [0.0; N];
+                let mut components = value.split_whitespace();
+
+                for component in &mut result {
+                    let Some(text) = components.next() else {
+                        return Err(E::invalid_length(N, &self));
+                    };
+
+                    *component = text.parse().map_err(E::custom)?;
+                }
+
+                Ok(Vector(result))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+                Ok(Vector::splat(value as f32))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(Vector::splat(value as f32))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(Vector::splat(value as f32))
+            }
+        }
+
+        deserializer.deserialize_any(VectorVisitor)
+    }
+}
+
+/// Value that may be bound to a user property
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Property<T> {
+    Value(T),
+    User {
+        value: T,
+        #[serde(default)]
+        user: Option<serde_json::Value>,
+    },
+}
+
+impl<T> Property<T> {
+    pub const fn value(&self) -> &T {
+        match self {
+            Self::Value(value) | Self::User { value, .. } => value,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct OrthogonalProjection {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct SceneGeneral {
+    #[serde(default)]
+    pub clearcolor: Option<Property<Vector3>>,
+    #[serde(default)]
+    pub orthogonalprojection: Option<OrthogonalProjection>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub struct SceneCamera {
+    pub center: Vector3,
+    pub eye: Vector3,
+    pub up: Vector3,
+}
+
+/// Single object of the scene
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SceneObject {
+    pub id: u32,
+    #[serde(default)]
+    pub name: String,
+    /// Path to the model json of an image layer
+    #[serde(default)]
+    pub image: Option<PathBuf>,
+    /// Position in scene pixels, relative to the parent if there is one
+    #[serde(default)]
+    pub origin: Option<Property<Vector3>>,
+    #[serde(default)]
+    pub scale: Option<Property<Vector3>>,
+    /// Rotation in radians
+    #[serde(default)]
+    pub angles: Option<Property<Vector3>>,
+    /// Size in scene pixels
+    #[serde(default)]
+    pub size: Option<Property<Vector2>>,
+    #[serde(default)]
+    pub visible: Option<Property<bool>>,
+    #[serde(default)]
+    pub parent: Option<u32>,
+}
+
+impl SceneObject {
+    pub fn origin(&self) -> Vector3 {
+        self.origin.as_ref().map_or(Vector::ZERO, |p| *p.value())
+    }
+
+    pub fn scale(&self) -> Vector3 {
+        self.scale.as_ref().map_or(Vector::ONE, |p| *p.value())
+    }
+
+    pub fn angles(&self) -> Vector3 {
+        self.angles.as_ref().map_or(Vector::ZERO, |p| *p.value())
+    }
+
+    pub fn size(&self) -> Option<Vector2> {
+        self.size.as_ref().map(|p| *p.value())
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible.as_ref().is_none_or(|p| *p.value())
+    }
+}
+
+/// Parsed `scene.json` file
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Scene {
+    #[serde(default)]
+    pub camera: Option<SceneCamera>,
+    #[serde(default)]
+    pub general: SceneGeneral,
+    #[serde(default)]
+    pub objects: Vec<SceneObject>,
+}
+
+impl Scene {
+    pub fn from_json(json: &str) -> Result<Self, ProjectError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// Model json referenced by [`SceneObject::image`]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Model {
+    /// Path to the material json
+    pub material: PathBuf,
+    #[serde(default)]
+    pub fullscreen: bool,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+}
+
+impl Model {
+    pub fn from_json(json: &str) -> Result<Self, ProjectError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct MaterialPass {
+    #[serde(default)]
+    pub shader: String,
+    /// Texture names, `null` marks an unused slot
+    #[serde(default)]
+    pub textures: Vec<Option<String>>,
+}
+
+/// Material json referenced by [`Model::material`]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Material {
+    #[serde(default)]
+    pub passes: Vec<MaterialPass>,
+}
+
+impl Material {
+    pub fn from_json(json: &str) -> Result<Self, ProjectError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Name of the base texture of the first pass
+    pub fn base_texture(&self) -> Option<&str> {
+        self.passes.first()?.textures.first()?.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn parse_vector() {
+        let vector: Vector3 = serde_json::from_str(r#""960 540.5 -1""#).unwrap();
+        assert_eq!(vector, Vector([960.0, 540.5, -1.0]));
+
+        let vector: Vector2 = serde_json::from_str("2").unwrap();
+        assert_eq!(vector, Vector([2.0, 2.0]));
+
+        assert!(serde_json::from_str::<Vector3>(r#""1 2""#).is_err());
+    }
+
+    #[test]
+    fn parse_user_property() {
+        let property: Property<bool> =
+            serde_json::from_str(r#"{ "user": "showlogo", "value": false }"#).unwrap();
+
+        assert!(!property.value());
+    }
+
+    #[test]
+    fn parse_scene() {
+        let json = fs::read_to_string("tests/scene_project/scene.json").unwrap();
+        let scene = Scene::from_json(&json).unwrap();
+
+        let projection = scene.general.orthogonalprojection.unwrap();
+        assert_eq!((projection.width, projection.height), (1920, 1080));
+        assert_eq!(scene.objects.len(), 3);
+
+        let background = &scene.objects[0];
+        assert_eq!(
+            background.image.as_deref(),
+            Some("models/background.json".as_ref())
+        );
+        assert_eq!(background.origin(), Vector([960.0, 540.0, 0.0]));
+        assert_eq!(background.size(), Some(Vector([1920.0, 1080.0])));
+
+        let logo = &scene.objects[1];
+        assert!(!logo.is_visible());
+        assert_eq!(logo.scale(), Vector([0.5, 0.5, 1.0]));
+
+        let child = &scene.objects[2];
+        assert_eq!(child.parent, Some(background.id));
+        assert!(child.is_visible());
+    }
+
+    #[test]
+    fn parse_material() {
+        let json = fs::read_to_string("tests/scene_project/materials/background.json").unwrap();
+        let material = Material::from_json(&json).unwrap();
+
+        assert_eq!(material.base_texture(), Some("background"));
+    }
+}
@@ -0,0 +1,170 @@
+//! Enumeration of installed Wallpaper Engine workshop items
+
+use crate::{
+    LocateResult, locate_library_folders,
+    project::{Project, ProjectError},
+    workshop_content_directory,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    vec,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum WorkshopError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("failed to read project '{directory}': {source}")]
+    Project {
+        directory: PathBuf,
+        source: ProjectError,
+    },
+}
+
+/// Installed workshop item
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkshopItem {
+    /// Workshop id which is the name of the item directory
+    pub id: u64,
+    /// Project directory of the item
+    pub directory: PathBuf,
+    pub project: Project,
+}
+
+impl WorkshopItem {
+    /// Reads `project.json` of the item with the given id from its directory
+    pub fn read(id: u64, directory: PathBuf) -> Result<Self, WorkshopError> {
+        match Project::read(&directory) {
+            Ok(project) => Ok(Self {
+                id,
+                directory,
+                project,
+            }),
+            Err(source) => Err(WorkshopError::Project { directory, source }),
+        }
+    }
+}
+
+/// Iterator over workshop items in the workshop content directories
+///
+/// Items of every directory are yielded in the order of their ids. Directories
+/// which are not workshop items (their names are not numbers) are skipped
+#[derive(Debug)]
+pub struct WorkshopItems {
+    content_directories: vec::IntoIter<PathBuf>,
+    items: vec::IntoIter<(u64, PathBuf)>,
+}
+
+impl WorkshopItems {
+    /// Iterates over items of the given workshop content directories,
+    /// see [`workshop_content_directory`](crate::workshop_content_directory)
+    pub fn new(content_directories: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            content_directories: content_directories
+                .into_iter()
+                .collect::<Vec<_>>()
+                .into_iter(),
+            items: Vec::new().into_iter(),
+        }
+    }
+
+    /// Lists item directories of the content directory sorted by their ids
+    fn list_items(content_directory: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+        let mut items = Vec::new();
+
+        for entry in fs::read_dir(content_directory)? {
+            let path = entry?.path();
+
+            if let Some(id) = workshop_id(&path)
+                && path.is_dir()
+            {
+                items.push((id, path));
+            }
+        }
+
+        items.sort_unstable();
+
+        Ok(items)
+    }
+}
+
+impl Iterator for WorkshopItems {
+    type Item = Result<WorkshopItem, WorkshopError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((id, directory)) = self.items.next() {
+                return Some(WorkshopItem::read(id, directory));
+            }
+
+            let content_directory = self.content_directories.next()?;
+
+            match Self::list_items(&content_directory) {
+                Ok(items) => self.items = items.into_iter(),
+                // NOTE: libraries without workshop items have no content directory
+                Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+                Err(error) => return Some(Err(error.into())),
+            }
+        }
+    }
+}
+
+/// Iterates over workshop items installed in every steam library folder
+pub fn workshop_items() -> LocateResult<WorkshopItems> {
+    let content_directories = locate_library_folders()?
+        .iter()
+        .map(|folder| workshop_content_directory(folder))
+        .collect::<Vec<_>>();
+
+    Ok(WorkshopItems::new(content_directories))
+}
+
+/// Workshop id of the item given its directory
+fn workshop_id(directory: &Path) -> Option<u64> {
+    directory.file_name()?.to_str()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iterate_items() {
+        let directory = tempfile::tempdir().unwrap();
+        let content_directory = directory.path();
+
+        let write_project = |id: &str, json: &str| {
+            let directory = content_directory.join(id);
+            fs::create_dir_all(&directory).unwrap();
+            fs::write(directory.join("project.json"), json).unwrap();
+        };
+
+        write_project(
+            "200",
+            r#"{ "title": "Video", "type": "video", "file": "a.mp4" }"#,
+        );
+        write_project("100", r#"{ "title": "Scene", "type": "scene" }"#);
+        write_project("300", "not a json");
+        write_project("not-an-item", "{}");
+
+        let items = WorkshopItems::new([
+            content_directory.to_owned(),
+            content_directory.join("missing"),
+        ])
+        .collect::<Vec<_>>();
+
+        assert_eq!(items.len(), 3);
+
+        let first = items[0].as_ref().unwrap();
+        assert_eq!(first.id, 100);
+        assert_eq!(first.project.title, "Scene");
+
+        let second = items[1].as_ref().unwrap();
+        assert_eq!(second.id, 200);
+        assert_eq!(second.directory, content_directory.join("200"));
+
+        assert!(matches!(items[2], Err(WorkshopError::Project { .. })));
+    }
+}
@@ -9,6 +9,9 @@ pub mod generated {
         "wayland-protocols/stable/xdg-shell/xdg-shell.xml",
         "wayland-protocols/stable/viewporter/viewporter.xml",
         "wayland-protocols/wlr-protocols/unstable/wlr-layer-shell-unstable-v1.xml",
+        "wayland-protocols/staging/ext-idle-notify/ext-idle-notify-v1.xml",
+        "wayland-protocols/staging/fractional-scale/fractional-scale-v1.xml",
+        "wayland-protocols/unstable/xdg-output/xdg-output-unstable-v1.xml",
     ]);
 }
 
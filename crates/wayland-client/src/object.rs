@@ -92,6 +92,10 @@ impl InterfaceMessageArgument {
         match self.object_type {
             WlObjectType::Shm => const { NonZeroU32::new(1).unwrap() },
             WlObjectType::LayerShell => const { NonZeroU32::new(4).unwrap() },
+            WlObjectType::ExtIdleNotifierV1 => const { NonZeroU32::new(1).unwrap() },
+            WlObjectType::WpFractionalScaleManagerV1 => const { NonZeroU32::new(1).unwrap() },
+            WlObjectType::WpViewporter => const { NonZeroU32::new(1).unwrap() },
+            WlObjectType::ZxdgOutputManagerV1 => const { NonZeroU32::new(1).unwrap() },
             _ => self.version(),
         }
     }
@@ -21,4 +21,10 @@ pub mod protocol {
     include_wl_interfaces!(
         "wayland-protocols/wlr-protocols/unstable/wlr-layer-shell-unstable-v1.xml"
     );
+
+    include_wl_interfaces!("wayland-protocols/staging/ext-idle-notify/ext-idle-notify-v1.xml");
+
+    include_wl_interfaces!("wayland-protocols/staging/fractional-scale/fractional-scale-v1.xml");
+
+    include_wl_interfaces!("wayland-protocols/unstable/xdg-output/xdg-output-unstable-v1.xml");
 }
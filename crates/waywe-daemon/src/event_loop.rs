@@ -1,19 +1,32 @@
-use crate::wallpaper_app::{NewWallpaperEvent, WallpaperPauseEvent};
+use crate::{
+    playlist::{PlaylistStepEvent, SetPlaylistEvent},
+    reload::ReloadConfigEvent,
+    response::PendingResponse,
+    wallpaper_app::{
+        LoadProfileEvent, NewWallpaperEvent, PauseAction, SaveProfileEvent, SubscribeEvent,
+        WallpaperPauseEvent, WallpaperState, WallpaperStatusEvent,
+    },
+};
 use rustix::io::Errno;
 use std::{
     io,
     os::fd::AsFd as _,
-    path::PathBuf,
     sync::{Once, atomic::Ordering, mpsc::TryRecvError},
     vec::Drain,
 };
 use tokio::runtime::Builder as AsyncRuntimeBuilder;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 use waywe_ipc::{
-    DaemonCommand, WallpaperType,
+    DaemonCommand, DaemonResponse, WallpaperType,
+    command::ProfileCommand,
+    config::Config,
     epoll::{Epoll, PolledFds},
+    file_watcher::FileWatcher,
     ipc::{IpcSocket, RecvError, Server},
+    profile::Profile,
     signals,
+    status::MonitorInfo,
+    wallpaper::Placement,
 };
 use waywe_runtime::{
     ControlFlow, Runtime,
@@ -30,6 +43,8 @@ pub struct EventLoop {
     runtime: Runtime,
     event_queue: EventQueue,
     epoll: Epoll,
+    /// Notifies about changes of the config, [`None`] if they can not be watched
+    config_watcher: Option<FileWatcher>,
 }
 
 impl EventLoop {
@@ -50,11 +65,25 @@ impl EventLoop {
 
         let runtime = Runtime::new(wayland, ControlFlow::Busy, task_pool);
 
+        let config_watcher = match Config::path().map(|path| FileWatcher::new(&path)) {
+            Ok(Ok(watcher)) => Some(watcher),
+            Ok(Err(error)) => {
+                warn!(?error, "failed to watch the config for changes");
+                None
+            }
+            Err(error) => {
+                warn!(%error, "failed to watch the config for changes");
+                None
+            }
+        };
+
         let fds = [
             runtime.wayland.display.as_fd(),
             runtime.ipc.as_fd(),
             event_queue.custom_receiver.pipe_fd(),
-        ];
+        ]
+        .into_iter()
+        .chain(config_watcher.as_ref().map(FileWatcher::as_fd));
 
         let epoll = match Epoll::new(fds) {
             Ok(epoll) => epoll,
@@ -66,10 +95,12 @@ impl EventLoop {
             app,
             event_queue,
             epoll,
+            config_watcher,
         }
     }
 
     async fn run_async(&mut self) {
+        self.app.init(&mut self.runtime).await;
         self.runtime.timer.mark_event_loop_start_time();
 
         let mut polled_fds = PolledFds::with_capacity(1);
@@ -82,7 +113,9 @@ impl EventLoop {
                 ControlFlow::Idle => {
                     self.runtime.timer.mark_block_start();
 
-                    match self.epoll.wait(&mut polled_fds, None) {
+                    let timeout = self.runtime.timer.time_until_wake_up();
+
+                    match self.epoll.wait(&mut polled_fds, timeout) {
                         Ok(()) | Err(Errno::INTR) => {}
                         Err(error) => {
                             error!(?error, "failed to sleep on multiple sockets");
@@ -117,6 +150,14 @@ impl EventLoop {
                 error!(?error, "failed to populate custom events");
             }
 
+            if let Some(watcher) = &self.config_watcher
+                && watcher.has_changed()
+            {
+                self.event_queue.add(ReloadConfigEvent {
+                    response: PendingResponse::detached(),
+                });
+            }
+
             if let Err(error) = self
                 .event_queue
                 .populate_from_cli(&self.runtime.wayland, &self.runtime.ipc)
@@ -128,6 +169,8 @@ impl EventLoop {
                 self.app.handle_event(&mut self.runtime, &mut event).await;
             }
 
+            self.runtime.timer.clear_wake_up();
+
             let info = match self.app.frame(&mut self.runtime).await {
                 Ok(info) => info,
                 Err(FrameError::StopRequested) => break 'event_loop,
@@ -197,12 +240,19 @@ impl EventQueue {
         wayland: &Wayland,
         cli: &IpcSocket<Server, DaemonCommand>,
     ) -> Result<(), RecvError> {
-        let command = match cli.try_recv() {
-            Ok(command) => command,
+        let (command, responder) = match cli.try_recv() {
+            Ok(request) => request,
             Err(RecvError::Empty) => return Ok(()),
             Err(error) => return Err(error),
         };
 
+        if let DaemonCommand::Subscribe = command {
+            self.add(SubscribeEvent { responder });
+            return Ok(());
+        }
+
+        let response = PendingResponse::new(responder);
+
         let get_target = |monitor_name: Option<&str>| {
             let Some(name) = monitor_name else {
                 return Some(WallpaperTarget::ForAll);
@@ -211,54 +261,180 @@ impl EventQueue {
             let target = wayland
                 .client_state
                 .monitor_id(name)
-                .map(WallpaperTarget::ForMonitor)?;
+                .map(WallpaperTarget::ForMonitor);
 
-            Some(target)
+            if target.is_none() {
+                response.fail(DaemonResponse::UnknownMonitor {
+                    name: name.to_owned(),
+                });
+            }
+
+            target
         };
 
-        match command {
-            DaemonCommand::SetVideo { path, monitor } => {
-                let Some(target) = get_target(monitor.as_deref()) else {
-                    return Ok(());
-                };
+        let (path, ty, monitor, placement) = match command {
+            DaemonCommand::SetVideo {
+                path,
+                monitor,
+                placement,
+            } => (path, WallpaperType::Video, monitor, placement),
+            DaemonCommand::SetImage {
+                path,
+                monitor,
+                placement,
+            } => (path, WallpaperType::Image, monitor, placement),
+            DaemonCommand::SetScene { path, monitor } => {
+                (path, WallpaperType::Scene, monitor, Placement::default())
+            }
+            DaemonCommand::SetPlaylist { playlist, monitor } => {
+                if let Some(target) = get_target(monitor.as_deref()) {
+                    self.add(SetPlaylistEvent {
+                        playlist,
+                        target,
+                        response: response.clone(),
+                    });
+                }
 
-                self.add(NewWallpaperEvent {
-                    path,
-                    ty: WallpaperType::Video,
-                    target,
-                });
+                return Ok(());
             }
-            DaemonCommand::SetImage { path, monitor } => {
-                let Some(target) = get_target(monitor.as_deref()) else {
-                    return Ok(());
-                };
-
-                self.add(NewWallpaperEvent {
-                    path,
-                    ty: WallpaperType::Image,
-                    target,
-                });
+            DaemonCommand::Next { monitor } => {
+                if let Some(target) = get_target(monitor.as_deref()) {
+                    self.add(PlaylistStepEvent {
+                        target,
+                        step: 1,
+                        response: response.clone(),
+                    });
+                }
+
+                return Ok(());
             }
-            DaemonCommand::SetScene { monitor } => {
-                let Some(target) = get_target(monitor.as_deref()) else {
-                    return Ok(());
-                };
-
-                self.add(NewWallpaperEvent {
-                    path: PathBuf::default(),
-                    ty: WallpaperType::Scene,
-                    target,
-                });
+            DaemonCommand::Prev { monitor } => {
+                if let Some(target) = get_target(monitor.as_deref()) {
+                    self.add(PlaylistStepEvent {
+                        target,
+                        step: -1,
+                        response: response.clone(),
+                    });
+                }
+
+                return Ok(());
             }
             DaemonCommand::Pause { monitor } => {
-                let Some(target) = get_target(monitor.as_deref()) else {
-                    return Ok(());
-                };
+                if let Some(target) = get_target(monitor.as_deref()) {
+                    self.add(WallpaperPauseEvent {
+                        target,
+                        action: PauseAction::Pause,
+                    });
+                }
+
+                return Ok(());
+            }
+            DaemonCommand::Resume { monitor } => {
+                if let Some(target) = get_target(monitor.as_deref()) {
+                    self.add(WallpaperPauseEvent {
+                        target,
+                        action: PauseAction::Resume,
+                    });
+                }
+
+                return Ok(());
+            }
+            DaemonCommand::Toggle { monitor } => {
+                if let Some(target) = get_target(monitor.as_deref()) {
+                    self.add(WallpaperPauseEvent {
+                        target,
+                        action: PauseAction::Toggle,
+                    });
+                }
+
+                return Ok(());
+            }
+            DaemonCommand::Status { monitor } => {
+                if let Some(target) = get_target(monitor.as_deref()) {
+                    self.add(WallpaperStatusEvent {
+                        target,
+                        response: response.clone(),
+                    });
+                }
 
-                self.add(WallpaperPauseEvent { target });
+                return Ok(());
+            }
+            DaemonCommand::Monitors => {
+                response.reply(DaemonResponse::Monitors(monitor_infos(wayland)));
+                return Ok(());
             }
+            DaemonCommand::Profile(command) => {
+                self.populate_profile_command(command, response.clone());
+                return Ok(());
+            }
+            DaemonCommand::Reload => {
+                self.add(ReloadConfigEvent {
+                    response: response.clone(),
+                });
+                return Ok(());
+            }
+            DaemonCommand::Subscribe => unreachable!("subscriptions are handled above"),
+        };
+
+        let Some(target) = get_target(monitor.as_deref()) else {
+            return Ok(());
         };
 
+        self.add(NewWallpaperEvent {
+            path,
+            ty,
+            placement,
+            target,
+            state: WallpaperState::Running,
+            from_playlist: false,
+            response,
+        });
+
         Ok(())
     }
+
+    fn populate_profile_command(&mut self, command: ProfileCommand, response: PendingResponse) {
+        let (name, result) = match command {
+            ProfileCommand::Save { name } => {
+                self.add(SaveProfileEvent { name, response });
+                return;
+            }
+            ProfileCommand::Load { name } => {
+                self.add(LoadProfileEvent { name, response });
+                return;
+            }
+            ProfileCommand::List => match Profile::list() {
+                Ok(names) => {
+                    response.reply(DaemonResponse::Profiles(names));
+                    return;
+                }
+                Err(error) => (String::new(), Err(error)),
+            },
+            ProfileCommand::Delete { name } => {
+                let result = Profile::delete(&name);
+                (name, result)
+            }
+        };
+
+        if let Err(error) = result {
+            response.fail(DaemonResponse::ProfileFailed {
+                name,
+                reason: error.to_string(),
+            });
+        }
+    }
+}
+
+/// Lists monitors known to the wayland client
+fn monitor_infos(wayland: &Wayland) -> Vec<MonitorInfo> {
+    let monitors = wayland.client_state.monitors.read().unwrap();
+
+    monitors
+        .values()
+        .map(|monitor| MonitorInfo {
+            name: monitor.name.as_deref().unwrap_or_default().to_owned(),
+            size: monitor.size.map(|size| size.to_array()),
+            scale: monitor.scale.unwrap_or(1),
+        })
+        .collect()
 }
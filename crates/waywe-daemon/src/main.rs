@@ -1,15 +1,18 @@
 pub mod detach;
 pub mod event_loop;
+pub mod playlist;
+pub mod power;
+pub mod reload;
+pub mod response;
+pub mod schedule;
+pub mod subscription;
 pub mod wallpaper;
 pub mod wallpaper_app;
 
 use clap::Parser;
 use detach::detach;
 use event_loop::EventLoop;
-use std::{
-    env, fs,
-    io::{self, ErrorKind},
-};
+use std::io;
 use tracing::error;
 use tracing_subscriber::EnvFilter;
 use wallpaper_app::WallpaperApp;
@@ -41,48 +44,11 @@ fn main() {
         error!(?error, "failed to start daemon in the background");
     }
 
-    let config = 'config: {
-        let Some(mut home_dir) = env::home_dir() else {
-            error!("can not find home directory");
-            break 'config Config::default();
-        };
-
-        let config_path = {
-            home_dir.push(".config/waywe/config.toml");
-            home_dir
-        };
-
-        let contents = match fs::read_to_string(&config_path) {
-            Ok(contents) => contents,
-            Err(error) if error.kind() == ErrorKind::NotFound => {
-                let config_dir = config_path.parent().unwrap();
-                let config = Config::default();
-
-                match fs::create_dir_all(config_dir) {
-                    Ok(()) => {}
-                    Err(error) => {
-                        error!(?error, "failed to create config directory");
-                        break 'config config;
-                    }
-                }
-
-                let config_string = toml::to_string(&config).unwrap();
-
-                if let Err(error) = fs::write(&config_path, &config_string) {
-                    error!(?error, "failed to save the default config");
-                }
-
-                break 'config config;
-            }
-            Err(error) => panic!("failed to load config: {error:?}"),
-        };
-
-        match toml::from_str(&contents) {
-            Ok(config) => config,
-            Err(error) => {
-                error!(?error, "failed to load config");
-                Config::default()
-            }
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(error) => {
+            error!(%error, "failed to load config, using the default one");
+            Config::default()
         }
     };
 
@@ -0,0 +1,55 @@
+//! Timed rotation of the wallpapers, see [`Playlist`]
+
+use crate::{event_loop::WallpaperTarget, response::PendingResponse};
+use std::time::Instant;
+use waywe_ipc::playlist::{Playlist, PlaylistEntry};
+
+/// Playlist shown on a monitor
+#[derive(Clone, Debug)]
+pub struct RunningPlaylist {
+    pub playlist: Playlist,
+    /// Time to show the next entry at
+    pub next_switch: Instant,
+}
+
+impl RunningPlaylist {
+    pub fn new(playlist: Playlist) -> Self {
+        Self {
+            next_switch: Instant::now() + playlist.interval(),
+            playlist,
+        }
+    }
+
+    /// Moves `step` entries through the playlist and restarts the interval
+    pub fn step(&mut self, step: isize) -> Option<PlaylistEntry> {
+        self.playlist.advance(step);
+        self.restart();
+        self.playlist.current().cloned()
+    }
+
+    /// Shows the current entry for the whole interval
+    pub fn restart(&mut self) {
+        self.next_switch = Instant::now() + self.playlist.interval();
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.next_switch <= now
+    }
+}
+
+/// The cli asked to cycle through the wallpapers of the playlist
+#[derive(Clone)]
+pub struct SetPlaylistEvent {
+    pub playlist: Playlist,
+    pub target: WallpaperTarget,
+    pub response: PendingResponse,
+}
+
+/// The cli asked to show the next or the previous wallpaper of the playlist
+#[derive(Clone)]
+pub struct PlaylistStepEvent {
+    pub target: WallpaperTarget,
+    /// `1` for the next wallpaper, `-1` for the previous one
+    pub step: isize,
+    pub response: PendingResponse,
+}
@@ -0,0 +1,84 @@
+//! Tracking of the power source, see [`PowerConfig`](waywe_ipc::config::PowerConfig)
+
+use std::{
+    mem,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+use tracing::{debug, error};
+use waywe_ipc::power::PowerSource;
+use waywe_runtime::task_pool::TaskPool;
+
+/// The machine switched between AC and battery
+#[derive(Clone, Copy, Debug)]
+pub struct PowerSourceChangedEvent {
+    pub source: PowerSource,
+}
+
+/// Stops the watcher thread of [`watch_power_source`] once dropped
+pub struct PowerWatcher {
+    stopped: Arc<AtomicBool>,
+}
+
+impl Drop for PowerWatcher {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Checks the power source every `interval` on a separate thread
+/// and emits [`PowerSourceChangedEvent`] once it differs from `source`
+pub fn watch_power_source(
+    task_pool: &mut TaskPool,
+    mut source: PowerSource,
+    interval: Duration,
+) -> PowerWatcher {
+    let stopped = Arc::new(AtomicBool::new(false));
+    let watcher = PowerWatcher {
+        stopped: Arc::clone(&stopped),
+    };
+
+    task_pool.spawn(move |mut emitter| {
+        let mut failed = false;
+
+        loop {
+            thread::sleep(interval);
+
+            if stopped.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let new_source = match PowerSource::read() {
+                Ok(source) => source,
+                Err(error) => {
+                    // the supply may come back, e.g. after resuming from suspend
+                    if !mem::replace(&mut failed, true) {
+                        error!(?error, "failed to read the power source, keep polling");
+                    }
+
+                    continue;
+                }
+            };
+
+            failed = false;
+
+            if new_source == source {
+                continue;
+            }
+
+            source = new_source;
+            debug!(?source, "power source changed");
+
+            // the event loop is gone, nobody needs the power source anymore
+            if emitter.emit(PowerSourceChangedEvent { source }).is_err() {
+                return;
+            }
+        }
+    });
+
+    watcher
+}
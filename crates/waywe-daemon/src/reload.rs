@@ -0,0 +1,40 @@
+//! Applying changes of the config without restarting the daemon
+
+use crate::response::PendingResponse;
+use std::sync::Arc;
+use waywe_ipc::config::{Config, Effects, TransitionConfig};
+use waywe_runtime::shaders::custom::{
+    CustomShader, CustomShaderCache, CustomShaderError, CustomShaderKind,
+};
+
+/// The config file changed or the cli asked to read it again
+#[derive(Clone)]
+pub struct ReloadConfigEvent {
+    pub response: PendingResponse,
+}
+
+/// Loads the shader of the custom transition of the config, if any
+pub fn load_custom_transition(
+    config: &Config,
+    cache: &mut CustomShaderCache,
+) -> Result<Option<Arc<CustomShader>>, CustomShaderError> {
+    let TransitionConfig::Custom(shader) = &config.animation.transition else {
+        return Ok(None);
+    };
+
+    cache.load(shader, CustomShaderKind::Transition).map(Some)
+}
+
+/// Checks that the custom effects of the config compile and caches them
+pub fn load_custom_effects(
+    effects: &[Effects],
+    cache: &mut CustomShaderCache,
+) -> Result<(), CustomShaderError> {
+    for effect in effects {
+        if let Effects::Custom(shader) = effect {
+            cache.load(shader, CustomShaderKind::Effect)?;
+        }
+    }
+
+    Ok(())
+}
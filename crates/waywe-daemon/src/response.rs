@@ -0,0 +1,68 @@
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+};
+use tracing::error;
+use waywe_ipc::{DaemonResponse, IpcResponder};
+
+/// Response to the cli command which is sent once every clone of it is dropped
+///
+/// Commands that finish on other threads (e.g. wallpaper loading) hold a clone
+/// until they are done, so the cli receives the result of the whole command.
+#[derive(Clone, Debug, Default)]
+pub struct PendingResponse(Option<Arc<Mutex<PendingResponseInner>>>);
+
+impl PendingResponse {
+    pub fn new(responder: IpcResponder) -> Self {
+        Self(Some(Arc::new(Mutex::new(PendingResponseInner {
+            responder: Some(responder),
+            response: DaemonResponse::Ok,
+        }))))
+    }
+
+    /// Response for commands that no one waits for, e.g. ones restored from the profile
+    pub const fn detached() -> Self {
+        Self(None)
+    }
+
+    /// Reports a failure. Only the first failure is sent to the cli
+    pub fn fail(&self, response: DaemonResponse) {
+        if self.0.is_none() {
+            error!(%response, "command failed");
+        }
+
+        self.reply(response);
+    }
+
+    /// Sets the reply to the command, e.g. the result of a query.
+    /// A failure reported before is kept instead
+    pub fn reply(&self, response: DaemonResponse) {
+        let Some(inner) = &self.0 else {
+            return;
+        };
+
+        let mut inner = inner.lock().unwrap();
+
+        if inner.response.is_ok() {
+            inner.response = response;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PendingResponseInner {
+    responder: Option<IpcResponder>,
+    response: DaemonResponse,
+}
+
+impl Drop for PendingResponseInner {
+    fn drop(&mut self) {
+        let Some(responder) = self.responder.take() else {
+            return;
+        };
+
+        if let Err(error) = responder.respond(mem::take(&mut self.response)) {
+            error!(?error, "failed to respond to waywe-cli");
+        }
+    }
+}
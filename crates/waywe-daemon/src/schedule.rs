@@ -0,0 +1,99 @@
+//! Switching wallpapers at the boundaries of the schedule,
+//! see [`ScheduleEntry`](waywe_ipc::schedule::ScheduleEntry)
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+use tracing::debug;
+use waywe_ipc::schedule::{self, LocalTime, Location, ScheduleEntry, SunTimes};
+use waywe_runtime::task_pool::TaskPool;
+
+/// Longest time the watcher sleeps before checking for a suspend
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// Difference between the wall clock and the monotonic clock considered a suspend
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(5);
+
+/// Time the watcher oversleeps a boundary, [`LocalTime`] has a precision of a second,
+/// so waking up right at the boundary could find the same boundary again
+const BOUNDARY_MARGIN: Duration = Duration::from_secs(1);
+
+/// An entry of the schedule started or ended, or the machine woke up from suspend
+#[derive(Clone, Copy, Debug)]
+pub struct ScheduleEvent {
+    /// The machine was suspended, so boundaries could be missed
+    pub resumed: bool,
+}
+
+/// Stops the watcher thread of [`watch_schedule`] once dropped
+pub struct ScheduleWatcher {
+    stopped: Arc<AtomicBool>,
+}
+
+impl Drop for ScheduleWatcher {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Emits [`ScheduleEvent`] at every boundary of the schedule on a separate thread
+///
+/// The monotonic clock stops during suspend, so the watcher sleeps in short
+/// steps and compares it to the wall clock to notice the machine waking up
+pub fn watch_schedule(
+    task_pool: &mut TaskPool,
+    entries: Vec<ScheduleEntry>,
+    location: Option<Location>,
+) -> ScheduleWatcher {
+    let stopped = Arc::new(AtomicBool::new(false));
+    let watcher = ScheduleWatcher {
+        stopped: Arc::clone(&stopped),
+    };
+
+    task_pool.spawn(move |mut emitter| {
+        loop {
+            let now = LocalTime::now();
+            let sun = SunTimes::new(location, &now);
+
+            // NOTE: sun times change every day, so they are recomputed at least daily
+            let until_boundary = schedule::next_boundary(&entries, &sun, now.time)
+                .unwrap_or(Duration::from_secs(24 * 60 * 60))
+                + BOUNDARY_MARGIN;
+
+            let wall_start = SystemTime::now();
+            let monotonic_start = Instant::now();
+
+            let resumed = loop {
+                let wall_elapsed = wall_start.elapsed().unwrap_or_default();
+
+                if wall_elapsed > monotonic_start.elapsed() + SUSPEND_THRESHOLD {
+                    break true;
+                }
+
+                if wall_elapsed >= until_boundary {
+                    break false;
+                }
+
+                thread::sleep((until_boundary - wall_elapsed).min(MAX_SLEEP));
+
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+            };
+
+            debug!(resumed, "reached a schedule boundary");
+
+            // the receiving event loop has exited, no one applies the schedule anymore
+            if emitter.emit(ScheduleEvent { resumed }).is_err() {
+                return;
+            }
+        }
+    });
+
+    watcher
+}
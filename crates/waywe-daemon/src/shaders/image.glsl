@@ -1,10 +1,21 @@
 #version 460
 
 layout(push_constant) uniform struct PushConst {
+    // size of the whole wallpaper, larger than the monitor if spanned across several of them
     vec2 resolution;
-    uint transparency_color;
+    // rectangle of the wallpaper shown on this monitor
+    vec2 offset;
+    vec2 size;
+    uint fit;
+    uint background;
 } push;
 
+const uint FIT_FILL = 0;
+const uint FIT_FIT = 1;
+const uint FIT_STRETCH = 2;
+const uint FIT_CENTER = 3;
+const uint FIT_TILE = 4;
+
 layout(set = 0, binding = 0) uniform texture2D image;
 layout(set = 0, binding = 1) uniform sampler image_sampler;
 
@@ -20,26 +31,53 @@ vec4 unpack_color(uint color) {
     );
 }
 
-void main() {
-    ivec2 image_size = textureSize(sampler2D(image, image_sampler), 0);
+// Texture coordinates of the screen position, outside of `0.0..=1.0` where no image is shown
+vec2 fit_texture_coordinates(vec2 image_size) {
+    vec2 pixel = push.offset + vec2(0.5 * position.x + 0.5, 0.5 - 0.5 * position.y) * push.size;
+    vec2 scale = push.resolution / image_size;
 
-    float image_aspect_ratio = float(image_size.x) / float(image_size.y);
-    float screen_aspect_ratio = push.resolution.x / push.resolution.y;
+    vec2 size;
 
-    float scale_factor = push.resolution.x * float(image_size.y) / (push.resolution.y * float(image_size.x));
-    vec2 scaled_position = vec2(scale_factor * position.x, position.y);
+    switch (push.fit) {
+    case FIT_FIT:
+        size = image_size * min(scale.x, scale.y);
+        break;
+    case FIT_STRETCH:
+        size = push.resolution;
+        break;
+    case FIT_CENTER:
+    case FIT_TILE:
+        size = image_size;
+        break;
+    default:
+        size = image_size * max(scale.x, scale.y);
+        break;
+    }
 
-    if (image_aspect_ratio < screen_aspect_ratio) {
-        scaled_position /= scale_factor;
+    if (push.fit == FIT_TILE) {
+        return fract(pixel / size);
     }
 
-    vec2 texture_coordinates = 0.5 * scaled_position + 0.5;
-    texture_coordinates.y = 1.0 - texture_coordinates.y;
+    return (pixel - 0.5 * (push.resolution - size)) / size;
+}
+
+bool is_inside(vec2 texture_coordinates) {
+    return all(greaterThanEqual(texture_coordinates, vec2(0.0)))
+        && all(lessThanEqual(texture_coordinates, vec2(1.0)));
+}
+
+void main() {
+    vec2 image_size = vec2(textureSize(sampler2D(image, image_sampler), 0));
+    vec2 texture_coordinates = fit_texture_coordinates(image_size);
 
     vec4 sample_color = texture(sampler2D(image, image_sampler), texture_coordinates);
 
+    if (!is_inside(texture_coordinates)) {
+        sample_color = vec4(0.0);
+    }
+
     surface_color.rgb = mix(
-        unpack_color(push.transparency_color).rgb,
+        unpack_color(push.background).rgb,
         sample_color.rgb,
         sample_color.a
     );
@@ -4,6 +4,8 @@ layout(push_constant) uniform struct Push {
     vec2 centre;
     float radius;
     float direction;
+    float progress;
+    vec4 params;
 } push;
 
 layout(set = 0, binding = 0) uniform texture2D from_image;
@@ -0,0 +1,26 @@
+#version 460
+
+layout(push_constant) uniform struct Push {
+    vec2 centre;
+    float radius;
+    float direction;
+    float progress;
+    vec4 params;
+} push;
+
+layout(set = 0, binding = 0) uniform texture2D from_image;
+layout(set = 0, binding = 1) uniform texture2D to_image;
+layout(set = 0, binding = 2) uniform sampler image_sampler;
+
+in vec2 position;
+out vec4 surface_color;
+
+void main() {
+    vec2 texture_coordinates = 0.5 * position + 0.5;
+    texture_coordinates.y = 1.0 - texture_coordinates.y;
+
+    vec3 from_color = texture(sampler2D(from_image, image_sampler), texture_coordinates).rgb;
+    vec3 to_color = texture(sampler2D(to_image, image_sampler), texture_coordinates).rgb;
+
+    surface_color = vec4(mix(from_color, to_color, push.progress), 1.0);
+}
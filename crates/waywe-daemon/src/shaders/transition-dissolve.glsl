@@ -0,0 +1,38 @@
+#version 460
+
+layout(push_constant) uniform struct Push {
+    vec2 centre;
+    float radius;
+    float direction;
+    float progress;
+    vec4 params;
+} push;
+
+layout(set = 0, binding = 0) uniform texture2D from_image;
+layout(set = 0, binding = 1) uniform texture2D to_image;
+layout(set = 0, binding = 2) uniform sampler image_sampler;
+
+in vec2 position;
+out vec4 surface_color;
+
+// params.x - size of the grains in pixels, params.y - softness of the grains
+float hash(vec2 cell) {
+    return fract(sin(dot(cell, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+void main() {
+    ivec2 image_size = textureSize(sampler2D(from_image, image_sampler), 0);
+
+    vec2 texture_coordinates = 0.5 * position + 0.5;
+    texture_coordinates.y = 1.0 - texture_coordinates.y;
+
+    vec2 cell = floor(texture_coordinates * vec2(image_size) / max(push.params.x, 1.0));
+    float softness = max(push.params.y, 0.0001);
+    float threshold = hash(cell);
+    float amount = smoothstep(threshold, threshold + softness, push.progress * (1.0 + softness));
+
+    vec3 from_color = texture(sampler2D(from_image, image_sampler), texture_coordinates).rgb;
+    vec3 to_color = texture(sampler2D(to_image, image_sampler), texture_coordinates).rgb;
+
+    surface_color = vec4(mix(from_color, to_color, amount), 1.0);
+}
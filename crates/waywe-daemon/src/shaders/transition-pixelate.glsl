@@ -0,0 +1,33 @@
+#version 460
+
+layout(push_constant) uniform struct Push {
+    vec2 centre;
+    float radius;
+    float direction;
+    float progress;
+    vec4 params;
+} push;
+
+layout(set = 0, binding = 0) uniform texture2D from_image;
+layout(set = 0, binding = 1) uniform texture2D to_image;
+layout(set = 0, binding = 2) uniform sampler image_sampler;
+
+in vec2 position;
+out vec4 surface_color;
+
+// params.x - size of the pixels in the middle of the transition
+void main() {
+    ivec2 image_size = textureSize(sampler2D(from_image, image_sampler), 0);
+
+    vec2 texture_coordinates = 0.5 * position + 0.5;
+    texture_coordinates.y = 1.0 - texture_coordinates.y;
+
+    float block_size = mix(1.0, max(push.params.x, 1.0), 1.0 - abs(2.0 * push.progress - 1.0));
+    vec2 pixel = texture_coordinates * vec2(image_size);
+    vec2 block_coordinates = (floor(pixel / block_size) + 0.5) * block_size / vec2(image_size);
+
+    vec3 from_color = texture(sampler2D(from_image, image_sampler), block_coordinates).rgb;
+    vec3 to_color = texture(sampler2D(to_image, image_sampler), block_coordinates).rgb;
+
+    surface_color = vec4(mix(from_color, to_color, smoothstep(0.4, 0.6, push.progress)), 1.0);
+}
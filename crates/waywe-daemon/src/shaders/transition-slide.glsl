@@ -0,0 +1,46 @@
+#version 460
+
+layout(push_constant) uniform struct Push {
+    vec2 centre;
+    float radius;
+    float direction;
+    float progress;
+    vec4 params;
+} push;
+
+layout(set = 0, binding = 0) uniform texture2D from_image;
+layout(set = 0, binding = 1) uniform texture2D to_image;
+layout(set = 0, binding = 2) uniform sampler image_sampler;
+
+in vec2 position;
+out vec4 surface_color;
+
+// params.xy - direction of the movement, params.z - 1.0 if the old image is pushed away
+vec2 to_texture_coordinates(vec2 position) {
+    vec2 result = 0.5 * position + 0.5;
+    result.y = 1.0 - result.y;
+    return result;
+}
+
+void main() {
+    vec2 direction = push.params.xy;
+    bool is_push = push.params.z > 0.5;
+
+    vec2 to_position = position + 2.0 * (1.0 - push.progress) * direction;
+
+    if (all(lessThanEqual(abs(to_position), vec2(1.0)))) {
+        surface_color.rgb = texture(
+            sampler2D(to_image, image_sampler),
+            to_texture_coordinates(to_position)
+        ).rgb;
+    } else {
+        vec2 from_position = is_push ? position - 2.0 * push.progress * direction : position;
+
+        surface_color.rgb = texture(
+            sampler2D(from_image, image_sampler),
+            to_texture_coordinates(from_position)
+        ).rgb;
+    }
+
+    surface_color.a = 1.0;
+}
@@ -0,0 +1,35 @@
+#version 460
+
+layout(push_constant) uniform struct Push {
+    vec2 centre;
+    float radius;
+    float direction;
+    float progress;
+    vec4 params;
+} push;
+
+layout(set = 0, binding = 0) uniform texture2D from_image;
+layout(set = 0, binding = 1) uniform texture2D to_image;
+layout(set = 0, binding = 2) uniform sampler image_sampler;
+
+in vec2 position;
+out vec4 surface_color;
+
+// params.xy - direction of the edge movement, params.z - width of the edge
+void main() {
+    vec2 texture_coordinates = 0.5 * position + 0.5;
+    texture_coordinates.y = 1.0 - texture_coordinates.y;
+
+    vec2 direction = push.params.xy;
+    float softness = max(push.params.z, 0.0001);
+
+    // distance along the direction in 0..=1
+    float along = 0.5 * dot(position, direction) + 0.5;
+    float edge = push.progress * (1.0 + softness);
+    float amount = 1.0 - smoothstep(edge - softness, edge, along);
+
+    vec3 from_color = texture(sampler2D(from_image, image_sampler), texture_coordinates).rgb;
+    vec3 to_color = texture(sampler2D(to_image, image_sampler), texture_coordinates).rgb;
+
+    surface_color = vec4(mix(from_color, to_color, amount), 1.0);
+}
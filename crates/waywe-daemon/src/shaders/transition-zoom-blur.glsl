@@ -0,0 +1,41 @@
+#version 460
+
+layout(push_constant) uniform struct Push {
+    vec2 centre;
+    float radius;
+    float direction;
+    float progress;
+    vec4 params;
+} push;
+
+layout(set = 0, binding = 0) uniform texture2D from_image;
+layout(set = 0, binding = 1) uniform texture2D to_image;
+layout(set = 0, binding = 2) uniform sampler image_sampler;
+
+in vec2 position;
+out vec4 surface_color;
+
+// params.x - strength of the blur in the middle of the transition
+const int N_SAMPLES = 16;
+
+void main() {
+    vec2 texture_coordinates = 0.5 * position + 0.5;
+    texture_coordinates.y = 1.0 - texture_coordinates.y;
+
+    float strength = push.params.x * sin(3.14159265 * push.progress);
+
+    vec3 from_color = vec3(0.0);
+    vec3 to_color = vec3(0.0);
+
+    for (int i = 0; i < N_SAMPLES; ++i) {
+        float scale = 1.0 - strength * float(i) / float(N_SAMPLES - 1);
+        vec2 coordinates = 0.5 + (texture_coordinates - 0.5) * scale;
+
+        from_color += texture(sampler2D(from_image, image_sampler), coordinates).rgb;
+        to_color += texture(sampler2D(to_image, image_sampler), coordinates).rgb;
+    }
+
+    vec3 color = mix(from_color, to_color, push.progress) / float(N_SAMPLES);
+
+    surface_color = vec4(color, 1.0);
+}
@@ -1,6 +1,20 @@
 #version 460
 
-layout(push_constant) uniform vec2 resolution;
+layout(push_constant) uniform struct PushConst {
+    // size of the whole wallpaper, larger than the monitor if spanned across several of them
+    vec2 resolution;
+    // rectangle of the wallpaper shown on this monitor
+    vec2 offset;
+    vec2 size;
+    uint fit;
+    uint background;
+} push;
+
+const uint FIT_FILL = 0;
+const uint FIT_FIT = 1;
+const uint FIT_STRETCH = 2;
+const uint FIT_CENTER = 3;
+const uint FIT_TILE = 4;
 
 layout(set = 0, binding = 0) uniform texture2D video_y_plane;
 layout(set = 0, binding = 1) uniform texture2D video_uv_plane;
@@ -21,26 +35,61 @@ vec3 yuv_to_rgb(float y, float u, float v) {
 
 const vec3 LUMA_DIRECTION = vec3(0.2126, 0.7152, 0.0772);
 
-void main() {
-    ivec2 video_size = textureSize(sampler2D(video_y_plane, video_sampler), 0);
+vec4 unpack_color(uint color) {
+    return vec4(
+        float((color >> 24) & 0xFF) / 255.0,
+        float((color >> 16) & 0xFF) / 255.0,
+        float((color >> 8) & 0xFF) / 255.0,
+        float((color >> 0) & 0xFF) / 255.0
+    );
+}
+
+// Texture coordinates of the screen position, outside of `0.0..=1.0` where no video is shown
+vec2 fit_texture_coordinates(vec2 video_size) {
+    vec2 pixel = push.offset + vec2(0.5 * position.x + 0.5, 0.5 - 0.5 * position.y) * push.size;
+    vec2 scale = push.resolution / video_size;
 
-    float video_aspect_ratio = float(video_size.x) / float(video_size.y);
-    float screen_aspect_ratio = resolution.x / resolution.y;
+    vec2 size;
 
-    float scale_factor = resolution.x * float(video_size.y) / (resolution.y * float(video_size.x));
-    vec2 scaled_position = vec2(scale_factor * position.x, position.y);
+    switch (push.fit) {
+    case FIT_FIT:
+        size = video_size * min(scale.x, scale.y);
+        break;
+    case FIT_STRETCH:
+        size = push.resolution;
+        break;
+    case FIT_CENTER:
+    case FIT_TILE:
+        size = video_size;
+        break;
+    default:
+        size = video_size * max(scale.x, scale.y);
+        break;
+    }
 
-    if (video_aspect_ratio < screen_aspect_ratio) {
-        scaled_position /= scale_factor;
+    if (push.fit == FIT_TILE) {
+        return fract(pixel / size);
     }
 
-    vec2 texture_coordinates = 0.5 * scaled_position + 0.5;
-    texture_coordinates.y = 1.0 - texture_coordinates.y;
+    return (pixel - 0.5 * (push.resolution - size)) / size;
+}
+
+bool is_inside(vec2 texture_coordinates) {
+    return all(greaterThanEqual(texture_coordinates, vec2(0.0)))
+        && all(lessThanEqual(texture_coordinates, vec2(1.0)));
+}
+
+void main() {
+    vec2 video_size = vec2(textureSize(sampler2D(video_y_plane, video_sampler), 0));
+    vec2 texture_coordinates = fit_texture_coordinates(video_size);
 
     float y = texture(sampler2D(video_y_plane, video_sampler), texture_coordinates).r;
     vec2 uv = texture(sampler2D(video_uv_plane, video_sampler), texture_coordinates).rg;
 
-    surface_color.rgb = yuv_to_rgb(y, uv.x, uv.y);
+    surface_color.rgb = is_inside(texture_coordinates)
+        ? yuv_to_rgb(y, uv.x, uv.y)
+        : unpack_color(push.background).rgb;
+
     surface_color.a = 1.0;
 
     surface_color.rgb = vec3(
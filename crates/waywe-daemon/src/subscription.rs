@@ -0,0 +1,39 @@
+use tracing::debug;
+use waywe_ipc::{DaemonEvent, DaemonResponse, IpcResponder, IpcStream};
+
+/// Clients subscribed to [`DaemonEvent`]s with `waywe watch`
+#[derive(Debug, Default)]
+pub struct Subscribers {
+    streams: Vec<IpcStream>,
+}
+
+impl Subscribers {
+    /// Acknowledges the subscription and keeps the connection to the client
+    pub fn subscribe(&mut self, responder: IpcResponder) {
+        let stream = match responder.into_stream() {
+            Ok(stream) => stream,
+            Err(error) => {
+                debug!(?error, "failed to subscribe a client");
+                return;
+            }
+        };
+
+        if let Err(error) = stream.send(DaemonResponse::Ok) {
+            debug!(?error, "failed to subscribe a client");
+            return;
+        }
+
+        self.streams.push(stream);
+    }
+
+    /// Sends the event to every subscriber dropping the disconnected ones
+    pub fn notify(&mut self, event: DaemonEvent) {
+        self.streams.retain(|stream| match stream.send(&event) {
+            Ok(()) => true,
+            Err(error) => {
+                debug!(?error, "dropping a subscriber");
+                false
+            }
+        });
+    }
+}
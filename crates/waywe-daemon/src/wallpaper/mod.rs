@@ -3,12 +3,21 @@ pub mod test_scene;
 pub mod transition;
 
 use crate::wallpaper::optimized::{
-    OptimizedWallpaper, image::ImageWallpaper, video::VideoWallpaper,
+    OptimizedWallpaper, Viewport,
+    image::ImageWallpaper,
+    video::{VideoSource, VideoWallpaper},
 };
-use glam::UVec2;
-use std::{path::Path, sync::Arc};
-use test_scene::SceneTestWallpaper;
-use waywe_ipc::WallpaperType;
+use glam::{IVec2, UVec2};
+use image::{ImageError, RgbaImage};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use thiserror::Error;
+use video::BackendError;
+use waywe_default_wallpapers::{SceneWallpaper, scene::SceneLoadError};
+use waywe_ipc::{WallpaperType, wallpaper::Placement};
 use waywe_runtime::{
     gpu::Wgpu,
     wayland::{MonitorId, Wayland},
@@ -22,37 +31,164 @@ pub enum RenderState {
     Done,
 }
 
+#[derive(Debug, Error)]
+pub enum CreateWallpaperError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Image(#[from] ImageError),
+    #[error(transparent)]
+    Scene(#[from] SceneLoadError),
+    #[error(transparent)]
+    Video(#[from] BackendError),
+}
+
 pub fn create(
     gpu: Arc<Wgpu>,
     wayland: Arc<Wayland>,
     path: &Path,
     ty: WallpaperType,
+    placement: Placement,
     monitor_id: MonitorId,
-) -> OptimizedWallpaper {
+) -> Result<OptimizedWallpaper, CreateWallpaperError> {
     let monitor_size = {
         let surfaces = gpu.surfaces.read().unwrap();
         let surface = &surfaces[&monitor_id];
         UVec2::new(surface.config.width, surface.config.height)
     };
 
-    match ty {
+    let viewport = Viewport::monitor(monitor_size);
+
+    Ok(match ty {
         WallpaperType::Image => {
-            let image = image::ImageReader::open(path)
-                .unwrap()
-                .decode()
-                .unwrap()
-                .into_rgba8();
-            let wallpaper = ImageWallpaper::new(&gpu, &image, 0, monitor_size, monitor_id);
+            let image = image::ImageReader::open(path)?.decode()?.into_rgba8();
+            let wallpaper = ImageWallpaper::new(&gpu, &image, placement, viewport, monitor_id);
             OptimizedWallpaper::Image(wallpaper)
         }
         WallpaperType::Scene => {
+            let scene = SceneWallpaper::open(path)?;
             let mut wallpaper = Wallpaper::new(gpu, &wayland, monitor_id);
-            SceneTestWallpaper.build(&mut wallpaper);
+            scene.build(&mut wallpaper);
             OptimizedWallpaper::Scene(PreparedWallpaper::prepare(wallpaper))
         }
         WallpaperType::Video => {
-            let wallpaper = VideoWallpaper::new(path, &gpu, monitor_size, monitor_id).unwrap();
+            let source = Arc::new(Mutex::new(VideoSource::new(path)?));
+            let wallpaper = VideoWallpaper::new(source, &gpu, placement, viewport, monitor_id);
             OptimizedWallpaper::Video(wallpaper)
         }
+    })
+}
+
+/// Decoded content of a spanned wallpaper
+///
+/// Shared by the monitors showing it and kept to lay the wallpaper out again
+/// once the monitors change
+#[derive(Clone)]
+pub enum SpanSource {
+    Image(Arc<RgbaImage>),
+    Video(Arc<Mutex<VideoSource>>),
+    /// Scenes can not be spanned and are created for each monitor
+    Scene(PathBuf),
+}
+
+impl SpanSource {
+    pub fn open(path: &Path, ty: WallpaperType) -> Result<Self, CreateWallpaperError> {
+        Ok(match ty {
+            WallpaperType::Image => {
+                let image = image::ImageReader::open(path)?.decode()?.into_rgba8();
+                Self::Image(Arc::new(image))
+            }
+            WallpaperType::Video => Self::Video(Arc::new(Mutex::new(VideoSource::new(path)?))),
+            WallpaperType::Scene => Self::Scene(path.to_owned()),
+        })
+    }
+}
+
+/// Creates the wallpaper spanned across the monitors of the viewports
+pub fn create_spanned(
+    gpu: Arc<Wgpu>,
+    wayland: Arc<Wayland>,
+    source: &SpanSource,
+    placement: Placement,
+    viewports: &[(MonitorId, Viewport)],
+) -> Result<Vec<(MonitorId, OptimizedWallpaper)>, CreateWallpaperError> {
+    match source {
+        SpanSource::Image(image) => Ok(viewports
+            .iter()
+            .map(|&(monitor_id, viewport)| {
+                let wallpaper = ImageWallpaper::new(&gpu, image, placement, viewport, monitor_id);
+                (monitor_id, OptimizedWallpaper::Image(wallpaper))
+            })
+            .collect()),
+        SpanSource::Scene(path) => viewports
+            .iter()
+            .map(|&(monitor_id, _)| {
+                let wallpaper = create(
+                    Arc::clone(&gpu),
+                    Arc::clone(&wayland),
+                    path,
+                    WallpaperType::Scene,
+                    placement,
+                    monitor_id,
+                )?;
+
+                Ok((monitor_id, wallpaper))
+            })
+            .collect(),
+        SpanSource::Video(source) => Ok(viewports
+            .iter()
+            .map(|&(monitor_id, viewport)| {
+                let wallpaper =
+                    VideoWallpaper::new(Arc::clone(source), &gpu, placement, viewport, monitor_id);
+
+                (monitor_id, OptimizedWallpaper::Video(wallpaper))
+            })
+            .collect()),
     }
 }
+
+/// Viewports of the configured monitors for a wallpaper spanned across all of them,
+/// laid out by the logical positions and sizes of the outputs
+pub fn span_viewports(wayland: &Wayland) -> Vec<(MonitorId, Viewport)> {
+    let monitors = wayland.client_state.monitors.read().unwrap();
+
+    let rectangles: Vec<(MonitorId, IVec2, UVec2)> = monitors
+        .iter()
+        .filter_map(|(&monitor_id, monitor)| {
+            Some((
+                monitor_id,
+                monitor.position.unwrap_or_default(),
+                // NOTE: the surface covers the whole output, its size is used without xdg-output
+                monitor.logical_size.or(monitor.size)?,
+            ))
+        })
+        .collect();
+
+    let Some(min) = rectangles
+        .iter()
+        .map(|&(_, position, _)| position)
+        .reduce(IVec2::min)
+    else {
+        return vec![];
+    };
+
+    let max = rectangles
+        .iter()
+        .map(|&(_, position, size)| position + size.as_ivec2())
+        .fold(min, IVec2::max);
+
+    let wallpaper_size = (max - min).as_vec2();
+
+    rectangles
+        .into_iter()
+        .map(|(monitor_id, position, size)| {
+            let viewport = Viewport {
+                wallpaper_size,
+                offset: (position - min).as_vec2(),
+                size: size.as_vec2(),
+            };
+
+            (monitor_id, viewport)
+        })
+        .collect()
+}
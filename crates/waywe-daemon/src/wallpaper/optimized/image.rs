@@ -1,8 +1,10 @@
+use crate::wallpaper::optimized::Viewport;
 use bytemuck::{Pod, Zeroable};
-use glam::{UVec2, Vec2};
+use glam::Vec2;
 use image::{ImageBuffer, ImageError, Rgba};
 use std::{borrow::Cow, io, mem};
 use thiserror::Error;
+use waywe_ipc::wallpaper::{FitMode, Placement};
 use waywe_runtime::{gpu::Wgpu, shaders::ShaderDescriptor, wayland::MonitorId};
 use wgpu::util::DeviceExt;
 
@@ -12,16 +14,16 @@ pub struct ImageWallpaper {
     vertex_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
     pipeline: wgpu::RenderPipeline,
-    screen_size: UVec2,
-    transparency_color: Color,
+    viewport: Viewport,
+    placement: Placement,
 }
 
 impl ImageWallpaper {
     pub fn new(
         gpu: &Wgpu,
         image: &ImageBuffer<Rgba<u8>, Vec<u8>>,
-        transparency_color: Color,
-        monitor_size: UVec2,
+        placement: Placement,
+        viewport: Viewport,
         monitor_id: MonitorId,
     ) -> Self {
         let vertex_buffer = gpu
@@ -171,8 +173,8 @@ impl ImageWallpaper {
             vertex_buffer,
             bind_group,
             pipeline,
-            screen_size: monitor_size,
-            transparency_color,
+            viewport,
+            placement,
         }
     }
 
@@ -198,10 +200,7 @@ impl ImageWallpaper {
         pass.set_push_constants(
             wgpu::ShaderStages::FRAGMENT,
             0,
-            bytemuck::bytes_of(&PushConst {
-                resolution: Vec2::new(self.screen_size.x as f32, self.screen_size.y as f32),
-                transparency_color: self.transparency_color,
-            }),
+            bytemuck::bytes_of(&PushConst::new(self.viewport, self.placement)),
         );
         pass.set_bind_group(0, &self.bind_group, &[]);
         pass.draw(0..SCREEN_TRIANGLE.len() as u32, 0..1);
@@ -222,15 +221,38 @@ const SCREEN_TRIANGLE: [Vec2; 3] = [
     Vec2::new(-1.0, 3.0),
 ];
 
-pub type Color = u32;
-
-pub const COLOR_WHITE: Color = u32::MAX;
-
+/// Push constants of the image and the video shaders
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
 pub struct PushConst {
+    /// Size of the whole wallpaper, see [`Viewport`]
     pub resolution: Vec2,
-    pub transparency_color: u32,
+    pub offset: Vec2,
+    pub size: Vec2,
+    /// One of the `FIT_*` constants of the shaders
+    pub fit: u32,
+    /// Packed as `0xRRGGBBAA`
+    pub background: u32,
+}
+
+impl PushConst {
+    pub fn new(viewport: Viewport, placement: Placement) -> Self {
+        let fit = match placement.fit {
+            FitMode::Fill => 0,
+            FitMode::Fit => 1,
+            FitMode::Stretch => 2,
+            FitMode::Center => 3,
+            FitMode::Tile => 4,
+        };
+
+        Self {
+            resolution: viewport.wallpaper_size,
+            offset: viewport.offset,
+            size: viewport.size,
+            fit,
+            background: placement.background.0,
+        }
+    }
 }
 
 pub struct FullscreenVertex;
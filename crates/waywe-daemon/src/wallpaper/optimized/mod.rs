@@ -2,8 +2,34 @@ pub mod image;
 pub mod video;
 
 use crate::wallpaper::optimized::{image::ImageWallpaper, video::VideoWallpaper};
+use glam::{UVec2, Vec2};
 use waywe_runtime::{frame::FrameInfo, gpu::Wgpu};
-use waywe_scene::wallpaper::PreparedWallpaper;
+use waywe_scene::{time::Time, wallpaper::PreparedWallpaper};
+
+/// Part of the wallpaper shown on a monitor
+///
+/// A wallpaper spanning several monitors is as large as their bounding box
+/// and each monitor shows its own rectangle of it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Viewport {
+    /// Size of the whole wallpaper
+    pub wallpaper_size: Vec2,
+    /// Position of the monitor in the wallpaper
+    pub offset: Vec2,
+    /// Size of the monitor
+    pub size: Vec2,
+}
+
+impl Viewport {
+    /// Viewport of the wallpaper shown on a single monitor
+    pub fn monitor(size: UVec2) -> Self {
+        Self {
+            wallpaper_size: size.as_vec2(),
+            offset: Vec2::ZERO,
+            size: size.as_vec2(),
+        }
+    }
+}
 
 #[expect(clippy::large_enum_variant)]
 pub enum OptimizedWallpaper {
@@ -30,4 +56,19 @@ impl OptimizedWallpaper {
             OptimizedWallpaper::Scene(wallpaper) => wallpaper.frame(surface.clone(), encoder),
         }
     }
+
+    /// Prepares the wallpaper to continue from where it was paused
+    /// instead of catching up with the time passed
+    pub fn resume(&mut self) {
+        match self {
+            OptimizedWallpaper::Image(..) => {}
+            OptimizedWallpaper::Video(wallpaper) => wallpaper.resume(),
+            OptimizedWallpaper::Scene(wallpaper) => wallpaper
+                .wallpaper
+                .main
+                .world
+                .resource_mut::<Time>()
+                .resume(),
+        }
+    }
 }
@@ -1,8 +1,18 @@
-use crate::wallpaper::optimized::image::FullscreenVertex;
+use crate::wallpaper::optimized::{
+    Viewport,
+    image::{FullscreenVertex, PushConst},
+};
 use for_sure::prelude::*;
-use glam::{UVec2, Vec2};
-use std::{borrow::Cow, mem, path::PathBuf};
+use glam::Vec2;
+use std::{
+    borrow::Cow,
+    mem,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use video::{BackendError, FrameDuration};
+use waywe_ipc::wallpaper::Placement;
 use waywe_runtime::{frame::FrameInfo, gpu::Wgpu, shaders::ShaderDescriptor, wayland::MonitorId};
 use waywe_scene::{
     time::Time,
@@ -12,48 +22,86 @@ use wgpu::util::DeviceExt;
 
 pub const LABEL: &str = "default-video";
 
-pub struct VideoWallpaper {
+/// Video decoded once for every monitor it is shown on
+pub struct VideoSource {
     pub video: Video,
     pub rendered_video: Almost<RenderVideo>,
+    pub time: Time,
+}
+
+impl VideoSource {
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, BackendError> {
+        Ok(Self {
+            video: Video::new(path)?,
+            rendered_video: Nil,
+            time: Time::default(),
+        })
+    }
+
+    /// Decodes the frames up to now and exports the new one to the gpu
+    ///
+    /// The time is shared, so the monitors advancing the source one after another
+    /// decode each frame once
+    pub fn advance(&mut self, gpu: &Wgpu) {
+        self.time.update();
+        self.video.advance_by(self.time.delta);
+
+        if self.video.n_frames_since_update == 0 || Almost::is_nil(&self.rendered_video) {
+            self.rendered_video = Value(RenderVideo::export_from(&self.video, gpu));
+        }
+    }
+
+    pub fn frame_duration(&self) -> Duration {
+        self.video
+            .frame
+            .duration_in(self.video.time_base)
+            .map(FrameDuration::to_duration)
+            .unwrap_or(self.video.frame_time_fallback)
+    }
+}
+
+pub struct VideoWallpaper {
+    pub source: Arc<Mutex<VideoSource>>,
     pub pipeline: VideoPipeline,
     pub monitor_id: MonitorId,
-    pub time: Time,
-    pub size: UVec2,
 }
 
 impl VideoWallpaper {
     pub fn new(
-        path: impl Into<PathBuf>,
+        source: Arc<Mutex<VideoSource>>,
         gpu: &Wgpu,
-        size: UVec2,
+        placement: Placement,
+        viewport: Viewport,
         monitor_id: MonitorId,
-    ) -> Result<Self, BackendError> {
-        Ok(Self {
-            video: Video::new(path)?,
-            rendered_video: Nil,
-            pipeline: VideoPipeline::new(gpu, size, monitor_id),
-            time: Time::default(),
+    ) -> Self {
+        Self {
+            source,
+            pipeline: VideoPipeline::new(gpu, viewport, placement, monitor_id),
             monitor_id,
-            size,
-        })
+        }
     }
 
-    fn create_bind_group(&self, device: &wgpu::Device) -> wgpu::BindGroup {
+    /// Prepares the video to continue from where it was paused
+    pub fn resume(&mut self) {
+        self.source.lock().unwrap().time.resume();
+    }
+
+    fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        rendered_video: &RenderVideo,
+    ) -> wgpu::BindGroup {
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some(LABEL),
             layout: &self.pipeline.bind_group_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(
-                        &self.rendered_video.texture_y_plane,
-                    ),
+                    resource: wgpu::BindingResource::TextureView(&rendered_video.texture_y_plane),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::TextureView(
-                        &self.rendered_video.texture_uv_plane,
-                    ),
+                    resource: wgpu::BindingResource::TextureView(&rendered_video.texture_uv_plane),
                 },
                 wgpu::BindGroupEntry {
                     binding: 2,
@@ -69,50 +117,14 @@ impl VideoWallpaper {
         surface: &wgpu::TextureView,
         encoder: &mut wgpu::CommandEncoder,
     ) -> FrameInfo {
-        self.time.update();
-        self.video.advance_by(self.time.delta);
-
-        if self.video.n_frames_since_update == 0 || Almost::is_nil(&self.rendered_video) {
-            self.rendered_video = Value(RenderVideo::export_from(&self.video, gpu));
-        }
-
-        let bind_group = self.create_bind_group(&gpu.device);
-
-        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: None,
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: surface,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Load,
-                    store: wgpu::StoreOp::Store,
-                },
-                depth_slice: None,
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
-
-        pass.set_pipeline(&self.pipeline.pipeline);
-        pass.set_vertex_buffer(0, self.pipeline.vertex_buffer.slice(..));
-        pass.set_push_constants(
-            wgpu::ShaderStages::FRAGMENT,
-            0,
-            bytemuck::bytes_of(&Vec2::new(self.size.x as f32, self.size.y as f32)),
-        );
-        pass.set_bind_group(0, &bind_group, &[]);
-        pass.draw(0..SCREEN_TRIANGLE.len() as u32, 0..1);
+        let mut source = self.source.lock().unwrap();
+        source.advance(gpu);
 
-        let duration = self
-            .video
-            .frame
-            .duration_in(self.video.time_base)
-            .map(FrameDuration::to_duration)
-            .unwrap_or(self.video.frame_time_fallback);
+        let bind_group = self.create_bind_group(&gpu.device, &source.rendered_video);
+        self.pipeline.render(encoder, surface, &bind_group);
 
         FrameInfo {
-            target_frame_time: Some(duration),
+            target_frame_time: Some(source.frame_duration()),
         }
     }
 }
@@ -124,7 +136,8 @@ const SCREEN_TRIANGLE: [Vec2; 3] = [
 ];
 
 pub struct VideoPipeline {
-    pub screen_size: UVec2,
+    pub viewport: Viewport,
+    pub placement: Placement,
     pub vertex_buffer: wgpu::Buffer,
     pub pipeline: wgpu::RenderPipeline,
     pub bind_group_layout: wgpu::BindGroupLayout,
@@ -132,7 +145,12 @@ pub struct VideoPipeline {
 }
 
 impl VideoPipeline {
-    pub fn new(gpu: &Wgpu, screen_size: UVec2, monitor_id: MonitorId) -> Self {
+    pub fn new(
+        gpu: &Wgpu,
+        viewport: Viewport,
+        placement: Placement,
+        monitor_id: MonitorId,
+    ) -> Self {
         gpu.require_shader::<FullscreenVertex>();
         gpu.require_shader::<VideoFragment>();
 
@@ -191,7 +209,7 @@ impl VideoPipeline {
                 bind_group_layouts: &[&bind_group_layout],
                 push_constant_ranges: &[wgpu::PushConstantRange {
                     stages: wgpu::ShaderStages::FRAGMENT,
-                    range: 0..mem::size_of::<Vec2>() as u32,
+                    range: 0..mem::size_of::<PushConst>() as u32,
                 }],
             });
 
@@ -251,7 +269,8 @@ impl VideoPipeline {
 
         Self {
             sampler,
-            screen_size,
+            viewport,
+            placement,
             vertex_buffer,
             pipeline,
             bind_group_layout,
@@ -285,10 +304,7 @@ impl VideoPipeline {
         pass.set_push_constants(
             wgpu::ShaderStages::FRAGMENT,
             0,
-            bytemuck::bytes_of(&Vec2::new(
-                self.screen_size.x as f32,
-                self.screen_size.y as f32,
-            )),
+            bytemuck::bytes_of(&PushConst::new(self.viewport, self.placement)),
         );
         pass.set_bind_group(0, bind_group, &[]);
         pass.draw(0..SCREEN_TRIANGLE.len() as u32, 0..1);
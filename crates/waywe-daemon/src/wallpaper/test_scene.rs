@@ -113,7 +113,7 @@ impl FromWorld for TestAssets {
             .into_rgba8();
 
         let image_aspect_ratio = image.height() as f32 / image.width() as f32;
-        let image = images.add(Image { image });
+        let image = images.add(Image::from(image));
 
         let mut image_materials = world.resource_mut::<Assets<ImageMaterial>>();
         let image_material = image_materials.add(ImageMaterial {
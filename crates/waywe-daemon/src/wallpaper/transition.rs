@@ -1,19 +1,25 @@
 use crate::wallpaper::optimized::OptimizedWallpaper;
 use bytemuck::{Pod, Zeroable};
 use for_sure::prelude::*;
-use glam::{UVec2, Vec2};
+use glam::{UVec2, Vec2, Vec4};
 use smallvec::SmallVec;
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     mem,
+    sync::Arc,
     time::{Duration, Instant},
 };
-use waywe_ipc::config::{AnimationConfig, AnimationDirection};
+use waywe_ipc::config::{
+    AnimationConfig, AnimationDirection, Effects as BuiltinEffects, TransitionConfig,
+};
 use waywe_runtime::{
     effects::{Effects, config::EffectsBuilder},
     frame::{FrameError, FrameInfo},
     gpu::Wgpu,
-    shaders::ShaderDescriptor,
+    shaders::{
+        ShaderDescriptor,
+        custom::{BINDING_UNIFORMS, CustomShader, CustomShaderCache, CustomUniforms},
+    },
     wayland::MonitorId,
 };
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
@@ -27,6 +33,8 @@ const SCREEN_TRIANGLE: [Vec2; 3] = [
 pub struct WallpaperTransitionState {
     pub from: wgpu::TextureView,
     pub to: wgpu::TextureView,
+    /// Uniforms of the custom transitions
+    pub uniforms: wgpu::Buffer,
     pub bind_group: wgpu::BindGroup,
 }
 
@@ -58,6 +66,13 @@ impl WallpaperTransitionState {
         let from = from_texture.create_view(&Default::default());
         let to = to_texture.create_view(&Default::default());
 
+        let uniforms = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("transition-uniforms"),
+            size: mem::size_of::<CustomUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
         let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("transition-binds"),
             layout: &pipeline.bind_group_layout,
@@ -74,12 +89,17 @@ impl WallpaperTransitionState {
                     binding: 2,
                     resource: wgpu::BindingResource::Sampler(&pipeline.sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: BINDING_UNIFORMS,
+                    resource: uniforms.as_entire_binding(),
+                },
             ],
         });
 
         Self {
             from,
             to,
+            uniforms,
             bind_group,
         }
     }
@@ -100,16 +120,140 @@ impl ShaderDescriptor for FullScreenVertexShader {
     }
 }
 
-pub struct FullScreenFragmentShader;
+fn transition_shader(source: &'static str) -> wgpu::ShaderModuleDescriptor<'static> {
+    wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Glsl {
+            shader: source.into(),
+            stage: wgpu::naga::ShaderStage::Fragment,
+            defines: &[],
+        },
+    }
+}
 
-impl ShaderDescriptor for FullScreenFragmentShader {
+pub struct CircleTransitionShader;
+
+impl ShaderDescriptor for CircleTransitionShader {
     fn shader_descriptor() -> wgpu::ShaderModuleDescriptor<'static> {
-        wgpu::ShaderModuleDescriptor {
-            label: None,
-            source: wgpu::ShaderSource::Glsl {
-                shader: include_str!("../shaders/transition.glsl").into(),
-                stage: wgpu::naga::ShaderStage::Fragment,
-                defines: &[],
+        transition_shader(include_str!("../shaders/transition-circle.glsl"))
+    }
+}
+
+pub struct CrossfadeTransitionShader;
+
+impl ShaderDescriptor for CrossfadeTransitionShader {
+    fn shader_descriptor() -> wgpu::ShaderModuleDescriptor<'static> {
+        transition_shader(include_str!("../shaders/transition-crossfade.glsl"))
+    }
+}
+
+pub struct WipeTransitionShader;
+
+impl ShaderDescriptor for WipeTransitionShader {
+    fn shader_descriptor() -> wgpu::ShaderModuleDescriptor<'static> {
+        transition_shader(include_str!("../shaders/transition-wipe.glsl"))
+    }
+}
+
+pub struct SlideTransitionShader;
+
+impl ShaderDescriptor for SlideTransitionShader {
+    fn shader_descriptor() -> wgpu::ShaderModuleDescriptor<'static> {
+        transition_shader(include_str!("../shaders/transition-slide.glsl"))
+    }
+}
+
+pub struct PixelateTransitionShader;
+
+impl ShaderDescriptor for PixelateTransitionShader {
+    fn shader_descriptor() -> wgpu::ShaderModuleDescriptor<'static> {
+        transition_shader(include_str!("../shaders/transition-pixelate.glsl"))
+    }
+}
+
+pub struct DissolveTransitionShader;
+
+impl ShaderDescriptor for DissolveTransitionShader {
+    fn shader_descriptor() -> wgpu::ShaderModuleDescriptor<'static> {
+        transition_shader(include_str!("../shaders/transition-dissolve.glsl"))
+    }
+}
+
+pub struct ZoomBlurTransitionShader;
+
+impl ShaderDescriptor for ZoomBlurTransitionShader {
+    fn shader_descriptor() -> wgpu::ShaderModuleDescriptor<'static> {
+        transition_shader(include_str!("../shaders/transition-zoom-blur.glsl"))
+    }
+}
+
+/// Fragment shader of the transition, parameters are passed in [`AnimationState::params`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransitionKind {
+    Circle,
+    Crossfade,
+    Wipe,
+    /// Used for both slide and push transitions
+    Slide,
+    Pixelate,
+    Dissolve,
+    ZoomBlur,
+    /// Shader written by the user, takes [`CustomUniforms`] instead of [`AnimationState`]
+    Custom,
+}
+
+impl TransitionKind {
+    /// Shader and its parameters for the transition,
+    /// [`TransitionConfig::Random`] should be resolved beforehand
+    pub fn from_config(config: TransitionConfig) -> (Self, Vec4) {
+        match config {
+            TransitionConfig::Circle
+            | TransitionConfig::GrowFromCursor
+            | TransitionConfig::Random => (Self::Circle, Vec4::ZERO),
+            TransitionConfig::Crossfade => (Self::Crossfade, Vec4::ZERO),
+            TransitionConfig::Wipe {
+                direction,
+                softness,
+            } => (Self::Wipe, direction.to_vec2().extend(softness).extend(0.0)),
+            TransitionConfig::Slide { direction } => {
+                (Self::Slide, direction.to_vec2().extend(0.0).extend(0.0))
+            }
+            TransitionConfig::Push { direction } => {
+                (Self::Slide, direction.to_vec2().extend(1.0).extend(0.0))
+            }
+            TransitionConfig::Pixelate { max_block_size } => {
+                (Self::Pixelate, Vec4::new(max_block_size, 0.0, 0.0, 0.0))
+            }
+            TransitionConfig::Dissolve {
+                grain_size,
+                softness,
+            } => (Self::Dissolve, Vec4::new(grain_size, softness, 0.0, 0.0)),
+            TransitionConfig::ZoomBlur { strength } => {
+                (Self::ZoomBlur, Vec4::new(strength, 0.0, 0.0, 0.0))
+            }
+            TransitionConfig::Custom(..) => (Self::Custom, Vec4::ZERO),
+        }
+    }
+
+    /// Compiles the fragment shader of the transition if it is not compiled yet,
+    /// `custom` is used for [`TransitionKind::Custom`]
+    pub fn shader(self, gpu: &Wgpu, custom: Option<&CustomShader>) -> wgpu::ShaderModule {
+        fn get<S: ShaderDescriptor>(gpu: &Wgpu) -> wgpu::ShaderModule {
+            gpu.require_shader::<S>();
+            gpu.shader_cache.get::<S>().unwrap().clone()
+        }
+
+        match self {
+            Self::Circle => get::<CircleTransitionShader>(gpu),
+            Self::Crossfade => get::<CrossfadeTransitionShader>(gpu),
+            Self::Wipe => get::<WipeTransitionShader>(gpu),
+            Self::Slide => get::<SlideTransitionShader>(gpu),
+            Self::Pixelate => get::<PixelateTransitionShader>(gpu),
+            Self::Dissolve => get::<DissolveTransitionShader>(gpu),
+            Self::ZoomBlur => get::<ZoomBlurTransitionShader>(gpu),
+            Self::Custom => match custom {
+                Some(shader) => shader.create_module(gpu),
+                None => get::<CircleTransitionShader>(gpu),
             },
         }
     }
@@ -117,7 +261,12 @@ impl ShaderDescriptor for FullScreenFragmentShader {
 
 pub struct WallpaperTransitionPipeline {
     pub monitor_id: MonitorId,
-    pub pipeline: wgpu::RenderPipeline,
+    /// Pipelines of the transitions used so far
+    pub pipelines: HashMap<TransitionKind, wgpu::RenderPipeline>,
+    /// Shader the pipeline of [`TransitionKind::Custom`] is made of
+    pub custom_shader: Option<Arc<CustomShader>>,
+    pub pipeline_layout: wgpu::PipelineLayout,
+    pub surface_format: wgpu::TextureFormat,
     pub bind_group_layout: wgpu::BindGroupLayout,
     pub sampler: wgpu::Sampler,
     pub vertices: wgpu::Buffer,
@@ -126,7 +275,6 @@ pub struct WallpaperTransitionPipeline {
 impl WallpaperTransitionPipeline {
     pub fn new(gpu: &Wgpu, monitor_id: MonitorId) -> Self {
         gpu.require_shader::<FullScreenVertexShader>();
-        gpu.require_shader::<FullScreenFragmentShader>();
 
         let vertices = gpu.device.create_buffer_init(&BufferInitDescriptor {
             label: Some("fullscreen-triangle"),
@@ -165,6 +313,16 @@ impl WallpaperTransitionPipeline {
                             ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                             count: None,
                         },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: BINDING_UNIFORMS,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
                     ],
                 });
 
@@ -184,78 +342,107 @@ impl WallpaperTransitionPipeline {
             surfaces[&monitor_id].format
         };
 
-        let pipeline = gpu
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("image-pipeline"),
-                layout: Some(&pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &gpu.shader_cache.get::<FullScreenVertexShader>().unwrap(),
-                    entry_point: Some("main"),
-                    compilation_options: wgpu::PipelineCompilationOptions {
-                        constants: &[],
-                        zero_initialize_workgroup_memory: false,
-                    },
-                    buffers: &[wgpu::VertexBufferLayout {
-                        array_stride: mem::size_of_val(&SCREEN_TRIANGLE[0]) as u64,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &[wgpu::VertexAttribute {
-                            format: wgpu::VertexFormat::Float32x2,
-                            offset: 0,
-                            shader_location: 0,
-                        }],
-                    }],
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &gpu.shader_cache.get::<FullScreenFragmentShader>().unwrap(),
-                    entry_point: Some("main"),
-                    compilation_options: wgpu::PipelineCompilationOptions {
-                        constants: &[],
-                        zero_initialize_workgroup_memory: false,
-                    },
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: surface_format,
-                        blend: None,
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-                cache: None,
-            });
-
         let sampler = gpu.device.create_sampler(&Default::default());
 
         Self {
             monitor_id,
-            pipeline,
+            pipelines: HashMap::new(),
+            custom_shader: None,
+            pipeline_layout,
+            surface_format,
             bind_group_layout,
             sampler,
             vertices,
         }
     }
 
+    /// Creates the pipeline of the transition on its first use
+    /// or once the custom shader changes
+    pub fn require_pipeline(
+        &mut self,
+        gpu: &Wgpu,
+        kind: TransitionKind,
+        custom: Option<&Arc<CustomShader>>,
+    ) {
+        if kind == TransitionKind::Custom
+            && !Option::zip(self.custom_shader.as_ref(), custom)
+                .is_some_and(|(a, b)| Arc::ptr_eq(a, b))
+        {
+            _ = self.pipelines.remove(&kind);
+            self.custom_shader = custom.cloned();
+        }
+
+        _ = self.pipelines.entry(kind).or_insert_with(|| {
+            let fragment_shader = kind.shader(gpu, self.custom_shader.as_deref());
+
+            gpu.device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("transition-pipeline"),
+                    layout: Some(&self.pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &gpu.shader_cache.get::<FullScreenVertexShader>().unwrap(),
+                        entry_point: Some("main"),
+                        compilation_options: wgpu::PipelineCompilationOptions {
+                            constants: &[],
+                            zero_initialize_workgroup_memory: false,
+                        },
+                        buffers: &[wgpu::VertexBufferLayout {
+                            array_stride: mem::size_of_val(&SCREEN_TRIANGLE[0]) as u64,
+                            step_mode: wgpu::VertexStepMode::Vertex,
+                            attributes: &[wgpu::VertexAttribute {
+                                format: wgpu::VertexFormat::Float32x2,
+                                offset: 0,
+                                shader_location: 0,
+                            }],
+                        }],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &fragment_shader,
+                        entry_point: Some("main"),
+                        compilation_options: wgpu::PipelineCompilationOptions {
+                            constants: &[],
+                            zero_initialize_workgroup_memory: false,
+                        },
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: self.surface_format,
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Ccw,
+                        cull_mode: None,
+                        unclipped_depth: false,
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        conservative: false,
+                    },
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: 1,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                    multiview: None,
+                    cache: None,
+                })
+        });
+    }
+
     pub fn render(
-        &self,
+        &mut self,
+        gpu: &Wgpu,
         state: &WallpaperTransitionState,
         surface_view: &wgpu::TextureView,
         encoder: &mut wgpu::CommandEncoder,
+        transition: &OngoingTransition,
         animation_state: &AnimationState,
     ) {
+        let kind = transition.kind;
+        self.require_pipeline(gpu, kind, transition.custom.as_ref());
+        let pipeline = &self.pipelines[&kind];
+
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("transition-pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -272,7 +459,7 @@ impl WallpaperTransitionPipeline {
             occlusion_query_set: None,
         });
 
-        pass.set_pipeline(&self.pipeline);
+        pass.set_pipeline(pipeline);
         pass.set_bind_group(0, &state.bind_group, &[]);
         pass.set_vertex_buffer(0, self.vertices.slice(..));
         pass.set_push_constants(
@@ -291,6 +478,11 @@ pub struct AnimationState {
     pub centre: Vec2,
     pub radius: f32,
     pub direction: f32,
+    /// Eased amount of work done in 0..=1
+    pub progress: f32,
+    pub _padding: [f32; 3],
+    /// Parameters of the transition, see [`TransitionKind::from_config`]
+    pub params: Vec4,
 }
 
 pub struct OngoingTransition {
@@ -301,10 +493,27 @@ pub struct OngoingTransition {
     pub animation_duration: Duration,
     pub direction: AnimationDirection,
     pub centre: Vec2,
+    pub kind: TransitionKind,
+    pub params: Vec4,
+    /// Shader of [`TransitionKind::Custom`]
+    pub custom: Option<Arc<CustomShader>>,
 }
 
 impl OngoingTransition {
-    pub fn new(aspect_ratio: f32, config: &AnimationConfig) -> Self {
+    /// Starts the transition, `cursor` is the last known cursor position in `-1.0..=1.0`.
+    /// Custom transitions fall back to the circle one without the `custom` shader
+    pub fn new(
+        aspect_ratio: f32,
+        config: &AnimationConfig,
+        cursor: Option<Vec2>,
+        custom: Option<Arc<CustomShader>>,
+    ) -> Self {
+        let transition = match (config.transition.resolve(), &custom) {
+            (TransitionConfig::Custom(..), None) => TransitionConfig::Circle,
+            (transition, _) => transition,
+        };
+        let (kind, params) = TransitionKind::from_config(transition.clone());
+
         let corners = [
             Vec2::new(-1.0 / aspect_ratio, -1.0),
             Vec2::new(1.0 / aspect_ratio, -1.0),
@@ -312,7 +521,10 @@ impl OngoingTransition {
             Vec2::new(-1.0 / aspect_ratio, 1.0),
         ];
 
-        let stretched_centre = config.center_position.get();
+        let stretched_centre = match (transition, cursor) {
+            (TransitionConfig::GrowFromCursor, Some(cursor)) => cursor,
+            _ => config.center_position.get(),
+        };
         let centre = Vec2::new(stretched_centre.x / aspect_ratio, stretched_centre.y);
 
         let scale = centre
@@ -328,6 +540,9 @@ impl OngoingTransition {
             animation_duration: Duration::from_millis(config.duration_milliseconds),
             direction: config.direction,
             centre,
+            kind,
+            params,
+            custom: custom.filter(|_| kind == TransitionKind::Custom),
         }
     }
 
@@ -344,6 +559,11 @@ impl OngoingTransition {
         self.done_fraction >= 1.0
     }
 
+    /// Continues the transition from the point where it was paused
+    pub fn resume(&mut self) {
+        self.start_time = Instant::now() - self.animation_duration.mul_f32(self.done_fraction);
+    }
+
     pub fn amount(&self) -> f32 {
         self.amount_with_easing(|t| t)
     }
@@ -357,6 +577,11 @@ impl OngoingTransition {
         ease(t) * self.scale
     }
 
+    /// Amount of work done in 0..=1 regardless of the direction
+    pub fn progress_with_easing(&self, ease: impl FnOnce(f32) -> f32) -> f32 {
+        ease(self.done_fraction)
+    }
+
     pub fn direction(&self) -> f32 {
         match self.direction {
             AnimationDirection::Out => 1.0,
@@ -392,38 +617,117 @@ impl EffectWallpaper {
 
 pub struct RunningWallpapers {
     pub monitor_id: MonitorId,
+    /// Size of the surface in physical pixels
+    pub monitor_size: UVec2,
     pub aspect_ratio: f32,
+    /// Last known cursor position in surface pixels
+    pub cursor: Option<UVec2>,
     pub executing: VecDeque<EffectWallpaper>,
     pub ongoing_transitions: SmallVec<[OngoingTransition; 8]>,
     pub transition_pipeline: Almost<WallpaperTransitionPipeline>,
     pub textures: Almost<WallpaperTransitionState>,
     pub config: AnimationConfig,
+    /// Shader of [`TransitionConfig::Custom`]
+    pub custom_transition: Option<Arc<CustomShader>>,
     pub effects_builder: EffectsBuilder,
+    /// The last enqueued wallpaper has not been drawn yet, so it has to be drawn
+    /// once even if the wallpaper is paused
+    pub needs_first_frame: bool,
 }
 
 impl RunningWallpapers {
-    pub const fn new(monitor_id: MonitorId, monitor_size: UVec2, config: AnimationConfig) -> Self {
+    pub const fn new(
+        monitor_id: MonitorId,
+        monitor_size: UVec2,
+        config: AnimationConfig,
+        custom_transition: Option<Arc<CustomShader>>,
+    ) -> Self {
         Self {
             monitor_id,
+            monitor_size,
             aspect_ratio: monitor_size.y as f32 / monitor_size.x as f32,
+            cursor: None,
             executing: VecDeque::new(),
             ongoing_transitions: SmallVec::new_const(),
             transition_pipeline: Nil,
             textures: Nil,
             config,
+            custom_transition,
             effects_builder: EffectsBuilder::new(monitor_id),
+            needs_first_frame: false,
         }
     }
 
     pub fn enqueue_wallpaper(&mut self, gpu: &Wgpu, wallpaper: OptimizedWallpaper) {
-        self.executing.push_back(EffectWallpaper {
-            wallpaper,
-            effects: self.effects_builder.build(gpu),
-        });
+        let mut effects = self.effects_builder.build(gpu);
+
+        if let Some(cursor) = self.cursor {
+            effects.set_cursor(cursor.as_vec2());
+        }
+
+        self.executing
+            .push_back(EffectWallpaper { wallpaper, effects });
+        self.needs_first_frame = true;
 
         if self.executing.len() >= 2 {
-            self.ongoing_transitions
-                .push(OngoingTransition::new(self.aspect_ratio, &self.config));
+            let cursor = self.cursor.map(|position| {
+                let normalized = position.as_vec2() / self.monitor_size.as_vec2();
+                Vec2::new(2.0 * normalized.x - 1.0, 1.0 - 2.0 * normalized.y)
+            });
+
+            self.ongoing_transitions.push(OngoingTransition::new(
+                self.aspect_ratio,
+                &self.config,
+                cursor,
+                self.custom_transition.clone(),
+            ));
+        }
+    }
+
+    /// Updates the size of the monitor given in physical pixels,
+    /// the transition textures are recreated on the next transition
+    pub fn resize(&mut self, monitor_size: UVec2) {
+        self.monitor_size = monitor_size;
+        self.aspect_ratio = monitor_size.y as f32 / monitor_size.x as f32;
+        self.transition_pipeline = Nil;
+        self.textures = Nil;
+    }
+
+    /// Remembers the cursor position given in surface pixels
+    pub fn set_cursor(&mut self, position: UVec2) {
+        self.cursor = Some(position);
+
+        for wallpaper in &mut self.executing {
+            wallpaper.effects.set_cursor(position.as_vec2());
+        }
+    }
+
+    /// Replaces the animation config used by the next transitions
+    pub fn set_config(
+        &mut self,
+        config: AnimationConfig,
+        custom_transition: Option<Arc<CustomShader>>,
+    ) {
+        self.config = config;
+        self.custom_transition = custom_transition;
+    }
+
+    /// Replaces the effects of the wallpapers, including the running ones
+    pub fn set_effects(
+        &mut self,
+        gpu: &Wgpu,
+        effects: &[BuiltinEffects],
+        custom_shaders: &mut CustomShaderCache,
+    ) {
+        self.effects_builder = EffectsBuilder::new(self.monitor_id);
+        self.effects_builder.add_builtins(effects, custom_shaders);
+
+        for wallpaper in &mut self.executing {
+            wallpaper.effects = self.effects_builder.build(gpu);
+
+            if let Some(cursor) = self.cursor {
+                wallpaper.effects.set_cursor(cursor.as_vec2());
+            }
         }
     }
 
@@ -438,6 +742,14 @@ impl RunningWallpapers {
         _ = self.executing.drain(..n_unfinished);
     }
 
+    /// Drops the ongoing transitions and all wallpapers but the last enqueued one
+    pub fn skip_transitions(&mut self) {
+        let n_replaced = self.executing.len().saturating_sub(1);
+
+        _ = self.executing.drain(..n_replaced);
+        self.ongoing_transitions.clear();
+    }
+
     pub fn is_transitioning(&self) -> bool {
         self.executing.len() >= 2
     }
@@ -461,6 +773,7 @@ impl RunningWallpapers {
     ) -> Result<FrameInfo, FrameError> {
         self.init_transitions(gpu);
         self.remove_finished();
+        self.needs_first_frame = false;
 
         let surface_view = surface.create_view(&Default::default());
 
@@ -491,10 +804,32 @@ impl RunningWallpapers {
                 centre: transition.centre(),
                 radius: transition.amount_with_easing(self.config.easing.get()),
                 direction: transition.direction(),
+                progress: transition.progress_with_easing(self.config.easing.get()),
+                params: transition.params,
+                ..Default::default()
             };
 
-            self.transition_pipeline
-                .render(&self.textures, &surface_view, encoder, &state);
+            if let Some(shader) = &transition.custom {
+                let uniforms = CustomUniforms {
+                    time: transition.start_time.elapsed().as_secs_f32(),
+                    progress: state.progress,
+                    resolution: self.monitor_size.as_vec2(),
+                    cursor: self.cursor.unwrap_or_default().as_vec2(),
+                    ..shader.uniforms
+                };
+
+                gpu.queue
+                    .write_buffer(&self.textures.uniforms, 0, bytemuck::bytes_of(&uniforms));
+            }
+
+            self.transition_pipeline.render(
+                gpu,
+                &self.textures,
+                &surface_view,
+                encoder,
+                transition,
+                &state,
+            );
 
             // TODO(hack3rmann): we can avoid copying the texture by swapping bind groups
             // with third intermediate texture
@@ -510,6 +845,17 @@ impl RunningWallpapers {
         })
     }
 
+    /// Continues wallpapers and transitions from the point where they were paused
+    pub fn resume(&mut self) {
+        for wallpaper in &mut self.executing {
+            wallpaper.wallpaper.resume();
+        }
+
+        for transition in &mut self.ongoing_transitions {
+            transition.resume();
+        }
+    }
+
     pub fn wallpapers_mut(&mut self) -> &mut [EffectWallpaper] {
         self.executing.make_contiguous()
     }
@@ -1,22 +1,46 @@
 use crate::{
     event_loop::WallpaperTarget,
-    wallpaper::{self, optimized::OptimizedWallpaper, transition::RunningWallpapers},
+    playlist::{PlaylistStepEvent, RunningPlaylist, SetPlaylistEvent},
+    power::{self, PowerSourceChangedEvent, PowerWatcher},
+    reload::{self, ReloadConfigEvent},
+    response::PendingResponse,
+    schedule::{self, ScheduleEvent, ScheduleWatcher},
+    subscription::Subscribers,
+    wallpaper::{
+        self, SpanSource,
+        optimized::{OptimizedWallpaper, Viewport},
+        transition::RunningWallpapers,
+    },
 };
 use for_sure::prelude::*;
 use smallvec::{SmallVec, smallvec};
-use std::{collections::btree_map::Entry, path::PathBuf, sync::Arc};
-use tracing::{debug, error};
+use std::{
+    any::Any,
+    collections::{BTreeSet, VecDeque},
+    mem,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, warn};
 use waywe_ipc::{
-    WallpaperType,
-    config::Config,
-    profile::{Monitor, SetupProfile},
+    DaemonEvent, DaemonResponse, IpcResponder, WallpaperType,
+    config::{Config, Effects},
+    power::PowerSource,
+    profile::{Monitor, MonitorProfile, Profile},
+    schedule::{LocalTime, SunTimes, active_entry},
+    status::{PlaybackState, PowerPolicy, WallpaperStatus},
+    wallpaper::Placement,
 };
 use waywe_runtime::{
     Runtime, RuntimeFeatures,
     app::App,
-    event::{EventHandler, Handle, TryReplicate},
+    effects::config::EffectsBuilder,
+    event::{EventEmitter, EventHandler, Handle, TryReplicate},
     frame::{FrameError, FrameInfo},
-    wayland::{MonitorId, MonitorMap, WaylandEvent},
+    shaders::custom::{CustomShader, CustomShaderCache},
+    wayland::{MonitorId, MonitorMap, Wayland, WaylandEvent},
 };
 use waywe_scene::cursor::CursorMoved;
 
@@ -28,13 +52,6 @@ pub enum WallpaperState {
 }
 
 impl WallpaperState {
-    pub const fn inverted(self) -> Self {
-        match self {
-            Self::Running => Self::Paused,
-            Self::Paused => Self::Running,
-        }
-    }
-
     pub const fn is_paused(self) -> bool {
         matches!(self, Self::Paused)
     }
@@ -44,12 +61,78 @@ impl WallpaperState {
     }
 }
 
+impl From<WallpaperState> for PlaybackState {
+    fn from(value: WallpaperState) -> Self {
+        match value {
+            WallpaperState::Running => Self::Running,
+            WallpaperState::Paused => Self::Paused,
+        }
+    }
+}
+
+/// Counts frames rendered during the last second
+#[derive(Clone, Debug, Default)]
+pub struct FrameRateCounter {
+    frames: VecDeque<Instant>,
+}
+
+impl FrameRateCounter {
+    const WINDOW: Duration = Duration::from_secs(1);
+
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        self.frames.push_back(now);
+        self.forget_before(now);
+    }
+
+    /// Frames per second, zero if nothing was rendered during the last second
+    pub fn fps(&mut self) -> f32 {
+        self.forget_before(Instant::now());
+        self.frames.len() as f32 / Self::WINDOW.as_secs_f32()
+    }
+
+    fn forget_before(&mut self, now: Instant) {
+        while let Some(&time) = self.frames.front()
+            && now.duration_since(time) > Self::WINDOW
+        {
+            self.frames.pop_front();
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct WallpaperApp {
     pub wallpapers: MonitorMap<RunningWallpapers>,
     pub wallpaper_states: MonitorMap<WallpaperState>,
+    /// Path and type of the wallpaper shown on each monitor
+    pub current: MonitorMap<Monitor>,
+    /// Fit mode and background of the wallpaper shown on each monitor
+    pub placements: MonitorMap<Placement>,
+    /// Wallpaper spanned across all monitors, laid out again once they change
+    pub spanned: Option<SpannedWallpaper>,
+    /// Frames rendered on each monitor, paused or hidden monitors count none
+    pub frame_rates: MonitorMap<FrameRateCounter>,
+    /// Names of the plugged monitors, kept to report unplugged ones
+    pub monitor_names: MonitorMap<Arc<str>>,
+    pub subscribers: Subscribers,
+    /// Effects of the monitors overriding the ones from the config, set by profiles
+    pub monitor_effects: MonitorMap<Vec<Effects>>,
+    /// Playlists the wallpapers of the monitors are switched by
+    pub playlists: MonitorMap<RunningPlaylist>,
+    /// Index of the schedule entry shown on each monitor
+    pub scheduled: MonitorMap<usize>,
+    pub schedule_watcher: Option<ScheduleWatcher>,
     pub config: Config,
+    /// Shader of the custom transition of the config
+    pub custom_transition: Option<Arc<CustomShader>>,
+    /// Custom shaders validated since the config was last loaded
+    pub custom_shaders: CustomShaderCache,
     pub do_force_frame: bool,
+    /// The user is idle, see [`AutoPauseConfig`](waywe_ipc::config::AutoPauseConfig)
+    pub session_idle: bool,
+    /// Rendering limits given the current power source
+    pub power: PowerPolicy,
+    pub power_watcher: Option<PowerWatcher>,
 }
 
 impl WallpaperApp {
@@ -65,36 +148,605 @@ impl WallpaperApp {
         runtime: &Runtime,
         wallpaper: OptimizedWallpaper,
         monitor_id: MonitorId,
+        info: Monitor,
+        placement: Placement,
+        state: WallpaperState,
     ) {
-        match self.wallpapers.entry(monitor_id) {
-            Entry::Vacant(entry) => {
-                let size = {
-                    let monitors = runtime.wayland.client_state.monitors.read().unwrap();
-                    monitors[&monitor_id].size.unwrap()
+        let wallpapers = self.wallpapers.entry(monitor_id).or_insert_with(|| {
+            let size = runtime
+                .wayland
+                .client_state
+                .monitor_physical_size(monitor_id)
+                .unwrap();
+
+            RunningWallpapers::new(
+                monitor_id,
+                size,
+                self.config.animation.clone(),
+                self.custom_transition.clone(),
+            )
+        });
+
+        // NOTE: effects could be changed by a profile since the last wallpaper
+        let effects = self
+            .monitor_effects
+            .get(&monitor_id)
+            .unwrap_or(&self.config.effects);
+
+        wallpapers.effects_builder = EffectsBuilder::new(monitor_id);
+        wallpapers
+            .effects_builder
+            .add_builtins(effects, &mut self.custom_shaders);
+        wallpapers.enqueue_wallpaper(&runtime.wgpu, wallpaper);
+
+        self.wallpaper_states.insert(monitor_id, state);
+
+        if let Some(monitor) = self.monitor_names.get(&monitor_id) {
+            self.subscribers.notify(DaemonEvent::WallpaperSet {
+                monitor: monitor.as_ref().to_owned(),
+                path: info.path.clone(),
+                wallpaper_type: info.wallpaper_type,
+            });
+        }
+
+        self.current.insert(monitor_id, info);
+        self.placements.insert(monitor_id, placement);
+    }
+
+    /// Status of the wallpaper shown on the monitor
+    pub fn status(&mut self, runtime: &Runtime, monitor_id: MonitorId) -> Option<WallpaperStatus> {
+        let info = self.current.get(&monitor_id)?;
+        let monitor = runtime.wayland.client_state.monitor_name(monitor_id)?;
+        let state = self
+            .wallpaper_states
+            .get(&monitor_id)
+            .copied()
+            .unwrap_or_default();
+
+        Some(WallpaperStatus {
+            monitor: monitor.as_ref().to_owned(),
+            path: info.path.clone(),
+            wallpaper_type: info.wallpaper_type,
+            state: state.into(),
+            effects: self
+                .effects(monitor_id)
+                .iter()
+                .map(|effect| effect.name().to_owned())
+                .collect(),
+            fps: self
+                .frame_rates
+                .get_mut(&monitor_id)
+                .map_or(0.0, FrameRateCounter::fps),
+            power: self.power,
+        })
+    }
+
+    /// Checks whether the wallpaper of the monitor is paused to save the battery
+    pub fn is_paused_by_power(&self, monitor_id: MonitorId) -> bool {
+        self.power.paused
+            && self.current.get(&monitor_id).is_some_and(|info| {
+                matches!(
+                    info.wallpaper_type,
+                    WallpaperType::Video | WallpaperType::Scene
+                )
+            })
+    }
+
+    /// Pauses or resumes the wallpaper of the monitor
+    ///
+    /// Paused wallpapers are not rendered, so videos and scenes are not decoded or updated
+    pub fn set_state(&mut self, monitor_id: MonitorId, state: WallpaperState) {
+        let Some(current) = self.wallpaper_states.get_mut(&monitor_id) else {
+            return;
+        };
+
+        if *current == state {
+            return;
+        }
+
+        *current = state;
+
+        if state.is_running()
+            && let Some(wallpapers) = self.wallpapers.get_mut(&monitor_id)
+        {
+            wallpapers.resume();
+        }
+
+        // NOTE: the playlist does not switch paused wallpapers,
+        // so the resumed one is shown for the whole interval
+        if state.is_running()
+            && let Some(playlist) = self.playlists.get_mut(&monitor_id)
+        {
+            playlist.restart();
+        }
+
+        let Some(monitor) = self.monitor_names.get(&monitor_id) else {
+            return;
+        };
+
+        let monitor = monitor.as_ref().to_owned();
+
+        self.subscribers.notify(match state {
+            WallpaperState::Running => DaemonEvent::Resumed { monitor },
+            WallpaperState::Paused => DaemonEvent::Paused { monitor },
+        });
+    }
+
+    /// Effects applied to the wallpapers of the monitor
+    pub fn effects(&self, monitor_id: MonitorId) -> &[Effects] {
+        self.monitor_effects
+            .get(&monitor_id)
+            .unwrap_or(&self.config.effects)
+    }
+
+    /// Current setup of every monitor showing a wallpaper
+    pub fn profile(&self) -> Profile {
+        let mut profile = Profile::default();
+
+        for (monitor_id, info) in &self.current {
+            let Some(name) = self.monitor_names.get(monitor_id) else {
+                continue;
+            };
+
+            let state = self
+                .wallpaper_states
+                .get(monitor_id)
+                .copied()
+                .unwrap_or_default();
+
+            let monitor = MonitorProfile {
+                path: info.path.clone(),
+                wallpaper_type: info.wallpaper_type,
+                paused: state.is_paused(),
+                placement: self.placements.get(monitor_id).copied().unwrap_or_default(),
+                effects: Some(self.effects(*monitor_id).to_vec()),
+                playlist: self
+                    .playlists
+                    .get(monitor_id)
+                    .map(|running| running.playlist.clone()),
+            };
+
+            profile = profile.with(name.as_ref().to_owned(), monitor);
+        }
+
+        profile
+    }
+
+    /// Shows the wallpaper of the monitor setup stored in a profile
+    fn load_monitor(
+        &mut self,
+        runtime: &Runtime,
+        monitor_id: MonitorId,
+        monitor: MonitorProfile,
+        response: PendingResponse,
+    ) {
+        match monitor.effects {
+            Some(effects) => _ = self.monitor_effects.insert(monitor_id, effects),
+            None => _ = self.monitor_effects.remove(&monitor_id),
+        }
+
+        let from_playlist = monitor.playlist.is_some();
+
+        match monitor.playlist {
+            Some(playlist) => {
+                _ = self
+                    .playlists
+                    .insert(monitor_id, RunningPlaylist::new(playlist))
+            }
+            None => _ = self.playlists.remove(&monitor_id),
+        }
+
+        let state = if monitor.paused {
+            WallpaperState::Paused
+        } else {
+            WallpaperState::Running
+        };
+
+        let event = NewWallpaperEvent {
+            path: monitor.path,
+            ty: monitor.wallpaper_type,
+            placement: monitor.placement,
+            target: WallpaperTarget::ForMonitor(monitor_id),
+            state,
+            from_playlist,
+            response,
+        };
+
+        runtime.task_pool.emitter.emit(event).unwrap();
+    }
+
+    /// Lays the spanned wallpaper out again over the current monitors.
+    /// Only the monitors whose part of the wallpaper changed get it anew.
+    /// Returns `false` if no wallpaper is spanned
+    fn respan(&mut self, runtime: &mut Runtime) -> bool {
+        let state = self
+            .wallpaper_states
+            .values()
+            .copied()
+            .next()
+            .unwrap_or_default();
+
+        let Some(spanned) = &mut self.spanned else {
+            return false;
+        };
+
+        let viewports = spanned.current_viewports(&runtime.wayland);
+
+        let changed: Vec<(MonitorId, Viewport)> = viewports
+            .iter()
+            .filter(|viewport| !spanned.viewports.contains(viewport))
+            .copied()
+            .collect();
+
+        spanned.viewports = viewports;
+
+        if changed.is_empty() {
+            return true;
+        }
+
+        let spanned = spanned.clone();
+
+        for &(monitor_id, _) in &changed {
+            self.store_session(
+                runtime,
+                monitor_id,
+                &spanned.path,
+                spanned.ty,
+                spanned.placement,
+                state,
+            );
+        }
+
+        Self::spawn_spanned(
+            runtime,
+            &spanned,
+            changed,
+            state,
+            PendingResponse::detached(),
+        );
+
+        true
+    }
+
+    /// Creates the spanned wallpaper on the monitors of the `viewports`,
+    /// its source is decoded by the first task and shared by the later ones
+    fn spawn_spanned(
+        runtime: &mut Runtime,
+        spanned: &SpannedWallpaper,
+        viewports: Vec<(MonitorId, Viewport)>,
+        state: WallpaperState,
+        response: PendingResponse,
+    ) {
+        let SpannedWallpaper {
+            path,
+            ty,
+            placement,
+            source,
+            ..
+        } = spanned.clone();
+
+        let info = Monitor {
+            wallpaper_type: ty,
+            path: path.clone(),
+        };
+
+        let gpu = Arc::clone(&runtime.wgpu);
+        let wayland = Arc::clone(&runtime.wayland);
+
+        runtime.task_pool.spawn(move |mut emitter| {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                let source = {
+                    let mut source = source.lock().unwrap_or_else(PoisonError::into_inner);
+
+                    match &*source {
+                        Some(source) => source.clone(),
+                        None => source.insert(SpanSource::open(&path, ty)?).clone(),
+                    }
                 };
-                let mut wallpapers =
-                    RunningWallpapers::new(monitor_id, size, self.config.animation.clone());
 
-                wallpapers
-                    .effects_builder
-                    .add_builtins(&self.config.effects);
-                wallpapers.enqueue_wallpaper(&runtime.wgpu, wallpaper);
+                wallpaper::create_spanned(gpu, wayland, &source, placement, &viewports)
+            }));
+
+            let reason = match result {
+                Ok(Ok(wallpapers)) => {
+                    for (monitor_id, wallpaper) in wallpapers {
+                        let event = WallpaperPreparedEvent {
+                            wallpaper,
+                            monitor_id,
+                            info: info.clone(),
+                            placement,
+                            state,
+                        };
+
+                        emitter.emit(event).unwrap();
+                    }
 
-                entry.insert(wallpapers);
+                    return;
+                }
+                Ok(Err(error)) => error.to_string(),
+                Err(payload) => panic_message(payload.as_ref()),
+            };
+
+            report_failure(&mut emitter, path, reason, &response);
+        });
+    }
+
+    /// Stores the wallpaper of the monitor in the session
+    fn store_session(
+        &self,
+        runtime: &Runtime,
+        monitor_id: MonitorId,
+        path: &Path,
+        ty: WallpaperType,
+        placement: Placement,
+        state: WallpaperState,
+    ) {
+        let Some(monitor_name) = runtime.wayland.client_state.monitor_name(monitor_id) else {
+            return;
+        };
+
+        let session_monitor = MonitorProfile {
+            path: path.to_owned(),
+            wallpaper_type: ty,
+            paused: state.is_paused(),
+            placement,
+            effects: self.monitor_effects.get(&monitor_id).cloned(),
+            playlist: self
+                .playlists
+                .get(&monitor_id)
+                .map(|running| running.playlist.clone()),
+        };
+
+        if let Err(error) = Profile::default()
+            .with(monitor_name.as_ref().to_owned(), session_monitor)
+            .store_session()
+        {
+            error!(?error, "failed to save setup profile");
+        }
+    }
+
+    /// Applies the new config to the running wallpapers
+    fn apply_config(
+        &mut self,
+        runtime: &mut Runtime,
+        config: Config,
+        custom_transition: Option<Arc<CustomShader>>,
+        custom_shaders: CustomShaderCache,
+    ) {
+        let old_config = mem::replace(&mut self.config, config);
+        self.custom_transition = custom_transition;
+        self.custom_shaders = custom_shaders;
+
+        for (monitor_id, wallpapers) in &mut self.wallpapers {
+            let effects = self
+                .monitor_effects
+                .get(monitor_id)
+                .unwrap_or(&self.config.effects);
+
+            wallpapers.set_config(
+                self.config.animation.clone(),
+                self.custom_transition.clone(),
+            );
+            wallpapers.set_effects(&runtime.wgpu, effects, &mut self.custom_shaders);
+        }
+
+        let idle_timeout = self.config.auto_pause.idle_timeout();
+
+        if idle_timeout != old_config.auto_pause.idle_timeout() {
+            runtime.wayland.notify_idle(idle_timeout);
+            self.session_idle = false;
+        }
+
+        self.set_power_policy(self.config.power.policy(self.power.source));
+
+        if self.config.power != old_config.power {
+            self.restart_power_watcher(runtime);
+        }
+
+        if self.config.schedule != old_config.schedule
+            || self.config.location != old_config.location
+        {
+            self.restart_schedule(runtime);
+        }
+
+        runtime.control_flow.busy();
+    }
+
+    /// Restarts the schedule watcher and shows the wallpapers of the active entries
+    fn restart_schedule(&mut self, runtime: &mut Runtime) {
+        self.scheduled.clear();
+        self.schedule_watcher = None;
+
+        if self.config.schedule.is_empty() {
+            return;
+        }
+
+        if self.config.location.is_none()
+            && self
+                .config
+                .schedule
+                .iter()
+                .any(|entry| entry.depends_on_sun())
+        {
+            warn!("schedule uses sunrise or sunset, but no location is configured");
+        }
+
+        self.schedule_watcher = Some(schedule::watch_schedule(
+            &mut runtime.task_pool,
+            self.config.schedule.clone(),
+            self.config.location,
+        ));
+
+        let monitor_ids: SmallVec<[MonitorId; 4]> = self.monitor_names.keys().copied().collect();
+
+        for monitor_id in monitor_ids {
+            self.apply_schedule(runtime, monitor_id);
+        }
+    }
+
+    /// Restarts the power source watcher with the poll interval of the config
+    fn restart_power_watcher(&mut self, runtime: &mut Runtime) {
+        // NOTE: the previous watcher stops once dropped here
+        self.power_watcher = Some(power::watch_power_source(
+            &mut runtime.task_pool,
+            self.power.source,
+            self.config.power.poll_interval(),
+        ));
+    }
+
+    fn set_power_policy(&mut self, policy: PowerPolicy) {
+        let was_paused = self.power.paused;
+        self.power = policy;
+
+        if was_paused && !self.power.paused {
+            for wallpapers in self.wallpapers.values_mut() {
+                wallpapers.resume();
             }
-            Entry::Occupied(mut occupied_entry) => occupied_entry
-                .get_mut()
-                .enqueue_wallpaper(&runtime.wgpu, wallpaper),
         }
+    }
+
+    /// Shows the wallpaper of the schedule entry active on the monitor unless it is shown already.
+    /// Returns `false` if no entry is active on the monitor
+    fn apply_schedule(&mut self, runtime: &Runtime, monitor_id: MonitorId) -> bool {
+        let Some(monitor_name) = self.monitor_names.get(&monitor_id) else {
+            return false;
+        };
+
+        let now = LocalTime::now();
+        let sun = SunTimes::new(self.config.location, &now);
+
+        let Some(index) = active_entry(&self.config.schedule, &sun, monitor_name, now.time) else {
+            _ = self.scheduled.remove(&monitor_id);
+            return false;
+        };
+
+        // NOTE: the wallpaper set by the user stays until the next entry starts
+        if self.scheduled.insert(monitor_id, index) == Some(index) {
+            return true;
+        }
+
+        let entry = &self.config.schedule[index];
 
+        debug!(?monitor_id, path = ?entry.path, "showing the scheduled wallpaper");
+
+        // NOTE: a monitor paused by the user stays paused, the new wallpaper is still
+        // drawn once, see `RunningWallpapers::needs_first_frame`
+        let state = self
+            .wallpaper_states
+            .get(&monitor_id)
+            .copied()
+            .unwrap_or_default();
+
+        let event = NewWallpaperEvent {
+            path: entry.path.clone(),
+            ty: entry.wallpaper_type,
+            placement: Placement::default(),
+            target: WallpaperTarget::ForMonitor(monitor_id),
+            state,
+            from_playlist: false,
+            response: PendingResponse::detached(),
+        };
+
+        runtime.task_pool.emitter.emit(event).unwrap();
+
+        true
+    }
+
+    fn is_paused(&self, monitor_id: MonitorId) -> bool {
         self.wallpaper_states
-            .insert(monitor_id, WallpaperState::Running);
+            .get(&monitor_id)
+            .is_some_and(|state| state.is_paused())
+    }
+
+    /// Checks whether frames of the monitor are not rendered: its wallpaper is paused
+    /// by the user or to save the battery, or the surface is covered
+    fn is_frame_skipped(&self, runtime: &Runtime, monitor_id: MonitorId) -> bool {
+        let auto_pause = &self.config.auto_pause;
+
+        self.is_paused(monitor_id)
+            || self.is_paused_by_power(monitor_id)
+            || auto_pause.when_covered
+                && runtime
+                    .wayland
+                    .client_state
+                    .is_surface_hidden(monitor_id, auto_pause.covered_timeout())
+    }
+
+    /// Shows the wallpaper `step` entries away in the playlist of the monitor
+    fn step_playlist(
+        &mut self,
+        runtime: &Runtime,
+        monitor_id: MonitorId,
+        step: isize,
+        response: PendingResponse,
+    ) {
+        let Some(entry) = self
+            .playlists
+            .get_mut(&monitor_id)
+            .and_then(|playlist| playlist.step(step))
+        else {
+            return;
+        };
+
+        let state = self
+            .wallpaper_states
+            .get(&monitor_id)
+            .copied()
+            .unwrap_or_default();
+
+        let event = NewWallpaperEvent {
+            path: entry.path,
+            ty: entry.wallpaper_type,
+            placement: Placement::default(),
+            target: WallpaperTarget::ForMonitor(monitor_id),
+            state,
+            from_playlist: true,
+            response,
+        };
+
+        runtime.task_pool.emitter.emit(event).unwrap();
+    }
+
+    /// Switches the playlists whose interval has passed
+    /// and wakes the daemon up for the next switch
+    ///
+    /// Playlists of monitors with skipped frames wait until the monitor renders again,
+    /// so wallpapers nobody sees are not queued up
+    fn rotate_playlists(&mut self, runtime: &mut Runtime) {
+        let now = Instant::now();
+
+        let due: SmallVec<[MonitorId; 4]> = self
+            .playlists
+            .iter()
+            .filter(|&(&monitor_id, playlist)| {
+                playlist.is_due(now) && !self.is_frame_skipped(runtime, monitor_id)
+            })
+            .map(|(&monitor_id, _)| monitor_id)
+            .collect();
+
+        for monitor_id in due {
+            self.step_playlist(runtime, monitor_id, 1, PendingResponse::detached());
+        }
+
+        let next_switch = self
+            .playlists
+            .iter()
+            .filter(|&(&monitor_id, _)| !self.is_frame_skipped(runtime, monitor_id))
+            .map(|(_, playlist)| playlist.next_switch)
+            .min();
+
+        if let Some(time) = next_switch {
+            runtime.timer.wake_up_at(time);
+        }
     }
 }
 
 pub struct WallpaperPreparedEvent {
     pub wallpaper: OptimizedWallpaper,
     pub monitor_id: MonitorId,
+    pub info: Monitor,
+    pub placement: Placement,
+    pub state: WallpaperState,
 }
 
 impl TryReplicate for WallpaperPreparedEvent {}
@@ -103,12 +755,89 @@ impl TryReplicate for WallpaperPreparedEvent {}
 pub struct NewWallpaperEvent {
     pub path: PathBuf,
     pub ty: WallpaperType,
+    pub placement: Placement,
     pub target: WallpaperTarget,
+    /// State the wallpaper starts in
+    pub state: WallpaperState,
+    /// The wallpaper is the next entry of the monitor playlist,
+    /// otherwise the playlist is stopped
+    pub from_playlist: bool,
+    /// Response to the cli, sent once the wallpaper is loaded on every target monitor
+    pub response: PendingResponse,
+}
+
+/// Image or video shown across all monitors, see [`Placement::span`]
+#[derive(Clone)]
+pub struct SpannedWallpaper {
+    pub path: PathBuf,
+    pub ty: WallpaperType,
+    pub placement: Placement,
+    /// Decoded by the first task creating the wallpaper and reused by the later layouts
+    pub source: Arc<Mutex<Option<SpanSource>>>,
+    /// Parts of the wallpaper shown on the monitors, the overwritten ones excluded
+    pub viewports: Vec<(MonitorId, Viewport)>,
+    /// Monitors showing another wallpaper set after this one
+    pub overwritten: BTreeSet<MonitorId>,
+}
+
+impl SpannedWallpaper {
+    /// Viewports of the monitors still showing the wallpaper
+    fn current_viewports(&self, wayland: &Wayland) -> Vec<(MonitorId, Viewport)> {
+        // NOTE: the overwritten monitors keep their place in the layout,
+        // so the rest of the wallpaper stays where it was
+        wallpaper::span_viewports(wayland)
+            .into_iter()
+            .filter(|(monitor_id, _)| !self.overwritten.contains(monitor_id))
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PauseAction {
+    Pause,
+    Resume,
+    /// Pause if any of the target wallpapers is running, resume otherwise
+    Toggle,
 }
 
 #[derive(Clone)]
 pub struct WallpaperPauseEvent {
     pub target: WallpaperTarget,
+    pub action: PauseAction,
+}
+
+/// Query of the wallpaper status sent by the cli
+#[derive(Clone)]
+pub struct WallpaperStatusEvent {
+    pub target: WallpaperTarget,
+    pub response: PendingResponse,
+}
+
+/// The cli subscribed to [`DaemonEvent`]s
+pub struct SubscribeEvent {
+    pub responder: IpcResponder,
+}
+
+impl TryReplicate for SubscribeEvent {}
+
+/// Wallpaper failed to load in the task pool
+#[derive(Clone)]
+pub struct WallpaperFailedEvent {
+    pub response: DaemonResponse,
+}
+
+/// The cli asked to store the current setup as a named profile
+#[derive(Clone)]
+pub struct SaveProfileEvent {
+    pub name: String,
+    pub response: PendingResponse,
+}
+
+/// The cli asked to show the wallpapers of a named profile
+#[derive(Clone)]
+pub struct LoadProfileEvent {
+    pub name: String,
+    pub response: PendingResponse,
 }
 
 impl App for WallpaperApp {
@@ -117,7 +846,49 @@ impl App for WallpaperApp {
             .add_event::<WaylandEvent>()
             .add_event::<NewWallpaperEvent>()
             .add_event::<WallpaperPreparedEvent>()
-            .add_event::<WallpaperPauseEvent>();
+            .add_event::<WallpaperPauseEvent>()
+            .add_event::<WallpaperStatusEvent>()
+            .add_event::<SubscribeEvent>()
+            .add_event::<WallpaperFailedEvent>()
+            .add_event::<SaveProfileEvent>()
+            .add_event::<LoadProfileEvent>()
+            .add_event::<PowerSourceChangedEvent>()
+            .add_event::<SetPlaylistEvent>()
+            .add_event::<PlaylistStepEvent>()
+            .add_event::<ScheduleEvent>()
+            .add_event::<ReloadConfigEvent>();
+    }
+
+    async fn init(&mut self, runtime: &mut Runtime) {
+        runtime
+            .wayland
+            .notify_idle(self.config.auto_pause.idle_timeout());
+
+        let source = PowerSource::read().unwrap_or_else(|error| {
+            warn!(?error, "failed to read the power source, assuming ac");
+            PowerSource::Ac
+        });
+
+        self.power = self.config.power.policy(source);
+
+        // the watcher reports the actual source once it can be read
+        self.restart_power_watcher(runtime);
+
+        // NOTE: the first wallpapers take the validated effects from the cache
+        if let Err(error) =
+            reload::load_custom_effects(&self.config.effects, &mut self.custom_shaders)
+        {
+            error!(%error, "failed to compile custom effect");
+        }
+
+        match reload::load_custom_transition(&self.config, &mut self.custom_shaders) {
+            Ok(shader) => self.custom_transition = shader,
+            Err(error) => {
+                error!(%error, "failed to compile custom transition, using the default one")
+            }
+        }
+
+        self.restart_schedule(runtime);
     }
 
     async fn frame(&mut self, runtime: &mut Runtime) -> Result<FrameInfo, FrameError> {
@@ -125,14 +896,37 @@ impl App for WallpaperApp {
             return Err(FrameError::NoWorkToDo);
         }
 
+        // NOTE: the daemon sleeps until the compositor reports the user is back
+        if self.session_idle {
+            runtime.control_flow.idle();
+            return Err(FrameError::NoWorkToDo);
+        }
+
+        self.rotate_playlists(runtime);
+
         // FIXME(hack3rmann): multiple monitors
         let mut result = Err(FrameError::NoWorkToDo);
 
+        let skipped: SmallVec<[MonitorId; 4]> = self
+            .wallpapers
+            .keys()
+            .copied()
+            .filter(|&monitor_id| self.is_frame_skipped(runtime, monitor_id))
+            .collect();
+
         for (&monitor_id, wallpapers) in self.wallpapers.iter_mut() {
-            if let Some(&state) = self.wallpaper_states.get(&monitor_id)
-                && state.is_paused()
-            {
-                continue;
+            let is_skipped = skipped.contains(&monitor_id);
+
+            if is_skipped {
+                // the monitor reports no frame rate until it renders again
+                _ = self.frame_rates.remove(&monitor_id);
+
+                if !wallpapers.needs_first_frame {
+                    continue;
+                }
+
+                // NOTE: a transition frozen at its start would hide the new wallpaper
+                wallpapers.skip_transitions();
             }
 
             let surface = {
@@ -145,12 +939,38 @@ impl App for WallpaperApp {
                 .device
                 .create_command_encoder(&Default::default());
 
+            let n_transitions = wallpapers.ongoing_transitions.len();
+
             result = wallpapers.render(&runtime.wgpu, &surface.texture, &mut encoder);
 
+            if result.is_ok() && !is_skipped {
+                self.frame_rates.entry(monitor_id).or_default().tick();
+            }
+
+            if wallpapers.ongoing_transitions.len() < n_transitions
+                && let Some(monitor) = self.monitor_names.get(&monitor_id)
+            {
+                self.subscribers.notify(DaemonEvent::TransitionFinished {
+                    monitor: monitor.as_ref().to_owned(),
+                });
+            }
+
             runtime.wgpu.queue.submit([encoder.finish()]);
+
+            if self.config.auto_pause.when_covered {
+                runtime.wayland.request_frame(monitor_id);
+            }
+
             surface.present();
         }
 
+        if let Ok(info) = &mut result
+            && let Some(max_fps) = self.power.max_fps
+            && let Some(frame_time) = &mut info.target_frame_time
+        {
+            *frame_time = (*frame_time).max(Duration::from_secs(1) / max_fps);
+        }
+
         if let Err(FrameError::NoWorkToDo) = &result {
             runtime.control_flow.idle();
         } else {
@@ -165,7 +985,58 @@ impl App for WallpaperApp {
 
 impl Handle<WallpaperPauseEvent> for WallpaperApp {
     async fn handle(&mut self, runtime: &mut Runtime, event: WallpaperPauseEvent) {
-        let WallpaperPauseEvent { target } = event;
+        let WallpaperPauseEvent { target, action } = event;
+
+        let monitor_ids: SmallVec<[MonitorId; 4]> = match target {
+            WallpaperTarget::ForAll => {
+                let monitors = runtime.wayland.client_state.monitors.read().unwrap();
+                monitors.keys().copied().collect()
+            }
+            WallpaperTarget::ForMonitor(id) => smallvec![id],
+        };
+
+        // NOTE: toggling every monitor on its own desynchronizes them
+        let state = match action {
+            PauseAction::Pause => WallpaperState::Paused,
+            PauseAction::Resume => WallpaperState::Running,
+            PauseAction::Toggle => {
+                let any_running = monitor_ids.iter().any(|monitor_id| {
+                    self.wallpaper_states
+                        .get(monitor_id)
+                        .is_some_and(|state| state.is_running())
+                });
+
+                if any_running {
+                    WallpaperState::Paused
+                } else {
+                    WallpaperState::Running
+                }
+            }
+        };
+
+        for monitor_id in monitor_ids {
+            self.set_state(monitor_id, state);
+        }
+    }
+}
+
+impl Handle<PowerSourceChangedEvent> for WallpaperApp {
+    async fn handle(&mut self, runtime: &mut Runtime, event: PowerSourceChangedEvent) {
+        self.set_power_policy(self.config.power.policy(event.source));
+
+        debug!(power = %self.power, "applied the power policy");
+
+        runtime.control_flow.busy();
+    }
+}
+
+impl Handle<SetPlaylistEvent> for WallpaperApp {
+    async fn handle(&mut self, runtime: &mut Runtime, event: SetPlaylistEvent) {
+        let SetPlaylistEvent {
+            playlist,
+            target,
+            response,
+        } = event;
 
         let monitor_ids: SmallVec<[MonitorId; 4]> = match target {
             WallpaperTarget::ForAll => {
@@ -176,11 +1047,181 @@ impl Handle<WallpaperPauseEvent> for WallpaperApp {
         };
 
         for monitor_id in monitor_ids {
-            let Some(state) = self.wallpaper_states.get_mut(&monitor_id) else {
+            self.playlists
+                .insert(monitor_id, RunningPlaylist::new(playlist.clone()));
+            self.step_playlist(runtime, monitor_id, 0, response.clone());
+        }
+    }
+}
+
+impl Handle<PlaylistStepEvent> for WallpaperApp {
+    async fn handle(&mut self, runtime: &mut Runtime, event: PlaylistStepEvent) {
+        let PlaylistStepEvent {
+            target,
+            step,
+            response,
+        } = event;
+
+        let monitor_ids: SmallVec<[MonitorId; 4]> = match target {
+            WallpaperTarget::ForAll => self.playlists.keys().copied().collect(),
+            WallpaperTarget::ForMonitor(id) if self.playlists.contains_key(&id) => smallvec![id],
+            WallpaperTarget::ForMonitor(..) => smallvec![],
+        };
+
+        if monitor_ids.is_empty() {
+            response.fail(DaemonResponse::NoPlaylist);
+            return;
+        }
+
+        for monitor_id in monitor_ids {
+            self.step_playlist(runtime, monitor_id, step, response.clone());
+        }
+    }
+}
+
+impl Handle<ReloadConfigEvent> for WallpaperApp {
+    async fn handle(&mut self, runtime: &mut Runtime, event: ReloadConfigEvent) {
+        let config = match Config::path().and_then(|path| Config::read_from(&path)) {
+            Ok(config) => config,
+            Err(error) => {
+                error!(%error, "failed to reload config");
+
+                let failure = DaemonResponse::ConfigFailed {
+                    reason: error.to_string(),
+                };
+
+                self.subscribers.notify(DaemonEvent::Error {
+                    message: failure.to_string(),
+                });
+
+                event.response.fail(failure);
+                return;
+            }
+        };
+
+        // NOTE: shaders are read again on reload, so edited files are picked up
+        let mut custom_shaders = CustomShaderCache::new();
+
+        let custom_transition =
+            match reload::load_custom_effects(&config.effects, &mut custom_shaders)
+                .and_then(|()| reload::load_custom_transition(&config, &mut custom_shaders))
+            {
+                Ok(shader) => shader,
+                Err(error) => {
+                    error!(%error, "failed to compile custom shader");
+
+                    let failure = match error.path() {
+                        Some(path) => DaemonResponse::ShaderFailed {
+                            path: path.to_owned(),
+                            reason: error.reason(),
+                        },
+                        None => DaemonResponse::ConfigFailed {
+                            reason: error.to_string(),
+                        },
+                    };
+
+                    self.subscribers.notify(DaemonEvent::Error {
+                        message: failure.to_string(),
+                    });
+
+                    event.response.fail(failure);
+                    return;
+                }
+            };
+
+        self.apply_config(runtime, config, custom_transition, custom_shaders);
+
+        debug!("reloaded config");
+        self.subscribers.notify(DaemonEvent::ConfigReloaded);
+    }
+}
+
+impl Handle<ScheduleEvent> for WallpaperApp {
+    async fn handle(&mut self, runtime: &mut Runtime, event: ScheduleEvent) {
+        if event.resumed {
+            debug!("woke up from suspend, recomputing the schedule");
+        }
+
+        let monitor_ids: SmallVec<[MonitorId; 4]> = self.monitor_names.keys().copied().collect();
+
+        for monitor_id in monitor_ids {
+            self.apply_schedule(runtime, monitor_id);
+        }
+    }
+}
+
+impl Handle<WallpaperStatusEvent> for WallpaperApp {
+    async fn handle(&mut self, runtime: &mut Runtime, event: WallpaperStatusEvent) {
+        let WallpaperStatusEvent { target, response } = event;
+
+        let monitor_ids: SmallVec<[MonitorId; 4]> = match target {
+            WallpaperTarget::ForAll => self.current.keys().copied().collect(),
+            WallpaperTarget::ForMonitor(id) => smallvec![id],
+        };
+
+        let statuses = monitor_ids
+            .into_iter()
+            .filter_map(|monitor_id| self.status(runtime, monitor_id))
+            .collect();
+
+        response.reply(DaemonResponse::Status(statuses));
+    }
+}
+
+impl Handle<SubscribeEvent> for WallpaperApp {
+    async fn handle(&mut self, _runtime: &mut Runtime, event: SubscribeEvent) {
+        self.subscribers.subscribe(event.responder);
+    }
+}
+
+impl Handle<WallpaperFailedEvent> for WallpaperApp {
+    async fn handle(&mut self, _runtime: &mut Runtime, event: WallpaperFailedEvent) {
+        self.subscribers.notify(DaemonEvent::Error {
+            message: event.response.to_string(),
+        });
+    }
+}
+
+impl Handle<SaveProfileEvent> for WallpaperApp {
+    async fn handle(&mut self, _runtime: &mut Runtime, event: SaveProfileEvent) {
+        let SaveProfileEvent { name, response } = event;
+
+        if let Err(error) = self.profile().store(&name) {
+            error!(?error, name, "failed to save profile");
+
+            response.fail(DaemonResponse::ProfileFailed {
+                name,
+                reason: error.to_string(),
+            });
+        }
+    }
+}
+
+impl Handle<LoadProfileEvent> for WallpaperApp {
+    async fn handle(&mut self, runtime: &mut Runtime, event: LoadProfileEvent) {
+        let LoadProfileEvent { name, response } = event;
+
+        let profile = match Profile::read(&name) {
+            Ok(profile) => profile,
+            Err(error) => {
+                error!(?error, name, "failed to load profile");
+
+                response.fail(DaemonResponse::ProfileFailed {
+                    name,
+                    reason: error.to_string(),
+                });
+
+                return;
+            }
+        };
+
+        for (monitor_name, monitor) in profile.monitors {
+            let Some(monitor_id) = runtime.wayland.client_state.monitor_id(&monitor_name) else {
+                debug!(monitor_name, "skipping unplugged monitor of the profile");
                 continue;
             };
 
-            *state = state.inverted();
+            self.load_monitor(runtime, monitor_id, monitor, response.clone());
         }
     }
 }
@@ -190,10 +1231,13 @@ impl Handle<WallpaperPreparedEvent> for WallpaperApp {
         let WallpaperPreparedEvent {
             wallpaper,
             monitor_id,
+            info,
+            placement,
+            state,
         } = event;
 
         runtime.control_flow.busy();
-        self.set_wallpaper(runtime, wallpaper, monitor_id);
+        self.set_wallpaper(runtime, wallpaper, monitor_id, info, placement, state);
     }
 }
 
@@ -205,6 +1249,11 @@ impl Handle<WaylandEvent> for WallpaperApp {
                     runtime.wgpu.resize_surface(monitor_id, size);
                 }
 
+                if let Some(wallpapers) = self.wallpapers.get_mut(&monitor_id) {
+                    wallpapers.resize(size);
+                }
+
+                self.respan(runtime);
                 self.do_force_frame = true;
             }
             WaylandEvent::MonitorPlugged { id: monitor_id } => {
@@ -212,22 +1261,25 @@ impl Handle<WaylandEvent> for WallpaperApp {
                     runtime.wgpu.register_surface(&runtime.wayland, monitor_id);
                 }
 
-                let monitors = runtime.wayland.client_state.monitors.read().unwrap();
-                let monitor = &monitors[&monitor_id];
-                let monitor_name = Arc::clone(monitor.name.as_ref().unwrap());
+                let monitor_name = {
+                    let monitors = runtime.wayland.client_state.monitors.read().unwrap();
+                    Arc::clone(monitors[&monitor_id].name.as_ref().unwrap())
+                };
 
                 debug!(?monitor_id, ?monitor_name, "new monitor detected");
 
-                if let Ok(mut profile) = SetupProfile::read()
-                    && let Some(info) = profile.monitors.remove(&monitor_name)
-                {
-                    let event = NewWallpaperEvent {
-                        path: info.path,
-                        ty: info.wallpaper_type,
-                        target: WallpaperTarget::ForMonitor(monitor_id),
-                    };
+                self.monitor_names
+                    .insert(monitor_id, Arc::clone(&monitor_name));
+                self.subscribers.notify(DaemonEvent::MonitorAdded {
+                    monitor: monitor_name.as_ref().to_owned(),
+                });
 
-                    runtime.task_pool.emitter.emit(event).unwrap();
+                if !self.respan(runtime)
+                    && !self.apply_schedule(runtime, monitor_id)
+                    && let Ok(mut session) = Profile::read_session()
+                    && let Some(monitor) = session.monitors.remove(monitor_name.as_ref())
+                {
+                    self.load_monitor(runtime, monitor_id, monitor, PendingResponse::detached());
                 }
 
                 runtime.control_flow.busy();
@@ -237,12 +1289,66 @@ impl Handle<WaylandEvent> for WallpaperApp {
 
                 _ = self.wallpapers.remove(&monitor_id);
                 _ = self.wallpaper_states.remove(&monitor_id);
+                _ = self.current.remove(&monitor_id);
+                _ = self.placements.remove(&monitor_id);
+                _ = self.playlists.remove(&monitor_id);
+                _ = self.scheduled.remove(&monitor_id);
+                _ = self.frame_rates.remove(&monitor_id);
+
+                if let Some(monitor) = self.monitor_names.remove(&monitor_id) {
+                    self.subscribers.notify(DaemonEvent::MonitorRemoved {
+                        monitor: monitor.as_ref().to_owned(),
+                    });
+                }
+
+                if let Some(spanned) = &mut self.spanned {
+                    spanned.overwritten.remove(&monitor_id);
+                }
 
                 runtime.wgpu.unregister_surface(monitor_id);
+                self.respan(runtime);
+            }
+            WaylandEvent::SessionIdle => {
+                debug!("user is idle, pausing wallpapers");
+                self.session_idle = true;
+            }
+            WaylandEvent::SessionResumed => {
+                debug!("user is back, resuming wallpapers");
+                self.session_idle = false;
+
+                for wallpapers in self.wallpapers.values_mut() {
+                    wallpapers.resume();
+                }
+
+                runtime.control_flow.busy();
+            }
+            WaylandEvent::SurfaceShown { monitor_id } => {
+                debug!(?monitor_id, "wallpaper is shown again");
+
+                if let Some(wallpapers) = self.wallpapers.get_mut(&monitor_id) {
+                    wallpapers.resume();
+                }
+
+                runtime.control_flow.busy();
             }
             WaylandEvent::CursorMoved { position } => {
                 let event = CursorMoved { position };
 
+                // NOTE: the event does not tell the surface the cursor is on
+                {
+                    let monitors = runtime.wayland.client_state.monitors.read().unwrap();
+
+                    for (monitor_id, wallpapers) in self.wallpapers.iter_mut() {
+                        let scale = monitors
+                            .get(monitor_id)
+                            .map_or(1.0, |monitor| monitor.scale_factor());
+
+                        // surface pixels are physical pixels of the monitor
+                        let physical = (position.as_dvec2() * scale).round().as_uvec2();
+                        wallpapers.set_cursor(physical);
+                    }
+                }
+
                 for wallpaper in self
                     .wallpapers
                     .values_mut()
@@ -259,11 +1365,49 @@ impl Handle<WaylandEvent> for WallpaperApp {
 
 impl Handle<NewWallpaperEvent> for WallpaperApp {
     async fn handle(&mut self, runtime: &mut Runtime, event: NewWallpaperEvent) {
-        let NewWallpaperEvent { path, ty, target } = event;
+        let NewWallpaperEvent {
+            path,
+            ty,
+            placement,
+            target,
+            state,
+            from_playlist,
+            response,
+        } = event;
 
         // FIXME(hack3rmann): remove runtime features
         runtime.enable(RuntimeFeatures::GPU).await;
 
+        // NOTE: the spanned wallpaper covers every monitor whatever the target is
+        let target = if placement.span {
+            WallpaperTarget::ForAll
+        } else {
+            target
+        };
+
+        match target {
+            WallpaperTarget::ForAll => {
+                self.spanned = placement.span.then(|| SpannedWallpaper {
+                    path: path.clone(),
+                    ty,
+                    placement,
+                    source: Arc::default(),
+                    viewports: vec![],
+                    overwritten: BTreeSet::new(),
+                });
+            }
+            WallpaperTarget::ForMonitor(monitor_id) => {
+                if let Some(spanned) = &mut self.spanned {
+                    spanned.overwritten.insert(monitor_id);
+                    spanned.viewports.retain(|&(id, _)| id != monitor_id);
+
+                    if spanned.viewports.is_empty() {
+                        self.spanned = None;
+                    }
+                }
+            }
+        }
+
         let monitor_ids: SmallVec<[MonitorId; 4]> = match target {
             WallpaperTarget::ForAll => {
                 let monitors = runtime.wayland.client_state.monitors.read().unwrap();
@@ -272,34 +1416,89 @@ impl Handle<NewWallpaperEvent> for WallpaperApp {
             WallpaperTarget::ForMonitor(id) => smallvec![id],
         };
 
+        for &monitor_id in &monitor_ids {
+            if !from_playlist {
+                _ = self.playlists.remove(&monitor_id);
+            }
+
+            self.store_session(runtime, monitor_id, &path, ty, placement, state);
+        }
+
+        if placement.span
+            && let Some(spanned) = &mut self.spanned
+        {
+            spanned.viewports = spanned.current_viewports(&runtime.wayland);
+            Self::spawn_spanned(runtime, spanned, spanned.viewports.clone(), state, response);
+            return;
+        }
+
+        let info = Monitor {
+            wallpaper_type: ty,
+            path: path.clone(),
+        };
+
         for monitor_id in monitor_ids {
             let path = path.clone();
+            let info = info.clone();
             let gpu = Arc::clone(&runtime.wgpu);
             let wayland = Arc::clone(&runtime.wayland);
+            let response = response.clone();
 
-            let monitors = runtime.wayland.client_state.monitors.read().unwrap();
-            let monitor = &monitors[&monitor_id];
-            let monitor_name = Arc::clone(monitor.name.as_ref().unwrap());
-            let monitor_profile = Monitor {
-                wallpaper_type: ty,
-                path: path.clone(),
-            };
+            runtime.task_pool.spawn(move |mut emitter| {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    wallpaper::create(gpu, wayland, &path, ty, placement, monitor_id)
+                }));
 
-            if let Err(error) = SetupProfile::default()
-                .with(monitor_name, monitor_profile)
-                .store()
-            {
-                error!(?error, "failed to save setup profile");
-            }
+                let reason = match result {
+                    Ok(Ok(wallpaper)) => {
+                        let event = WallpaperPreparedEvent {
+                            wallpaper,
+                            monitor_id,
+                            info,
+                            placement,
+                            state,
+                        };
 
-            runtime.task_pool.spawn(move |mut emitter| {
-                let event = WallpaperPreparedEvent {
-                    wallpaper: wallpaper::create(gpu, wayland, &path, ty, monitor_id),
-                    monitor_id,
+                        emitter.emit(event).unwrap();
+                        return;
+                    }
+                    Ok(Err(error)) => error.to_string(),
+                    Err(payload) => panic_message(payload.as_ref()),
                 };
 
-                emitter.emit(event).unwrap();
+                report_failure(&mut emitter, path, reason, &response);
             });
         }
     }
 }
+
+/// Logs the failed wallpaper and reports it to the subscribers and the cli
+fn report_failure(
+    emitter: &mut EventEmitter,
+    path: PathBuf,
+    reason: String,
+    response: &PendingResponse,
+) {
+    error!(?path, reason, "failed to create wallpaper");
+
+    let failure = DaemonResponse::DecodeFailed { path, reason };
+
+    emitter
+        .emit(WallpaperFailedEvent {
+            response: failure.clone(),
+        })
+        .unwrap();
+
+    response.fail(failure);
+}
+
+/// Extracts the message from the panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(&message) = payload.downcast_ref::<&str>() {
+        message.to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "wallpaper loading panicked".to_owned()
+    }
+}
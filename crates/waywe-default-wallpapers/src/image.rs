@@ -72,7 +72,7 @@ pub fn setup(
     };
 
     let mesh = meshes.add(Mesh::rect(Vec2::ONE));
-    let image = images.add(Image { image });
+    let image = images.add(Image::from(image));
     let material = materials.add(ImageMaterial { image });
 
     commands.spawn((
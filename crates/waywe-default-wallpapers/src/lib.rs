@@ -1,5 +1,7 @@
 pub mod image;
+pub mod scene;
 pub mod video;
 
 pub use image::ImageWallpaper;
+pub use scene::SceneWallpaper;
 pub use video::VideoWallpaper;
@@ -0,0 +1,460 @@
+//! Wallpaper Engine scene wallpaper implementation.
+//!
+//! This module loads a Wallpaper Engine project (`project.json` and `scene.json`,
+//! optionally packed into `scene.pkg`) and shows its image layers as textured quads.
+//! Only image layers and animated textures are supported for now.
+
+use bevy_ecs::prelude::*;
+use extractor::package::{MappedPackageArchive, PackageArchive, PackageExtractError};
+use project_parser::{
+    project::{PROJECT_FILE_NAME, Project, ProjectError, ProjectType},
+    scene::{Material, Model, Scene, SceneObject},
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    str::{self, Utf8Error},
+    sync::Arc,
+};
+use thiserror::Error;
+use tracing::debug;
+use waywe_scene::{
+    animation::AnimatedImage, clear_screen::ClearColor, image::ImageLoadError, prelude::*,
+    sprite::QuadMesh,
+};
+
+const PACKAGE_FILE_NAME: &str = "scene.pkg";
+const DEFAULT_SCENE_FILE_NAME: &str = "scene.json";
+
+#[derive(Debug, Error)]
+pub enum SceneLoadError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Project(#[from] ProjectError),
+    #[error(transparent)]
+    Package(#[from] PackageExtractError),
+    #[error(transparent)]
+    Image(#[from] ImageLoadError),
+    #[error(transparent)]
+    Utf8(#[from] Utf8Error),
+    #[error("'{0}' is not a wallpaper engine project")]
+    NotAProject(PathBuf),
+    #[error("unsupported project type '{0:?}'")]
+    UnsupportedProject(ProjectType),
+    #[error("scene has no orthogonal projection")]
+    NoProjection,
+}
+
+/// Image layer of the scene prepared for spawning.
+#[derive(Debug, Clone)]
+pub struct SceneLayer {
+    /// Name of the object in the scene.
+    pub name: String,
+    /// Decoded base texture of the layer, a single frame for static textures.
+    pub animation: AnimatedImage,
+    /// Center of the layer in scene pixels.
+    pub origin: Vec3,
+    /// Size of the layer in scene pixels with the object scale applied.
+    pub size: Vec2,
+    /// Rotation in radians.
+    pub angles: Vec3,
+}
+
+/// A wallpaper that displays a Wallpaper Engine scene.
+///
+/// All files are read and decoded by [`SceneWallpaper::open`], so building
+/// the wallpaper itself can not fail.
+pub struct SceneWallpaper {
+    /// Size of the scene in scene pixels.
+    pub projection: UVec2,
+    /// Color the screen is cleared with.
+    pub clear_color: Vec3,
+    /// Visible image layers in draw order.
+    pub layers: Vec<SceneLayer>,
+    /// Directory of the project.
+    pub project_directory: PathBuf,
+    /// Package the scene is stored in, mounted into the [`AssetServer`] at the project directory.
+    pub package: Option<Arc<MappedPackageArchive>>,
+}
+
+impl SceneWallpaper {
+    /// Loads the project located at `path`.
+    ///
+    /// `path` is either the project directory or its `project.json` or `scene.pkg`.
+    pub fn open(path: &Path) -> Result<Self, SceneLoadError> {
+        let project_directory = project_directory(path)?;
+        let project = Project::read(&project_directory)?;
+
+        if project.ty != ProjectType::Scene {
+            return Err(SceneLoadError::UnsupportedProject(project.ty));
+        }
+
+        let scene_file = project
+            .file
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_SCENE_FILE_NAME));
+
+        let package_path = project_directory.join(PACKAGE_FILE_NAME);
+
+        let files = if !project_directory.join(&scene_file).exists() && package_path.exists() {
+            debug!(?package_path, "reading scene from package");
+            SceneFiles::Package(Arc::new(PackageArchive::map(&package_path)?))
+        } else {
+            SceneFiles::Directory(project_directory.clone())
+        };
+
+        let scene = Scene::from_json(str::from_utf8(&files.read(&scene_file)?)?)?;
+
+        let projection = scene
+            .general
+            .orthogonalprojection
+            .ok_or(SceneLoadError::NoProjection)?;
+
+        let clear_color = scene
+            .general
+            .clearcolor
+            .as_ref()
+            .map_or(Vec3::ZERO, |color| Vec3::from_array(color.value().0));
+
+        Ok(Self {
+            projection: UVec2::new(projection.width, projection.height),
+            clear_color,
+            layers: load_layers(&files, &scene)?,
+            project_directory,
+            package: match files {
+                SceneFiles::Package(archive) => Some(archive),
+                SceneFiles::Directory(_) => None,
+            },
+        })
+    }
+}
+
+/// Storage of the project files.
+enum SceneFiles {
+    /// Files are stored in the project directory.
+    Directory(PathBuf),
+    /// Files are packed into `scene.pkg`.
+    Package(Arc<MappedPackageArchive>),
+}
+
+impl SceneFiles {
+    /// Reads the file at `path` relative to the project root.
+    fn read(&self, path: &Path) -> Result<Cow<'_, [u8]>, SceneLoadError> {
+        match self {
+            Self::Directory(root) => Ok(Cow::Owned(fs::read(root.join(path))?)),
+            Self::Package(archive) => {
+                let name = package_entry_name(path);
+
+                match archive.get(&name) {
+                    Some(bytes) => Ok(Cow::Borrowed(bytes)),
+                    None => Err(PackageExtractError::NotFound(name).into()),
+                }
+            }
+        }
+    }
+}
+
+/// Package entries are named by their path with `/` separators
+fn package_entry_name(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Resource that holds the scene during initialization.
+#[derive(Resource)]
+pub struct SceneLayout {
+    /// Size of the scene in scene pixels.
+    pub projection: UVec2,
+    /// Layers not yet spawned.
+    pub layers: Vec<SceneLayer>,
+}
+
+impl WallpaperBuilder for SceneWallpaper {
+    /// Build the scene wallpaper by setting up the scene.
+    ///
+    /// This adds the default plugins, sets the clear color, inserts the
+    /// prepared layers as a resource and adds the setup system to the startup schedule.
+    fn build(self, wallpaper: &mut Wallpaper) {
+        wallpaper.add_plugins(DefaultPlugins);
+
+        if let Some(package) = self.package {
+            wallpaper
+                .main
+                .resource::<AssetServer>()
+                .mount(self.project_directory, package);
+        }
+
+        wallpaper
+            .render
+            .insert_resource(ClearColor(self.clear_color));
+
+        let is_animated = self
+            .layers
+            .iter()
+            .any(|layer| layer.animation.frames.len() > 1);
+
+        wallpaper
+            .main
+            .insert_resource(if is_animated {
+                FrameRateSetting::CAP_TO_60_FPS
+            } else {
+                FrameRateSetting::NoUpdate
+            })
+            .insert_resource(SceneLayout {
+                projection: self.projection,
+                layers: self.layers,
+            })
+            .add_systems(Startup, setup);
+    }
+}
+
+/// System that spawns scene layers.
+///
+/// Scene pixels are mapped so that the scene width spans `-1..1`, and the
+/// camera is zoomed in so that the scene covers the whole monitor.
+pub fn setup(
+    mut commands: Commands,
+    mut layout: ResMut<SceneLayout>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<ImageMaterial>>,
+    mut camera: ResMut<Camera>,
+    quad_mesh: Res<QuadMesh>,
+    monitor: Res<Monitor>,
+) {
+    let projection = layout.projection.as_vec2();
+    let pixels_to_world = 2.0 / projection.x;
+
+    let scene_aspect_ratio = projection.y / projection.x;
+    camera.zoom = f32::max(1.0, monitor.aspect_ratio() / scene_aspect_ratio);
+
+    for layer in layout.layers.drain(..) {
+        // NOTE: meshes are drawn in order of their material ids,
+        // so materials are created per layer to preserve scene draw order
+        let animation = SpriteAnimation::new(layer.animation, &mut images, &mut materials);
+
+        let Some(material) = animation.current_material().cloned() else {
+            continue;
+        };
+
+        let translation = (layer.origin.truncate() - 0.5 * projection) * pixels_to_world;
+        let scale = 0.5 * layer.size * pixels_to_world;
+
+        let mut entity = commands.spawn((
+            Mesh3d(quad_mesh.0.clone()),
+            MeshMaterial(material),
+            Transform {
+                translation: translation.extend(0.0),
+                scale: scale.extend(1.0),
+                rotation: Quat::from_euler(
+                    EulerRot::XYZ,
+                    layer.angles.x,
+                    layer.angles.y,
+                    layer.angles.z,
+                ),
+            },
+        ));
+
+        if animation.frames.len() > 1 {
+            entity.insert(animation);
+        }
+    }
+}
+
+/// Finds the project directory given a path to the directory itself or a file in it
+fn project_directory(path: &Path) -> Result<PathBuf, SceneLoadError> {
+    if path.is_dir() {
+        return Ok(path.to_owned());
+    }
+
+    match path.file_name().and_then(|name| name.to_str()) {
+        Some(PROJECT_FILE_NAME | PACKAGE_FILE_NAME) => path
+            .parent()
+            .map(Path::to_owned)
+            .ok_or_else(|| SceneLoadError::NotAProject(path.to_owned())),
+        _ => Err(SceneLoadError::NotAProject(path.to_owned())),
+    }
+}
+
+/// Scene placement of an object with all its parents applied
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Placement {
+    origin: Vec3,
+    scale: Vec3,
+    angles: Vec3,
+}
+
+impl Placement {
+    fn of(object: &SceneObject) -> Self {
+        Self {
+            origin: Vec3::from_array(object.origin().0),
+            scale: Vec3::from_array(object.scale().0),
+            angles: Vec3::from_array(object.angles().0),
+        }
+    }
+
+    fn rotation(self) -> Quat {
+        Quat::from_euler(EulerRot::XYZ, self.angles.x, self.angles.y, self.angles.z)
+    }
+
+    /// Places `child` given in the space of `self`
+    fn then(self, child: Self) -> Self {
+        Self {
+            origin: self.origin + self.rotation() * (self.scale * child.origin),
+            scale: self.scale * child.scale,
+            angles: self.angles + child.angles,
+        }
+    }
+}
+
+fn global_placement(object: &SceneObject, objects: &HashMap<u32, &SceneObject>) -> Placement {
+    let mut placement = Placement::of(object);
+    let mut parent_id = object.parent;
+
+    // NOTE: bounded by the object count to survive cyclic parents
+    for _ in 0..objects.len() {
+        let Some(parent) = parent_id.and_then(|id| objects.get(&id)) else {
+            break;
+        };
+
+        placement = Placement::of(parent).then(placement);
+        parent_id = parent.parent;
+    }
+
+    placement
+}
+
+fn load_layers(files: &SceneFiles, scene: &Scene) -> Result<Vec<SceneLayer>, SceneLoadError> {
+    let objects = scene
+        .objects
+        .iter()
+        .map(|object| (object.id, object))
+        .collect::<HashMap<_, _>>();
+
+    let mut textures = HashMap::<String, AnimatedImage>::new();
+    let mut layers = Vec::new();
+
+    for object in &scene.objects {
+        let Some(model_path) = &object.image else {
+            continue;
+        };
+
+        if !object.is_visible() {
+            continue;
+        }
+
+        let model = Model::from_json(str::from_utf8(&files.read(model_path)?)?)?;
+        let material = Material::from_json(str::from_utf8(&files.read(&model.material)?)?)?;
+
+        let Some(texture_name) = material.base_texture() else {
+            debug!(name = object.name, "skipping layer without a texture");
+            continue;
+        };
+
+        // Render targets are produced by effects we do not support yet
+        if texture_name.starts_with("_rt_") {
+            debug!(name = object.name, "skipping render target layer");
+            continue;
+        }
+
+        let animation = match textures.get(texture_name) {
+            Some(animation) => animation.clone(),
+            None => {
+                let path = Path::new("materials").join(format!("{texture_name}.tex"));
+                let animation = AnimatedImage::from_tex(&mut files.read(&path)?.as_ref())?;
+                textures.insert(texture_name.to_owned(), animation.clone());
+                animation
+            }
+        };
+
+        let placement = global_placement(object, &objects);
+
+        let size = match (object.size(), model.width, model.height) {
+            (Some(size), _, _) => Vec2::from_array(size.0),
+            (None, Some(width), Some(height)) => UVec2::new(width, height).as_vec2(),
+            // `AnimatedImage` always has at least one frame
+            (None, _, _) => animation.frames[0].image.size().as_vec2(),
+        };
+
+        layers.push(SceneLayer {
+            name: object.name.clone(),
+            animation,
+            origin: placement.origin,
+            size: size * placement.scale.truncate(),
+            angles: placement.angles,
+        });
+    }
+
+    Ok(layers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use project_parser::scene::{Property, Vector};
+    use std::f32::consts::FRAC_PI_2;
+
+    fn object(id: u32, parent: Option<u32>, origin: [f32; 3], angles: [f32; 3]) -> SceneObject {
+        SceneObject {
+            id,
+            name: String::new(),
+            image: None,
+            origin: Some(Property::Value(Vector(origin))),
+            scale: Some(Property::Value(Vector([2.0, 2.0, 1.0]))),
+            angles: Some(Property::Value(Vector(angles))),
+            size: None,
+            visible: None,
+            parent,
+        }
+    }
+
+    fn assert_near(actual: Vec3, expected: Vec3) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-4),
+            "{actual} is not {expected}"
+        );
+    }
+
+    #[test]
+    fn rotated_parent() {
+        let parent = object(1, None, [100.0, 50.0, 0.0], [0.0, 0.0, FRAC_PI_2]);
+        let child = object(2, Some(1), [10.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        let objects = HashMap::from([(1, &parent), (2, &child)]);
+
+        let placement = global_placement(&child, &objects);
+
+        assert_near(placement.origin, Vec3::new(100.0, 70.0, 0.0));
+        assert_near(placement.scale, Vec3::new(4.0, 4.0, 1.0));
+        assert_near(placement.angles, Vec3::new(0.0, 0.0, FRAC_PI_2));
+    }
+
+    #[test]
+    fn nested_parents() {
+        let root = object(1, None, [100.0, 0.0, 0.0], [0.0, 0.0, FRAC_PI_2]);
+        let middle = object(2, Some(1), [10.0, 0.0, 0.0], [0.0, 0.0, FRAC_PI_2]);
+        let leaf = object(3, Some(2), [1.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        let objects = HashMap::from([(1, &root), (2, &middle), (3, &leaf)]);
+
+        let placement = global_placement(&leaf, &objects);
+
+        // middle sits at root + rot90(2 * 10) = (100, 20), leaf adds rot180(4 * 1) = (-4, 0)
+        assert_near(placement.origin, Vec3::new(96.0, 20.0, 0.0));
+        assert_near(placement.scale, Vec3::new(8.0, 8.0, 1.0));
+        assert_near(placement.angles, Vec3::new(0.0, 0.0, 2.0 * FRAC_PI_2));
+    }
+
+    #[test]
+    fn cyclic_parents() {
+        let first = object(1, Some(2), [1.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        let second = object(2, Some(1), [1.0, 0.0, 0.0], [0.0, 0.0, 0.0]);
+        let objects = HashMap::from([(1, &first), (2, &second)]);
+
+        // terminates despite the cycle
+        let placement = global_placement(&first, &objects);
+
+        assert!(placement.origin.x.is_finite());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{playlist::Playlist, wallpaper::Placement};
 use bincode::{Decode, Encode};
 use std::path::PathBuf;
 
@@ -6,15 +7,67 @@ pub enum DaemonCommand {
     SetVideo {
         path: PathBuf,
         monitor: Option<String>,
+        placement: Placement,
     },
     SetImage {
         path: PathBuf,
         monitor: Option<String>,
+        placement: Placement,
     },
     SetScene {
+        path: PathBuf,
+        monitor: Option<String>,
+    },
+    /// Cycle through the wallpapers of the playlist
+    SetPlaylist {
+        playlist: Playlist,
+        monitor: Option<String>,
+    },
+    /// Show the next wallpaper of the playlist
+    Next {
+        monitor: Option<String>,
+    },
+    /// Show the previous wallpaper of the playlist
+    Prev {
         monitor: Option<String>,
     },
     Pause {
         monitor: Option<String>,
     },
+    Resume {
+        monitor: Option<String>,
+    },
+    /// Pause the wallpapers if any of them is running, resume them otherwise
+    Toggle {
+        monitor: Option<String>,
+    },
+    /// Query wallpapers shown on the monitors
+    Status {
+        monitor: Option<String>,
+    },
+    /// Query monitors known to the daemon
+    Monitors,
+    Profile(ProfileCommand),
+    /// Read the config again and apply it to the running wallpapers
+    Reload,
+    /// Keep the connection open and receive [`DaemonEvent`](crate::DaemonEvent)s
+    /// after the [`DaemonResponse::Ok`](crate::DaemonResponse::Ok)
+    Subscribe,
+}
+
+/// Management of the named profiles, see [`Profile`](crate::profile::Profile)
+#[derive(Encode, Decode, Debug, PartialEq, PartialOrd, Hash, Eq, Ord, Clone)]
+pub enum ProfileCommand {
+    /// Store the current setup of every monitor under the name
+    Save {
+        name: String,
+    },
+    /// Show wallpapers of the profile on the monitors listed in it
+    Load {
+        name: String,
+    },
+    List,
+    Delete {
+        name: String,
+    },
 }
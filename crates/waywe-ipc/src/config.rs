@@ -1,7 +1,20 @@
+use crate::{
+    power::PowerSource,
+    schedule::{Location, ScheduleEntry},
+    status::PowerPolicy,
+};
 use glam::Vec2;
-use rand::distr::{Distribution as _, Uniform};
+use rand::{
+    distr::{Distribution as _, Uniform},
+    seq::IndexedRandom as _,
+};
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use thiserror::Error;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -9,6 +22,183 @@ pub struct Config {
     pub animation: AnimationConfig,
     #[serde(default)]
     pub effects: Vec<Effects>,
+    #[serde(default)]
+    pub auto_pause: AutoPauseConfig,
+    #[serde(default)]
+    pub power: PowerConfig,
+    /// Location used to compute sunrise and sunset for the schedule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    /// Wallpapers shown during the time ranges of the day
+    #[serde(default)]
+    pub schedule: Vec<ScheduleEntry>,
+}
+
+impl Config {
+    /// Location of the config, `~/.config/waywe/config.toml`
+    pub fn path() -> Result<PathBuf, ConfigError> {
+        let mut path = env::home_dir().ok_or(ConfigError::NoHomeDirectory)?;
+        path.push(".config/waywe/config.toml");
+        Ok(path)
+    }
+
+    /// Reads the config from [`Config::path`], the default config is stored there if it is missing
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = Self::path()?;
+
+        match Self::read_from(&path) {
+            Err(ConfigError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {}
+            other => return other,
+        }
+
+        let config = Self::default();
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        fs::write(&path, toml::to_string(&config)?)?;
+
+        Ok(config)
+    }
+
+    pub fn read_from(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to find user's home directory")]
+    NoHomeDirectory,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Parse(#[from] toml::de::Error),
+    #[error(transparent)]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Pausing wallpapers nobody is looking at
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AutoPauseConfig {
+    /// Pause the wallpapers after the user is idle for this many seconds, `0` to never pause
+    #[serde(default = "get_default_idle_timeout")]
+    pub idle_timeout_seconds: u64,
+    /// Pause the wallpaper while the compositor does not show it,
+    /// e.g. under a fullscreen window
+    #[serde(default = "get_true")]
+    pub when_covered: bool,
+    /// The wallpaper is considered covered if it was not shown for this long
+    #[serde(default = "get_default_covered_timeout")]
+    pub covered_timeout_milliseconds: u64,
+}
+
+impl AutoPauseConfig {
+    pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+    pub const DEFAULT_COVERED_TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// Idle time after which the wallpapers are paused, [`None`] to never pause
+    pub const fn idle_timeout(&self) -> Option<Duration> {
+        match self.idle_timeout_seconds {
+            0 => None,
+            seconds => Some(Duration::from_secs(seconds)),
+        }
+    }
+
+    pub const fn covered_timeout(&self) -> Duration {
+        Duration::from_millis(self.covered_timeout_milliseconds)
+    }
+}
+
+impl Default for AutoPauseConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_seconds: get_default_idle_timeout(),
+            when_covered: true,
+            covered_timeout_milliseconds: get_default_covered_timeout(),
+        }
+    }
+}
+
+const fn get_default_idle_timeout() -> u64 {
+    AutoPauseConfig::DEFAULT_IDLE_TIMEOUT.as_secs()
+}
+
+const fn get_default_covered_timeout() -> u64 {
+    AutoPauseConfig::DEFAULT_COVERED_TIMEOUT.as_millis() as u64
+}
+
+const fn get_true() -> bool {
+    true
+}
+
+/// Rendering limits while the machine is on battery
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PowerConfig {
+    /// Frames per second limit on battery, `0` to render at the full rate
+    #[serde(default = "get_default_battery_max_fps")]
+    pub battery_max_fps: u32,
+    /// Pause videos and scenes on battery
+    #[serde(default)]
+    pub pause_on_battery: bool,
+    /// How often the power supply is checked, at least once a second
+    #[serde(default = "get_default_poll_interval")]
+    pub poll_interval_seconds: u64,
+}
+
+impl PowerConfig {
+    pub const DEFAULT_BATTERY_MAX_FPS: u32 = 30;
+    pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// Decides how to render given the power source
+    pub const fn policy(&self, source: PowerSource) -> PowerPolicy {
+        if !source.is_battery() {
+            return PowerPolicy {
+                source,
+                max_fps: None,
+                paused: false,
+            };
+        }
+
+        PowerPolicy {
+            source,
+            max_fps: match self.battery_max_fps {
+                0 => None,
+                fps => Some(fps),
+            },
+            paused: self.pause_on_battery,
+        }
+    }
+
+    /// Interval of [`PowerConfig::poll_interval_seconds`], zero is clamped to one second
+    pub const fn poll_interval(&self) -> Duration {
+        match self.poll_interval_seconds {
+            0 => Duration::from_secs(1),
+            seconds => Duration::from_secs(seconds),
+        }
+    }
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            battery_max_fps: Self::DEFAULT_BATTERY_MAX_FPS,
+            pause_on_battery: false,
+            poll_interval_seconds: get_default_poll_interval(),
+        }
+    }
+}
+
+const fn get_default_battery_max_fps() -> u32 {
+    PowerConfig::DEFAULT_BATTERY_MAX_FPS
+}
+
+const fn get_default_poll_interval() -> u64 {
+    PowerConfig::DEFAULT_POLL_INTERVAL.as_secs()
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -16,6 +206,47 @@ pub struct Config {
 pub enum Effects {
     Convolve(ConvolveConfig),
     Blur(BlurConfig),
+    Custom(CustomShaderConfig),
+}
+
+impl Effects {
+    /// Name of the effect as written in the config
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Convolve(..) => "convolve",
+            Self::Blur(..) => "blur",
+            Self::Custom(..) => "custom",
+        }
+    }
+}
+
+/// Shader written by the user, see the README for the interface it should follow
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CustomShaderConfig {
+    /// `.wgsl` or `.glsl` file, relative paths start at [`CustomShaderConfig::directory`]
+    pub path: PathBuf,
+    /// Values passed to the shader in `waywe.params`, at most [`CustomShaderConfig::MAX_UNIFORMS`]
+    #[serde(default)]
+    pub uniforms: Vec<f32>,
+}
+
+impl CustomShaderConfig {
+    pub const MAX_UNIFORMS: usize = 16;
+
+    /// Directory of the user shaders, `~/.config/waywe/shaders`
+    pub fn directory() -> Result<PathBuf, ConfigError> {
+        Ok(Config::path()?.with_file_name("shaders"))
+    }
+
+    /// Path to the shader with relative paths resolved
+    pub fn full_path(&self) -> Result<PathBuf, ConfigError> {
+        if self.path.is_absolute() {
+            Ok(self.path.clone())
+        } else {
+            Ok(Self::directory()?.join(&self.path))
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -64,6 +295,8 @@ pub struct AnimationConfig {
     pub easing: Interpolation,
     #[serde(default)]
     pub center_position: CenterPosition,
+    #[serde(default)]
+    pub transition: TransitionConfig,
 }
 
 impl Default for AnimationConfig {
@@ -73,6 +306,7 @@ impl Default for AnimationConfig {
             direction: AnimationDirection::default(),
             easing: Interpolation::default(),
             center_position: CenterPosition::default(),
+            transition: TransitionConfig::default(),
         }
     }
 }
@@ -150,9 +384,166 @@ impl Default for CenterPosition {
     }
 }
 
+/// The way the new wallpaper replaces the old one
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "kebab-case",
+    rename_all_fields = "kebab-case"
+)]
+pub enum TransitionConfig {
+    /// Circle growing from (or shrinking to) the `center-position`
+    #[default]
+    Circle,
+    /// The new wallpaper fades in over the old one
+    Crossfade,
+    /// A soft edge sweeps across the screen
+    Wipe {
+        #[serde(default)]
+        direction: TransitionDirection,
+        /// Width of the edge as a fraction of the screen
+        #[serde(default = "get_default_softness")]
+        softness: f32,
+    },
+    /// The new wallpaper slides in over the old one
+    Slide {
+        #[serde(default)]
+        direction: TransitionDirection,
+    },
+    /// The new wallpaper pushes the old one off the screen
+    Push {
+        #[serde(default)]
+        direction: TransitionDirection,
+    },
+    /// The old wallpaper turns into large pixels, then the new one gets sharp
+    Pixelate {
+        /// Size of the pixels in the middle of the transition
+        #[serde(default = "get_default_max_block_size")]
+        max_block_size: f32,
+    },
+    /// The new wallpaper appears in random grains
+    Dissolve {
+        /// Size of the grains in pixels
+        #[serde(default = "get_default_grain_size")]
+        grain_size: f32,
+        #[serde(default = "get_default_softness")]
+        softness: f32,
+    },
+    /// Both wallpapers are blurred away from the centre of the screen
+    ZoomBlur {
+        #[serde(default = "get_default_zoom_strength")]
+        strength: f32,
+    },
+    /// Circle growing from the last known cursor position
+    GrowFromCursor,
+    /// One of the other transitions with default parameters, picked for each wallpaper
+    Random,
+    /// Transition shader written by the user
+    Custom(CustomShaderConfig),
+}
+
+impl TransitionConfig {
+    pub const DEFAULT_SOFTNESS: f32 = 0.1;
+    pub const DEFAULT_MAX_BLOCK_SIZE: f32 = 64.0;
+    pub const DEFAULT_GRAIN_SIZE: f32 = 4.0;
+    pub const DEFAULT_ZOOM_STRENGTH: f32 = 0.3;
+
+    /// Transitions [`TransitionConfig::Random`] picks from
+    pub const RANDOM_CHOICES: [Self; 9] = [
+        Self::Circle,
+        Self::Crossfade,
+        Self::Wipe {
+            direction: TransitionDirection::Left,
+            softness: Self::DEFAULT_SOFTNESS,
+        },
+        Self::Slide {
+            direction: TransitionDirection::Left,
+        },
+        Self::Push {
+            direction: TransitionDirection::Left,
+        },
+        Self::Pixelate {
+            max_block_size: Self::DEFAULT_MAX_BLOCK_SIZE,
+        },
+        Self::Dissolve {
+            grain_size: Self::DEFAULT_GRAIN_SIZE,
+            softness: Self::DEFAULT_SOFTNESS,
+        },
+        Self::ZoomBlur {
+            strength: Self::DEFAULT_ZOOM_STRENGTH,
+        },
+        Self::GrowFromCursor,
+    ];
+
+    /// Picks the transition for [`TransitionConfig::Random`] with a random direction,
+    /// other transitions are returned as is
+    pub fn resolve(&self) -> Self {
+        let Self::Random = self else {
+            return self.clone();
+        };
+
+        let mut rng = rand::rng();
+        let direction = *TransitionDirection::ALL.choose(&mut rng).unwrap();
+
+        match Self::RANDOM_CHOICES.choose(&mut rng).unwrap().clone() {
+            Self::Wipe { softness, .. } => Self::Wipe {
+                direction,
+                softness,
+            },
+            Self::Slide { .. } => Self::Slide { direction },
+            Self::Push { .. } => Self::Push { direction },
+            other => other,
+        }
+    }
+}
+
+const fn get_default_softness() -> f32 {
+    TransitionConfig::DEFAULT_SOFTNESS
+}
+
+const fn get_default_max_block_size() -> f32 {
+    TransitionConfig::DEFAULT_MAX_BLOCK_SIZE
+}
+
+const fn get_default_grain_size() -> f32 {
+    TransitionConfig::DEFAULT_GRAIN_SIZE
+}
+
+const fn get_default_zoom_strength() -> f32 {
+    TransitionConfig::DEFAULT_ZOOM_STRENGTH
+}
+
+/// Direction the new wallpaper moves in
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransitionDirection {
+    #[default]
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl TransitionDirection {
+    pub const ALL: [Self; 4] = [Self::Left, Self::Right, Self::Up, Self::Down];
+
+    /// Unit vector of the direction with `y` pointing up
+    pub const fn to_vec2(self) -> Vec2 {
+        match self {
+            Self::Left => Vec2::NEG_X,
+            Self::Right => Vec2::X,
+            Self::Up => Vec2::Y,
+            Self::Down => Vec2::NEG_Y,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schedule::{ScheduleTime, TimeOfDay};
 
     #[test]
     #[ignore = "used for debugging only"]
@@ -165,8 +556,219 @@ mod tests {
                 ..AnimationConfig::default()
             },
             effects: vec![],
+            auto_pause: AutoPauseConfig::default(),
+            power: PowerConfig::default(),
+            location: None,
+            schedule: vec![],
         };
         let string = toml::to_string(&config).unwrap();
         println!("{string}");
     }
+
+    #[test]
+    fn auto_pause_defaults() {
+        let config = toml::from_str::<Config>("[animation]\n").unwrap();
+
+        assert_eq!(config.auto_pause, AutoPauseConfig::default());
+        assert_eq!(
+            config.auto_pause.idle_timeout(),
+            Some(AutoPauseConfig::DEFAULT_IDLE_TIMEOUT),
+        );
+
+        let config = toml::from_str::<Config>(
+            "[animation]\n[auto-pause]\nidle-timeout-seconds = 0\nwhen-covered = false\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.auto_pause.idle_timeout(), None);
+        assert!(!config.auto_pause.when_covered);
+    }
+
+    #[test]
+    fn schedule() {
+        let config = toml::from_str::<Config>(
+            r#"
+            [animation]
+
+            [location]
+            latitude = 55.75
+            longitude = 37.62
+
+            [[schedule]]
+            from = "sunrise"
+            to = "sunset"
+            path = "/wallpapers/day.png"
+            type = "image"
+
+            [[schedule]]
+            from = "22:00"
+            to = "06:30"
+            path = "/wallpapers/night.mp4"
+            type = "video"
+            monitor = "DP-1"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.location.is_some());
+        assert_eq!(config.schedule.len(), 2);
+        assert_eq!(config.schedule[0].from, ScheduleTime::Sunrise);
+        assert_eq!(
+            config.schedule[1].to,
+            ScheduleTime::At(TimeOfDay::from_hm(6, 30)),
+        );
+        assert_eq!(config.schedule[1].monitor.as_deref(), Some("DP-1"));
+
+        let contents = toml::to_string(&config).unwrap();
+        assert_eq!(
+            toml::from_str::<Config>(&contents).unwrap().schedule,
+            config.schedule,
+        );
+    }
+
+    #[test]
+    fn transitions() {
+        let config = toml::from_str::<Config>("[animation]\n").unwrap();
+        assert_eq!(config.animation.transition, TransitionConfig::Circle);
+
+        let config = toml::from_str::<Config>(
+            "[animation.transition]\ntype = \"wipe\"\ndirection = \"up\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.animation.transition,
+            TransitionConfig::Wipe {
+                direction: TransitionDirection::Up,
+                softness: TransitionConfig::DEFAULT_SOFTNESS,
+            },
+        );
+
+        let config = toml::from_str::<Config>(
+            "[animation.transition]\ntype = \"pixelate\"\nmax-block-size = 32.0\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.animation.transition,
+            TransitionConfig::Pixelate {
+                max_block_size: 32.0
+            },
+        );
+
+        let custom = TransitionConfig::Custom(CustomShaderConfig {
+            path: PathBuf::from("swirl.wgsl"),
+            uniforms: vec![1.0, 2.0],
+        });
+
+        for transition in TransitionConfig::RANDOM_CHOICES.into_iter().chain([custom]) {
+            let config = Config {
+                animation: AnimationConfig {
+                    transition: transition.clone(),
+                    ..AnimationConfig::default()
+                },
+                ..Config::default()
+            };
+
+            let string = toml::to_string(&config).unwrap();
+            let parsed = toml::from_str::<Config>(&string).unwrap();
+
+            assert_eq!(parsed.animation.transition, transition);
+        }
+
+        for _ in 0..32 {
+            assert_ne!(TransitionConfig::Random.resolve(), TransitionConfig::Random);
+        }
+
+        assert_eq!(
+            TransitionConfig::Crossfade.resolve(),
+            TransitionConfig::Crossfade
+        );
+    }
+
+    #[test]
+    fn custom_shaders() {
+        let config = toml::from_str::<Config>(
+            r#"
+            [animation.transition]
+            type = "custom"
+            path = "/shaders/swirl.glsl"
+
+            [[effects]]
+            type = "custom"
+            path = "vignette.wgsl"
+            uniforms = [0.5, 0.25]
+            "#,
+        )
+        .unwrap();
+
+        let TransitionConfig::Custom(transition) = &config.animation.transition else {
+            panic!("expected a custom transition");
+        };
+
+        assert!(transition.uniforms.is_empty());
+        assert_eq!(
+            transition.full_path().unwrap(),
+            PathBuf::from("/shaders/swirl.glsl"),
+        );
+
+        let [Effects::Custom(effect)] = config.effects.as_slice() else {
+            panic!("expected a custom effect");
+        };
+
+        assert_eq!(effect.uniforms, [0.5, 0.25]);
+
+        assert!(
+            effect
+                .full_path()
+                .unwrap()
+                .ends_with(".config/waywe/shaders/vignette.wgsl")
+        );
+    }
+
+    #[test]
+    fn read_invalid_config() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("config.toml");
+
+        fs::write(&path, "[animation]\nduration-milliseconds = \"long\"\n").unwrap();
+        let result = Config::read_from(&path);
+
+        assert!(matches!(result, Err(ConfigError::Parse(..))));
+    }
+
+    #[test]
+    fn power_policy() {
+        let config = PowerConfig::default();
+
+        assert_eq!(
+            config.policy(PowerSource::Ac),
+            PowerPolicy {
+                source: PowerSource::Ac,
+                max_fps: None,
+                paused: false,
+            },
+        );
+        assert_eq!(
+            config.policy(PowerSource::Battery).max_fps,
+            Some(PowerConfig::DEFAULT_BATTERY_MAX_FPS),
+        );
+
+        let config = PowerConfig {
+            battery_max_fps: 0,
+            pause_on_battery: true,
+            ..PowerConfig::default()
+        };
+
+        let policy = config.policy(PowerSource::Battery);
+        assert_eq!(policy.max_fps, None);
+        assert!(policy.paused);
+
+        let config = PowerConfig {
+            poll_interval_seconds: 0,
+            ..PowerConfig::default()
+        };
+
+        assert_eq!(config.poll_interval(), Duration::from_secs(1));
+    }
 }
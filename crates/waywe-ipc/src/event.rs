@@ -0,0 +1,42 @@
+//! Events streamed to the clients subscribed with
+//! [`DaemonCommand::Subscribe`](crate::DaemonCommand::Subscribe)
+
+use crate::WallpaperType;
+use bincode::{Decode, Encode};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Change of the daemon state
+#[derive(Clone, Debug, PartialEq, Encode, Decode, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DaemonEvent {
+    /// New wallpaper is loaded and shown on the monitor
+    WallpaperSet {
+        monitor: String,
+        path: PathBuf,
+        #[serde(rename = "type")]
+        wallpaper_type: WallpaperType,
+    },
+    /// Transition to the new wallpaper is over
+    TransitionFinished {
+        monitor: String,
+    },
+    Paused {
+        monitor: String,
+    },
+    Resumed {
+        monitor: String,
+    },
+    MonitorAdded {
+        monitor: String,
+    },
+    MonitorRemoved {
+        monitor: String,
+    },
+    /// The config was read again and applied
+    ConfigReloaded,
+    /// The daemon failed to do its job, e.g. to load a wallpaper
+    Error {
+        message: String,
+    },
+}
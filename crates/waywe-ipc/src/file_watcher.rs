@@ -0,0 +1,114 @@
+//! Notifications about file changes through inotify
+
+use rustix::{
+    fs::inotify::{self, CreateFlags, WatchFlags},
+    io::Errno,
+};
+use std::{
+    ffi::OsString,
+    mem::MaybeUninit,
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+        unix::ffi::OsStrExt as _,
+    },
+    path::Path,
+};
+use tracing::error;
+
+/// Watches a file for being written or replaced
+///
+/// The parent directory is watched instead of the file itself,
+/// so the file survives editors replacing it on save
+pub struct FileWatcher {
+    fd: OwnedFd,
+    file_name: OsString,
+}
+
+impl FileWatcher {
+    /// Starts watching the file. Its parent directory should exist
+    pub fn new(path: &Path) -> Result<Self, Errno> {
+        let (Some(directory), Some(file_name)) = (path.parent(), path.file_name()) else {
+            return Err(Errno::INVAL);
+        };
+
+        let fd = inotify::init(CreateFlags::CLOEXEC | CreateFlags::NONBLOCK)?;
+        inotify::add_watch(
+            &fd,
+            directory,
+            WatchFlags::CLOSE_WRITE | WatchFlags::MOVED_TO,
+        )?;
+
+        Ok(Self {
+            fd,
+            file_name: file_name.to_owned(),
+        })
+    }
+
+    /// Reads the pending notifications without blocking
+    /// and checks whether the file changed since the last call
+    pub fn has_changed(&self) -> bool {
+        let mut buffer = [MaybeUninit::uninit(); 4096];
+        let mut reader = inotify::Reader::new(&self.fd, &mut buffer);
+        let mut has_changed = false;
+
+        loop {
+            match reader.next() {
+                Ok(event) => {
+                    has_changed |= event
+                        .file_name()
+                        .is_some_and(|name| name.to_bytes() == self.file_name.as_bytes());
+                }
+                Err(Errno::AGAIN | Errno::INTR) => return has_changed,
+                Err(error) => {
+                    error!(?error, "failed to read file notifications");
+                    return has_changed;
+                }
+            }
+        }
+    }
+}
+
+impl AsFd for FileWatcher {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for FileWatcher {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn watch_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let directory = directory.path();
+
+        let path = directory.join("config.toml");
+        let watcher = FileWatcher::new(&path).unwrap();
+
+        let before_write = watcher.has_changed();
+
+        fs::write(directory.join("other.toml"), "").unwrap();
+        let after_other_write = watcher.has_changed();
+
+        fs::write(&path, "[animation]\n").unwrap();
+        let after_write = watcher.has_changed();
+
+        fs::write(directory.join("config.toml.tmp"), "").unwrap();
+        fs::rename(directory.join("config.toml.tmp"), &path).unwrap();
+        let after_replace = watcher.has_changed();
+
+        assert!(!before_write);
+        assert!(!after_other_write);
+        assert!(after_write);
+        assert!(after_replace);
+        assert!(!watcher.has_changed());
+    }
+}
@@ -2,10 +2,13 @@ use bincode::{
     Decode, Encode,
     error::{DecodeError, EncodeError},
 };
+use bytemuck::{Pod, Zeroable};
 use rustix::{
+    event::{EventfdFlags, eventfd},
     io::{self, Errno},
     net::{
-        self, AddressFamily, RecvFlags, SocketAddrUnix, SocketFlags, SocketType, sockopt::Timeout,
+        self, AddressFamily, RecvFlags, SendFlags, SocketAddrUnix, SocketFlags, SocketType,
+        sockopt::Timeout,
     },
 };
 use smallvec::{SmallVec, smallvec};
@@ -18,7 +21,11 @@ use std::{
         unix::prelude::{BorrowedFd, RawFd},
     },
     path::Path,
-    sync::OnceLock,
+    sync::{
+        Arc, Mutex, OnceLock,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
     time::Duration,
 };
 use thiserror::Error;
@@ -26,6 +33,17 @@ use tracing::{debug, error, warn};
 
 pub const BUFFER_SIZE: usize = 256;
 
+/// Maximum size of an encoded message in bytes
+pub const MAX_MESSAGE_SIZE: u32 = 4 * 1024 * 1024;
+
+/// Version of the messages exchanged between `waywe` and `waywe-daemon`.
+/// Has to be bumped on every change of [`DaemonCommand`](crate::DaemonCommand)
+/// [`DaemonResponse`](crate::DaemonResponse) or [`DaemonEvent`](crate::DaemonEvent)
+pub const PROTOCOL_VERSION: u32 = 11;
+
+/// Time for which the client waits for the response, see [`IpcSocket::request`]
+pub const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub enum Side {
     #[default]
@@ -50,8 +68,12 @@ impl SocketSide for Server {
 }
 
 pub struct IpcSocket<Side: SocketSide, T> {
+    /// Connection to the server for the client, readiness of
+    /// the received requests for the server, see [`IpcSocket::try_recv`]
     fd: OwnedFd,
-    _p: PhantomData<(Side, T)>,
+    /// Requests read by the listener thread, [`None`] for the client
+    requests: Option<Mutex<Receiver<(T, IpcResponder)>>>,
+    _p: PhantomData<Side>,
 }
 
 impl<S: SocketSide, T> AsFd for IpcSocket<S, T> {
@@ -96,53 +118,6 @@ impl<S: SocketSide, T> IpcSocket<S, T> {
         static PATH: OnceLock<String> = OnceLock::new();
         PATH.get_or_init(Self::socket_file)
     }
-
-    pub fn send(&self, value: T) -> Result<(), SendError>
-    where
-        T: Encode,
-    {
-        let mut buf = SmallVec::<[u8; BUFFER_SIZE]>::new_const();
-        buf.extend_from_slice(&[0; mem::size_of::<u32>()]);
-
-        let n_bytes = bincode::encode_into_std_write(value, &mut buf, bincode::config::standard())?;
-
-        let size = bytemuck::from_bytes_mut::<u32>(&mut buf[..mem::size_of::<u32>()]);
-        *size = n_bytes as u32;
-
-        io::write(&self.fd, &buf)?;
-
-        Ok(())
-    }
-
-    pub fn try_recv(&self) -> Result<T, RecvError>
-    where
-        T: Decode<()>,
-    {
-        let fd = match net::accept_with(&self.fd, SocketFlags::empty()) {
-            Ok(fd) => fd,
-            Err(Errno::INTR | Errno::WOULDBLOCK) => return Err(RecvError::Empty),
-            Err(other) => return Err(RecvError::Os(other)),
-        };
-
-        let mut length = 0_u32;
-
-        match net::recv(
-            &fd,
-            bytemuck::bytes_of_mut(&mut length),
-            RecvFlags::DONTWAIT,
-        ) {
-            Ok(n_bytes) => assert_eq!(n_bytes, mem::size_of_val(&length)),
-            Err(Errno::WOULDBLOCK) => return Err(RecvError::Empty),
-            Err(error) => return Err(RecvError::Os(error)),
-        }
-
-        let mut buf: SmallVec<[u8; BUFFER_SIZE]> = smallvec![0; length as usize];
-        net::recv(&fd, &mut buf, RecvFlags::WAITALL)?;
-
-        let (value, _n_bytes) = bincode::decode_from_slice(&buf, bincode::config::standard())?;
-
-        Ok(value)
-    }
 }
 
 impl<T> IpcSocket<Client, T> {
@@ -163,12 +138,52 @@ impl<T> IpcSocket<Client, T> {
 
         Ok(Self {
             fd: socket,
+            requests: None,
             _p: PhantomData,
         })
     }
+
+    pub fn send(&self, value: T) -> Result<(), SendError>
+    where
+        T: Encode,
+    {
+        write_message(&self.fd, value)
+    }
+
+    /// Receives a message sent back by the server
+    pub fn recv<R>(&self) -> Result<R, RecvError>
+    where
+        R: Decode<()>,
+    {
+        read_message(&self.fd)
+    }
+
+    /// Sends `value` to the server and waits for its response
+    pub fn request<R>(&self, value: T) -> Result<R, RequestError>
+    where
+        T: Encode,
+        R: Decode<()>,
+    {
+        self.send(value)?;
+
+        // NOTE: the server responds after the wallpaper is loaded which can take a while
+        net::sockopt::set_socket_timeout(&self.fd, Timeout::Recv, Some(RESPONSE_TIMEOUT))
+            .map_err(RecvError::from)?;
+
+        Ok(self.recv()?)
+    }
+
+    /// Receives a message waiting for it as long as needed, e.g. an event streamed by the server
+    pub fn recv_blocking<R>(&self) -> Result<R, RecvError>
+    where
+        R: Decode<()>,
+    {
+        net::sockopt::set_socket_timeout(&self.fd, Timeout::Recv, None)?;
+        self.recv()
+    }
 }
 
-impl<T> IpcSocket<Server, T> {
+impl<T: Decode<()> + Send + 'static> IpcSocket<Server, T> {
     pub fn server() -> Result<Self, Errno> {
         let path = Path::new(Self::path());
 
@@ -185,7 +200,7 @@ impl<T> IpcSocket<Server, T> {
         let socket = net::socket_with(
             AddressFamily::UNIX,
             SocketType::STREAM,
-            SocketFlags::CLOEXEC | SocketFlags::NONBLOCK,
+            SocketFlags::CLOEXEC,
             None,
         )?;
 
@@ -205,11 +220,110 @@ impl<T> IpcSocket<Server, T> {
 
         net::listen(&socket, 0)?;
 
+        Self::serve(socket)
+    }
+
+    /// Accepts clients of the `listener` on a separate thread
+    fn serve(listener: OwnedFd) -> Result<Self, Errno> {
+        // NOTE: the semaphore counts the requests, so every read matches one of them
+        let ready = eventfd(
+            0,
+            EventfdFlags::CLOEXEC | EventfdFlags::NONBLOCK | EventfdFlags::SEMAPHORE,
+        )?;
+
+        let notifier = io::fcntl_dupfd_cloexec(&ready, 0)?;
+        let (sender, receiver) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("waywe-ipc".to_owned())
+            .spawn(move || accept_requests(listener, sender, notifier))
+            .map_err(|error| Errno::from_io_error(&error).unwrap_or(Errno::AGAIN))?;
+
         Ok(Self {
-            fd: socket,
+            fd: ready,
+            requests: Some(Mutex::new(receiver)),
             _p: PhantomData,
         })
     }
+
+    /// Takes a single message received from a client without blocking.
+    /// The file descriptor of the socket becomes readable once a message is received
+    ///
+    /// The returned [`IpcResponder`] should be used to reply to the client
+    pub fn try_recv(&self) -> Result<(T, IpcResponder), RecvError> {
+        let mut counter = [0; mem::size_of::<u64>()];
+
+        match io::read(&self.fd, &mut counter) {
+            Ok(_) => {}
+            Err(Errno::INTR | Errno::WOULDBLOCK) => return Err(RecvError::Empty),
+            Err(other) => return Err(RecvError::Os(other)),
+        }
+
+        let requests = self.requests.as_ref().expect("server has requests");
+
+        // the listener thread has stopped if the request is not there
+        requests
+            .lock()
+            .unwrap()
+            .try_recv()
+            .map_err(|_| RecvError::Closed)
+    }
+}
+
+/// Accepts clients and reads their messages off the thread of the daemon,
+/// so a slow client can not stall rendering
+fn accept_requests<T: Decode<()> + Send + 'static>(
+    listener: OwnedFd,
+    requests: Sender<(T, IpcResponder)>,
+    ready: OwnedFd,
+) {
+    let ready = Arc::new(ready);
+
+    loop {
+        let fd = match net::accept_with(&listener, SocketFlags::CLOEXEC) {
+            Ok(fd) => fd,
+            Err(Errno::INTR | Errno::CONNABORTED) => continue,
+            Err(error) => {
+                error!(?error, "failed to accept a client, stopped listening");
+                return;
+            }
+        };
+
+        let requests = requests.clone();
+        let ready = Arc::clone(&ready);
+
+        // every client is read on its own, so one of them can not delay the others
+        thread::spawn(move || {
+            let value = match read_request(&fd) {
+                Ok(value) => value,
+                Err(error) => {
+                    warn!(?error, "can not recv from waywe-cli");
+                    return;
+                }
+            };
+
+            if requests.send((value, IpcResponder { fd })).is_ok() {
+                _ = io::write(&*ready, &1_u64.to_ne_bytes());
+            }
+        });
+    }
+}
+
+/// Receives the message of a just connected client
+fn read_request<T: Decode<()>>(fd: &OwnedFd) -> Result<T, RecvError> {
+    // clients send their message right after connecting
+    const TIMEOUT: Duration = Duration::from_secs(1);
+    net::sockopt::set_socket_timeout(fd, Timeout::Recv, Some(TIMEOUT))?;
+
+    match read_message(fd) {
+        Ok(value) => Ok(value),
+        Err(RecvError::VersionMismatch { version }) => {
+            // let the client know which version the server uses
+            _ = io::write(fd, bytemuck::bytes_of(&MessageHeader::new(0)));
+            Err(RecvError::VersionMismatch { version })
+        }
+        Err(error) => Err(error),
+    }
 }
 
 impl<S: SocketSide, T> Drop for IpcSocket<S, T> {
@@ -221,6 +335,123 @@ impl<S: SocketSide, T> Drop for IpcSocket<S, T> {
     }
 }
 
+/// Connection to the client which sent a message, see [`IpcSocket::try_recv`]
+#[derive(Debug)]
+pub struct IpcResponder {
+    fd: OwnedFd,
+}
+
+impl IpcResponder {
+    /// Sends the response to the client closing the connection
+    pub fn respond(self, value: impl Encode) -> Result<(), SendError> {
+        write_message(&self.fd, value)
+    }
+
+    /// Keeps the connection open to send any number of messages to the client
+    pub fn into_stream(self) -> Result<IpcStream, Errno> {
+        // NOTE: the daemon should never wait for a slow client
+        io::ioctl_fionbio(&self.fd, true)?;
+
+        Ok(IpcStream { fd: self.fd })
+    }
+}
+
+/// Connection to the client which receives messages until it disconnects,
+/// see [`IpcResponder::into_stream`]
+#[derive(Debug)]
+pub struct IpcStream {
+    fd: OwnedFd,
+}
+
+impl IpcStream {
+    /// Sends a message to the client. Fails if the client has disconnected
+    /// or does not keep up with the messages
+    pub fn send(&self, value: impl Encode) -> Result<(), SendError> {
+        write_message(&self.fd, value)
+    }
+}
+
+/// Header sent before every encoded message
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
+struct MessageHeader {
+    /// Size of the encoded message in bytes
+    size: u32,
+    /// [`PROTOCOL_VERSION`] of the sender
+    version: u32,
+}
+
+impl MessageHeader {
+    const fn new(size: u32) -> Self {
+        Self {
+            size,
+            version: PROTOCOL_VERSION,
+        }
+    }
+}
+
+fn write_message(fd: impl AsFd, value: impl Encode) -> Result<(), SendError> {
+    let header_size = mem::size_of::<MessageHeader>();
+
+    let mut buf = SmallVec::<[u8; BUFFER_SIZE]>::new_const();
+    buf.extend_from_slice(&[0; mem::size_of::<MessageHeader>()]);
+
+    let n_bytes = bincode::encode_into_std_write(value, &mut buf, bincode::config::standard())?;
+
+    let n_bytes = u32::try_from(n_bytes)
+        .ok()
+        .filter(|&size| size <= MAX_MESSAGE_SIZE)
+        .ok_or(SendError::TooLarge { size: n_bytes })?;
+
+    let header = bytemuck::from_bytes_mut::<MessageHeader>(&mut buf[..header_size]);
+    *header = MessageHeader::new(n_bytes);
+
+    // NOTE: disconnected clients should not kill the sender with SIGPIPE
+    if net::send(fd, &buf, SendFlags::NOSIGNAL)? != buf.len() {
+        return Err(SendError::Truncated);
+    }
+
+    Ok(())
+}
+
+fn read_message<T: Decode<()>>(fd: impl AsFd) -> Result<T, RecvError> {
+    let mut header = MessageHeader::default();
+
+    let n_bytes = net::recv(&fd, bytemuck::bytes_of_mut(&mut header), RecvFlags::WAITALL)?;
+
+    if n_bytes == 0 {
+        return Err(RecvError::Closed);
+    }
+
+    if n_bytes != mem::size_of_val(&header) {
+        return Err(RecvError::Truncated);
+    }
+
+    // the size comes from the peer, so it can not be trusted with an allocation
+    if header.size > MAX_MESSAGE_SIZE {
+        return Err(RecvError::TooLarge {
+            size: header.size as usize,
+        });
+    }
+
+    let mut buf: SmallVec<[u8; BUFFER_SIZE]> = smallvec![0; header.size as usize];
+
+    // NOTE: waiting for zero bytes blocks until the next message arrives
+    if !buf.is_empty() && net::recv(&fd, &mut buf, RecvFlags::WAITALL)? != buf.len() {
+        return Err(RecvError::Truncated);
+    }
+
+    if header.version != PROTOCOL_VERSION {
+        return Err(RecvError::VersionMismatch {
+            version: header.version,
+        });
+    }
+
+    let (value, _n_bytes) = bincode::decode_from_slice(&buf, bincode::config::standard())?;
+
+    Ok(value)
+}
+
 #[derive(Clone, Copy, Default, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
 pub enum RecvMode {
     #[default]
@@ -230,6 +461,10 @@ pub enum RecvMode {
 
 #[derive(Debug, Error)]
 pub enum SendError {
+    #[error("message is sent partially")]
+    Truncated,
+    #[error("message of {size} bytes exceeds {MAX_MESSAGE_SIZE} bytes")]
+    TooLarge { size: usize },
     #[error(transparent)]
     Os(#[from] Errno),
     #[error(transparent)]
@@ -240,8 +475,157 @@ pub enum SendError {
 pub enum RecvError {
     #[error("socket is empty")]
     Empty,
+    #[error("connection closed without a message")]
+    Closed,
+    #[error("message is truncated")]
+    Truncated,
+    #[error("message of {size} bytes exceeds {MAX_MESSAGE_SIZE} bytes")]
+    TooLarge { size: usize },
+    #[error("unsupported protocol version {version}, expected {PROTOCOL_VERSION}")]
+    VersionMismatch { version: u32 },
     #[error(transparent)]
     Os(#[from] Errno),
     #[error(transparent)]
     Decode(#[from] DecodeError),
 }
+
+#[derive(Debug, Error)]
+pub enum RequestError {
+    #[error(transparent)]
+    Send(#[from] SendError),
+    #[error(transparent)]
+    Recv(#[from] RecvError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DaemonCommand,
+        wallpaper::{Color, FitMode, Placement},
+    };
+    use std::path::PathBuf;
+
+    fn socket_pair() -> (OwnedFd, OwnedFd) {
+        net::socketpair(
+            AddressFamily::UNIX,
+            SocketType::STREAM,
+            SocketFlags::CLOEXEC,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let (client, server) = socket_pair();
+
+        let command = DaemonCommand::SetImage {
+            path: PathBuf::from("/tmp/image.png"),
+            monitor: Some("DP-1".to_owned()),
+            placement: Placement {
+                fit: FitMode::Tile,
+                background: Color(0x1e1e_2eff),
+                span: false,
+            },
+        };
+
+        write_message(&client, command.clone()).unwrap();
+        assert_eq!(read_message::<DaemonCommand>(&server).unwrap(), command);
+
+        drop(client);
+        assert!(matches!(
+            read_message::<DaemonCommand>(&server),
+            Err(RecvError::Closed),
+        ));
+    }
+
+    #[test]
+    fn stream_messages() {
+        let (client, server) = socket_pair();
+
+        let stream = IpcResponder { fd: server }.into_stream().unwrap();
+        stream.send(1_u32).unwrap();
+        stream.send(2_u32).unwrap();
+
+        assert_eq!(read_message::<u32>(&client).unwrap(), 1);
+        assert_eq!(read_message::<u32>(&client).unwrap(), 2);
+
+        drop(client);
+        assert!(stream.send(3_u32).is_err());
+    }
+
+    #[test]
+    fn slow_client_does_not_block_requests() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("waywe.sock");
+        let addr = SocketAddrUnix::new(&path).unwrap();
+
+        let listener = net::socket(AddressFamily::UNIX, SocketType::STREAM, None).unwrap();
+        net::bind_unix(&listener, &addr).unwrap();
+        net::listen(&listener, 0).unwrap();
+
+        let server = IpcSocket::<Server, u32>::serve(listener).unwrap();
+        assert!(matches!(server.try_recv(), Err(RecvError::Empty)));
+
+        let connect = || {
+            let client = net::socket(AddressFamily::UNIX, SocketType::STREAM, None).unwrap();
+            net::connect_unix(&client, &addr).unwrap();
+            client
+        };
+
+        // connects and never sends anything
+        let _slow = connect();
+
+        let fast = connect();
+        write_message(&fast, 42_u32).unwrap();
+
+        let mut fds = [rustix::event::PollFd::new(
+            &server,
+            rustix::event::PollFlags::IN,
+        )];
+        assert_eq!(rustix::event::poll(&mut fds, 500).unwrap(), 1);
+
+        let (value, responder) = server.try_recv().unwrap();
+        assert_eq!(value, 42);
+
+        responder.respond(7_u32).unwrap();
+        assert_eq!(read_message::<u32>(&fast).unwrap(), 7);
+
+        assert!(matches!(server.try_recv(), Err(RecvError::Empty)));
+    }
+
+    #[test]
+    fn message_too_large() {
+        let (client, server) = socket_pair();
+
+        assert!(matches!(
+            write_message(&client, vec![0_u8; MAX_MESSAGE_SIZE as usize]),
+            Err(SendError::TooLarge { .. }),
+        ));
+
+        let header = MessageHeader::new(MAX_MESSAGE_SIZE + 1);
+        io::write(&client, bytemuck::bytes_of(&header)).unwrap();
+
+        assert!(matches!(
+            read_message::<Vec<u8>>(&server),
+            Err(RecvError::TooLarge { .. }),
+        ));
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let (client, server) = socket_pair();
+
+        let header = MessageHeader {
+            size: 0,
+            version: PROTOCOL_VERSION + 1,
+        };
+        io::write(&client, bytemuck::bytes_of(&header)).unwrap();
+
+        assert!(matches!(
+            read_message::<DaemonCommand>(&server),
+            Err(RecvError::VersionMismatch { version }) if version == PROTOCOL_VERSION + 1,
+        ));
+    }
+}
@@ -1,12 +1,21 @@
 pub mod command;
 pub mod config;
 pub mod epoll;
+pub mod event;
+pub mod file_watcher;
 pub mod ipc;
+pub mod playlist;
+pub mod power;
 pub mod profile;
+pub mod response;
+pub mod schedule;
 pub mod signals;
+pub mod status;
 pub mod wallpaper;
 
 pub use command::DaemonCommand;
 pub use epoll::Epoll;
-pub use ipc::{IpcSocket, RecvError, SendError};
+pub use event::DaemonEvent;
+pub use ipc::{IpcResponder, IpcSocket, IpcStream, RecvError, RequestError, SendError};
+pub use response::DaemonResponse;
 pub use wallpaper::WallpaperType;
@@ -0,0 +1,243 @@
+//! Wallpapers shown one after another on a monitor
+
+use crate::WallpaperType;
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::{num::ParseIntError, path::PathBuf, time::Duration};
+use thiserror::Error;
+
+/// Single wallpaper of a [`Playlist`]
+#[derive(
+    Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Encode, Decode, Serialize, Deserialize,
+)]
+pub struct PlaylistEntry {
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub wallpaper_type: WallpaperType,
+}
+
+/// Wallpapers switched every `interval_seconds`
+///
+/// Shuffled playlists are ordered by the `seed`, so the order survives daemon restarts
+#[derive(
+    Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Encode, Decode, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub struct Playlist {
+    pub entries: Vec<PlaylistEntry>,
+    pub interval_seconds: u64,
+    #[serde(default)]
+    pub shuffle: bool,
+    #[serde(default)]
+    pub seed: u32,
+    /// Index into the playing order of the entry being shown
+    #[serde(default)]
+    pub position: usize,
+}
+
+impl Playlist {
+    /// Maximum number of entries, keeps [`DaemonCommand::SetPlaylist`](crate::DaemonCommand)
+    /// well below [`MAX_MESSAGE_SIZE`](crate::ipc::MAX_MESSAGE_SIZE)
+    pub const MAX_ENTRIES: usize = 4096;
+
+    pub fn new(entries: Vec<PlaylistEntry>, interval: Duration, shuffle: bool) -> Self {
+        Self {
+            entries,
+            interval_seconds: interval.as_secs().max(1),
+            shuffle,
+            seed: if shuffle { rand::random() } else { 0 },
+            position: 0,
+        }
+    }
+
+    pub const fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_seconds)
+    }
+
+    /// Indices of the entries in the playing order
+    pub fn order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.entries.len()).collect();
+
+        if self.shuffle {
+            let mut state = u64::from(self.seed);
+
+            // NOTE: Fisher-Yates shuffle over splitmix64, the order
+            // has to be the same for the same seed on every platform
+            for i in (1..order.len()).rev() {
+                let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+                order.swap(i, j);
+            }
+        }
+
+        order
+    }
+
+    /// Entry being shown, [`None`] if the playlist is empty
+    pub fn current(&self) -> Option<&PlaylistEntry> {
+        let order = self.order();
+        let &index = order.get(self.position % order.len().max(1))?;
+        self.entries.get(index)
+    }
+
+    /// Moves `step` entries forward, or backward for negative steps, wrapping around the ends
+    pub fn advance(&mut self, step: isize) {
+        let len = self.entries.len() as isize;
+
+        if len == 0 {
+            return;
+        }
+
+        self.position = (self.position as isize + step).rem_euclid(len) as usize;
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Parses an interval like `90`, `30s`, `10m`, `1h` or `1h30m`, plain numbers are seconds
+pub fn parse_interval(source: &str) -> Result<Duration, ParseIntervalError> {
+    let mut seconds = 0_u64;
+    let mut rest = source.trim();
+
+    if rest.is_empty() {
+        return Err(ParseIntervalError::Empty);
+    }
+
+    while !rest.is_empty() {
+        let digits_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(digits_end);
+        let number = number.parse::<u64>()?;
+
+        let unit_end = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_end);
+
+        let multiplier = match unit {
+            "" | "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => 24 * 60 * 60,
+            _ => return Err(ParseIntervalError::UnknownUnit(unit.to_owned())),
+        };
+
+        seconds = number
+            .checked_mul(multiplier)
+            .and_then(|value| seconds.checked_add(value))
+            .ok_or(ParseIntervalError::TooLong)?;
+        rest = tail;
+    }
+
+    if seconds == 0 {
+        return Err(ParseIntervalError::Zero);
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ParseIntervalError {
+    #[error("interval is empty")]
+    Empty,
+    #[error("interval should be longer than zero")]
+    Zero,
+    #[error("interval is too long")]
+    TooLong,
+    #[error("unknown time unit '{0}', expected one of 's', 'm', 'h' or 'd'")]
+    UnknownUnit(String),
+    #[error(transparent)]
+    Number(#[from] ParseIntError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playlist(len: usize, shuffle: bool) -> Playlist {
+        let entries = (0..len)
+            .map(|i| PlaylistEntry {
+                path: PathBuf::from(format!("/wallpapers/{i}.png")),
+                wallpaper_type: WallpaperType::Image,
+            })
+            .collect();
+
+        Playlist::new(entries, Duration::from_secs(600), shuffle)
+    }
+
+    #[test]
+    fn advance_wraps_around() {
+        let mut playlist = playlist(3, false);
+
+        assert_eq!(playlist.order(), [0, 1, 2]);
+        assert_eq!(
+            playlist.current().unwrap().path,
+            PathBuf::from("/wallpapers/0.png")
+        );
+
+        playlist.advance(-1);
+        assert_eq!(playlist.position, 2);
+
+        playlist.advance(2);
+        assert_eq!(playlist.position, 1);
+        assert_eq!(
+            playlist.current().unwrap().path,
+            PathBuf::from("/wallpapers/1.png")
+        );
+    }
+
+    #[test]
+    fn shuffle_is_deterministic() {
+        let mut playlist = playlist(16, true);
+        playlist.seed = 42;
+
+        let mut order = playlist.order();
+        assert_eq!(order, playlist.order());
+
+        order.sort_unstable();
+        assert_eq!(order, (0..16).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn empty_playlist() {
+        let mut playlist = playlist(0, true);
+        playlist.advance(1);
+
+        assert_eq!(playlist.position, 0);
+        assert_eq!(playlist.current(), None);
+    }
+
+    #[test]
+    fn playlist_toml_round_trip() {
+        let mut playlist = playlist(2, true);
+        playlist.advance(1);
+
+        let contents = toml::to_string(&playlist).unwrap();
+        assert_eq!(toml::from_str::<Playlist>(&contents).unwrap(), playlist);
+    }
+
+    #[test]
+    fn intervals() {
+        assert_eq!(parse_interval("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_interval("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_interval("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(parse_interval("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_interval(""), Err(ParseIntervalError::Empty));
+        assert_eq!(parse_interval("0m"), Err(ParseIntervalError::Zero));
+        assert_eq!(
+            parse_interval("5w"),
+            Err(ParseIntervalError::UnknownUnit("w".to_owned()))
+        );
+        assert!(matches!(
+            parse_interval("m"),
+            Err(ParseIntervalError::Number(..))
+        ));
+    }
+}
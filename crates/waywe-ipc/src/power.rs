@@ -0,0 +1,125 @@
+//! Power supply state read from `/sys/class/power_supply`
+
+use bincode::{Decode, Encode};
+use serde::Serialize;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Default location of the power supplies in the sysfs
+pub const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Where the machine takes power from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Encode, Decode, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerSource {
+    #[default]
+    Ac,
+    Battery,
+}
+
+impl PowerSource {
+    pub const fn is_battery(self) -> bool {
+        matches!(self, Self::Battery)
+    }
+
+    /// Reads the power source from [`POWER_SUPPLY_DIR`]
+    pub fn read() -> Result<Self, io::Error> {
+        Self::read_from(Path::new(POWER_SUPPLY_DIR))
+    }
+
+    /// Reads the power source from a directory laid out as `/sys/class/power_supply`
+    ///
+    /// The machine is on battery if none of the adapters is online and one of
+    /// its batteries is discharging. Batteries of the peripherals are ignored
+    pub fn read_from(root: &Path) -> Result<Self, io::Error> {
+        let mut is_discharging = false;
+
+        for entry in fs::read_dir(root)? {
+            let supply = PowerSupply(entry?.path());
+
+            match supply.attribute("type").as_deref() {
+                Some("Mains" | "USB") if supply.attribute("online").as_deref() == Some("1") => {
+                    return Ok(Self::Ac);
+                }
+                Some("Battery") if supply.attribute("scope").as_deref() != Some("Device") => {
+                    is_discharging |= supply.attribute("status").as_deref() == Some("Discharging");
+                }
+                _ => {}
+            }
+        }
+
+        Ok(if is_discharging {
+            Self::Battery
+        } else {
+            Self::Ac
+        })
+    }
+}
+
+/// Directory of a single power supply, e.g. `/sys/class/power_supply/BAT0`
+struct PowerSupply(PathBuf);
+
+impl PowerSupply {
+    /// Contents of the attribute file without the trailing newline,
+    /// [`None`] if the supply has no such attribute
+    fn attribute(&self, name: &str) -> Option<String> {
+        let contents = fs::read_to_string(self.0.join(name)).ok()?;
+        Some(contents.trim_end().to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_supply(root: &Path, name: &str, attributes: &[(&str, &str)]) {
+        let directory = root.join(name);
+        fs::create_dir_all(&directory).unwrap();
+
+        for (attribute, value) in attributes {
+            fs::write(directory.join(attribute), format!("{value}\n")).unwrap();
+        }
+    }
+
+    #[test]
+    fn read_power_source() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = directory.path();
+
+        write_supply(
+            root,
+            "BAT0",
+            &[("type", "Battery"), ("status", "Discharging")],
+        );
+        write_supply(
+            root,
+            "hidpp_battery_0",
+            &[
+                ("type", "Battery"),
+                ("scope", "Device"),
+                ("status", "Charging"),
+            ],
+        );
+        write_supply(root, "AC", &[("type", "Mains"), ("online", "0")]);
+
+        let on_battery = PowerSource::read_from(root).unwrap();
+
+        write_supply(root, "AC", &[("type", "Mains"), ("online", "1")]);
+        write_supply(root, "BAT0", &[("type", "Battery"), ("status", "Charging")]);
+
+        let on_ac = PowerSource::read_from(root).unwrap();
+
+        assert_eq!(on_battery, PowerSource::Battery);
+        assert_eq!(on_ac, PowerSource::Ac);
+    }
+
+    #[test]
+    fn no_power_supplies() {
+        let root = tempfile::tempdir().unwrap();
+        let source = PowerSource::read_from(root.path()).unwrap();
+
+        assert_eq!(source, PowerSource::Ac);
+    }
+}
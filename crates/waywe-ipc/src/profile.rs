@@ -1,17 +1,22 @@
-use crate::WallpaperType;
-use bincode::{
-    Decode, Encode, config,
-    error::{DecodeError, EncodeError},
-};
+//! Wallpaper setups of the monitors
+//!
+//! Named profiles are stored as toml files in `~/.config/waywe/profiles/`.
+//! The setup of the last session, restored when the daemon starts, is stored
+//! in the same format in `~/.cache/waywe/session.toml`
+
+use crate::{WallpaperType, config::Effects, playlist::Playlist, wallpaper::Placement};
+use bincode::{Decode, Encode, config, error::DecodeError};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     env,
     fs::{self, File},
     io,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use thiserror::Error;
+use tracing::{info, warn};
 
 #[derive(Clone, PartialEq, PartialOrd, Debug, Hash, Eq, Ord, Encode, Decode)]
 pub struct Monitor {
@@ -19,61 +24,194 @@ pub struct Monitor {
     pub path: PathBuf,
 }
 
+/// Setup stored in `~/.cache/waywe/profile.bin` by the older versions of waywe,
+/// see [`Profile::read_session`] for its migration
 #[derive(Clone, Default, PartialEq, Debug, Eq, Encode, Decode)]
 pub struct SetupProfile {
     pub monitors: HashMap<Arc<str>, Monitor>,
 }
 
 impl SetupProfile {
-    pub fn read() -> Result<Self, SetupProfileError> {
-        let profile_path = {
-            let mut path = cache_dir().ok_or(SetupProfileError::NoHomeDirectory)?;
-            path.push("profile.bin");
-            path
-        };
+    pub fn path() -> Result<PathBuf, SetupProfileError> {
+        let mut path = cache_dir().ok_or(SetupProfileError::NoHomeDirectory)?;
+        path.push("profile.bin");
+        Ok(path)
+    }
 
-        let mut file = File::open(&profile_path)?;
+    pub fn read() -> Result<Self, SetupProfileError> {
+        let mut file = File::open(Self::path()?)?;
 
         Ok(bincode::decode_from_std_read(
             &mut file,
             config::standard(),
         )?)
     }
+}
+
+/// Wallpaper setup of a single monitor
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MonitorProfile {
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub wallpaper_type: WallpaperType,
+    #[serde(default)]
+    pub paused: bool,
+    /// Fit mode and background of images and videos
+    #[serde(default, flatten)]
+    pub placement: Placement,
+    /// Effects applied to the wallpaper, [`None`] to use the ones from the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects: Option<Vec<Effects>>,
+    /// Playlist the wallpaper is shown from, see [`Playlist::current`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playlist: Option<Playlist>,
+}
+
+impl From<Monitor> for MonitorProfile {
+    fn from(value: Monitor) -> Self {
+        Self {
+            path: value.path,
+            wallpaper_type: value.wallpaper_type,
+            paused: false,
+            placement: Placement::default(),
+            effects: None,
+            playlist: None,
+        }
+    }
+}
+
+/// Wallpaper setup of every monitor keyed by the monitor name
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub monitors: BTreeMap<String, MonitorProfile>,
+}
+
+impl From<SetupProfile> for Profile {
+    fn from(value: SetupProfile) -> Self {
+        Self {
+            monitors: value
+                .monitors
+                .into_iter()
+                .map(|(name, monitor)| (name.as_ref().to_owned(), monitor.into()))
+                .collect(),
+        }
+    }
+}
 
-    pub fn with(mut self, name: Arc<str>, monitor: Monitor) -> Self {
+impl Profile {
+    pub fn with(mut self, name: String, monitor: MonitorProfile) -> Self {
         self.monitors.insert(name, monitor);
         self
     }
 
-    pub fn store(&self) -> Result<(), SetupProfileError> {
-        let profile = match Self::read() {
-            Ok(mut profile) => {
-                for (key, value) in &self.monitors {
-                    profile.monitors.insert(Arc::clone(key), value.clone());
-                }
+    /// Reads the profile with the given name
+    pub fn read(name: &str) -> Result<Self, SetupProfileError> {
+        match Self::read_from(&profile_path(name)?) {
+            Err(SetupProfileError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+                Err(SetupProfileError::NotFound(name.to_owned()))
+            }
+            other => other,
+        }
+    }
 
-                profile
+    /// Stores the profile under the given name replacing the existing one
+    pub fn store(&self, name: &str) -> Result<(), SetupProfileError> {
+        self.store_to(&profile_path(name)?)
+    }
+
+    /// Deletes the profile with the given name
+    pub fn delete(name: &str) -> Result<(), SetupProfileError> {
+        match fs::remove_file(profile_path(name)?) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                Err(SetupProfileError::NotFound(name.to_owned()))
             }
-            Err(_) => self.clone(),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Names of the stored profiles in alphabetical order
+    pub fn list() -> Result<Vec<String>, SetupProfileError> {
+        let entries = match fs::read_dir(profiles_dir()?) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => return Err(error.into()),
         };
 
-        let cache_directory = cache_dir().ok_or(SetupProfileError::NoHomeDirectory)?;
+        let mut names = vec![];
+
+        for entry in entries {
+            let path = entry?.path();
+
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "toml")
+                && let Some(name) = path.file_stem().and_then(|stem| stem.to_str())
+            {
+                names.push(name.to_owned());
+            }
+        }
+
+        names.sort_unstable();
+
+        Ok(names)
+    }
+
+    /// Reads the setup of the last session
+    ///
+    /// The setup stored by the older versions of waywe is converted on the first read
+    pub fn read_session() -> Result<Self, SetupProfileError> {
+        let session_path = session_path()?;
+
+        match Self::read_from(&session_path) {
+            Err(SetupProfileError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {}
+            other => return other,
+        }
+
+        let profile = Self::from(SetupProfile::read()?);
+        profile.store_to(&session_path)?;
 
-        fs::create_dir_all(&cache_directory)?;
+        info!(path = ?session_path, "migrated the setup profile");
 
-        let profile_path = {
-            let mut path = cache_directory;
-            path.push("profile.bin");
-            path
+        if let Err(error) = fs::remove_file(SetupProfile::path()?) {
+            warn!(?error, "failed to remove the old setup profile");
+        }
+
+        Ok(profile)
+    }
+
+    /// Stores the monitors into the setup of the current session.
+    /// Monitors missing from `self` keep their previous setup, so a session
+    /// which fails to parse is left untouched and the error is returned
+    pub fn store_session(&self) -> Result<(), SetupProfileError> {
+        let mut session = match Self::read_session() {
+            Ok(session) => session,
+            // neither the session nor the old setup profile exist yet
+            Err(SetupProfileError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+                Self::default()
+            }
+            Err(error) => return Err(error),
         };
 
-        let mut file = File::options()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&profile_path)?;
+        for (name, monitor) in &self.monitors {
+            session.monitors.insert(name.clone(), monitor.clone());
+        }
+
+        session.store_to(&session_path()?)
+    }
+
+    fn read_from(path: &Path) -> Result<Self, SetupProfileError> {
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    fn store_to(&self, path: &Path) -> Result<(), SetupProfileError> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
 
-        bincode::encode_into_std_write(&profile, &mut file, config::standard())?;
+        fs::write(path, toml::to_string(self)?)?;
 
         Ok(())
     }
@@ -86,14 +224,136 @@ pub fn cache_dir() -> Option<PathBuf> {
     })
 }
 
+/// Directory with the named profiles, `~/.config/waywe/profiles`
+pub fn profiles_dir() -> Result<PathBuf, SetupProfileError> {
+    let mut path = env::home_dir().ok_or(SetupProfileError::NoHomeDirectory)?;
+    path.push(".config/waywe/profiles");
+    Ok(path)
+}
+
+fn session_path() -> Result<PathBuf, SetupProfileError> {
+    let mut path = cache_dir().ok_or(SetupProfileError::NoHomeDirectory)?;
+    path.push("session.toml");
+    Ok(path)
+}
+
+fn profile_path(name: &str) -> Result<PathBuf, SetupProfileError> {
+    if !is_valid_profile_name(name) {
+        return Err(SetupProfileError::InvalidName(name.to_owned()));
+    }
+
+    let mut path = profiles_dir()?;
+    path.push(format!("{name}.toml"));
+    Ok(path)
+}
+
+/// Checks that the name can be used as a file name inside the profiles directory
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\0'])
+}
+
 #[derive(Debug, Error)]
 pub enum SetupProfileError {
     #[error("failed to find user's home directory")]
     NoHomeDirectory,
+    #[error("invalid profile name '{0}'")]
+    InvalidName(String),
+    #[error("no profile named '{0}'")]
+    NotFound(String),
     #[error(transparent)]
     Io(#[from] io::Error),
     #[error(transparent)]
     Decode(#[from] DecodeError),
     #[error(transparent)]
-    Encode(#[from] EncodeError),
+    Parse(#[from] toml::de::Error),
+    #[error(transparent)]
+    Serialize(#[from] toml::ser::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::BlurConfig,
+        playlist::PlaylistEntry,
+        wallpaper::{Color, FitMode},
+    };
+
+    #[test]
+    fn profile_toml_round_trip() {
+        let profile = Profile {
+            monitors: BTreeMap::from([
+                (
+                    "DP-1".to_owned(),
+                    MonitorProfile {
+                        path: PathBuf::from("/wallpapers/forest.mp4"),
+                        wallpaper_type: WallpaperType::Video,
+                        paused: true,
+                        placement: Placement {
+                            fit: FitMode::Fit,
+                            background: Color(0x1e1e_2eff),
+                            span: true,
+                        },
+                        effects: Some(vec![Effects::Blur(BlurConfig::default())]),
+                        playlist: None,
+                    },
+                ),
+                (
+                    "HDMI-A-1".to_owned(),
+                    MonitorProfile {
+                        path: PathBuf::from("/wallpapers/sky.png"),
+                        wallpaper_type: WallpaperType::Image,
+                        paused: false,
+                        placement: Placement::default(),
+                        effects: None,
+                        playlist: Some(Playlist {
+                            entries: vec![PlaylistEntry {
+                                path: PathBuf::from("/wallpapers/sky.png"),
+                                wallpaper_type: WallpaperType::Image,
+                            }],
+                            interval_seconds: 600,
+                            shuffle: true,
+                            seed: 7,
+                            position: 0,
+                        }),
+                    },
+                ),
+            ]),
+        };
+
+        let contents = toml::to_string(&profile).unwrap();
+        assert_eq!(toml::from_str::<Profile>(&contents).unwrap(), profile);
+    }
+
+    #[test]
+    fn migrate_setup_profile() {
+        let legacy = SetupProfile {
+            monitors: HashMap::from([(
+                Arc::from("DP-1"),
+                Monitor {
+                    wallpaper_type: WallpaperType::Scene,
+                    path: PathBuf::from("/workshop/123"),
+                },
+            )]),
+        };
+
+        let profile = Profile::from(legacy);
+        let monitor = &profile.monitors["DP-1"];
+
+        assert_eq!(monitor.wallpaper_type, WallpaperType::Scene);
+        assert_eq!(monitor.path, PathBuf::from("/workshop/123"));
+        assert!(!monitor.paused);
+        assert_eq!(monitor.placement, Placement::default());
+        assert_eq!(monitor.effects, None);
+        assert_eq!(monitor.playlist, None);
+    }
+
+    #[test]
+    fn profile_names() {
+        assert!(is_valid_profile_name("work"));
+        assert!(is_valid_profile_name("gaming 2"));
+        assert!(!is_valid_profile_name(""));
+        assert!(!is_valid_profile_name(".hidden"));
+        assert!(!is_valid_profile_name("../config"));
+    }
 }
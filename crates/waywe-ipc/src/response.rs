@@ -0,0 +1,59 @@
+use crate::status::{MonitorInfo, WallpaperStatus};
+use bincode::{Decode, Encode};
+use std::{fmt, path::PathBuf};
+
+/// Reply of the daemon to a [`DaemonCommand`](crate::DaemonCommand)
+#[derive(Encode, Decode, Debug, Default, PartialEq, Clone)]
+pub enum DaemonResponse {
+    /// The command succeeded
+    #[default]
+    Ok,
+    /// No monitor has the requested name
+    UnknownMonitor { name: String },
+    /// The wallpaper failed to load
+    DecodeFailed { path: PathBuf, reason: String },
+    /// Reply to [`DaemonCommand::Status`](crate::DaemonCommand::Status)
+    Status(Vec<WallpaperStatus>),
+    /// Reply to [`DaemonCommand::Monitors`](crate::DaemonCommand::Monitors)
+    Monitors(Vec<MonitorInfo>),
+    /// Reply to [`ProfileCommand::List`](crate::command::ProfileCommand::List)
+    Profiles(Vec<String>),
+    /// The profile failed to be read, stored or deleted
+    ProfileFailed { name: String, reason: String },
+    /// None of the monitors plays a playlist
+    NoPlaylist,
+    /// The config failed to be read, the previous one is kept
+    ConfigFailed { reason: String },
+    /// A custom shader of the config failed to compile, the previous config is kept
+    ShaderFailed { path: PathBuf, reason: String },
+}
+
+impl DaemonResponse {
+    /// Checks that the response is not an error
+    pub const fn is_ok(&self) -> bool {
+        matches!(
+            self,
+            Self::Ok | Self::Status(..) | Self::Monitors(..) | Self::Profiles(..)
+        )
+    }
+}
+
+impl fmt::Display for DaemonResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ok | Self::Status(..) | Self::Monitors(..) | Self::Profiles(..) => {
+                write!(f, "ok")
+            }
+            Self::UnknownMonitor { name } => write!(f, "unknown monitor '{name}'"),
+            Self::DecodeFailed { path, reason } => {
+                write!(f, "failed to load '{}': {reason}", path.display())
+            }
+            Self::ProfileFailed { name, reason } => write!(f, "profile '{name}': {reason}"),
+            Self::NoPlaylist => write!(f, "no playlist is running"),
+            Self::ConfigFailed { reason } => write!(f, "failed to reload config: {reason}"),
+            Self::ShaderFailed { path, reason } => {
+                write!(f, "failed to compile '{}': {reason}", path.display())
+            }
+        }
+    }
+}
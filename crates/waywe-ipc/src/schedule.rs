@@ -0,0 +1,407 @@
+//! Wallpapers shown during the time ranges of the day, see [`ScheduleEntry`]
+//!
+//! Sunrise and sunset are computed offline from the [`Location`]
+//! using the NOAA solar equations
+
+use crate::WallpaperType;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    f64::consts::TAU,
+    fmt,
+    mem::MaybeUninit,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Time of the day in seconds since the local midnight
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay(u32);
+
+impl TimeOfDay {
+    pub const MIDNIGHT: Self = Self(0);
+
+    /// Wraps the time around the day if `seconds` is out of it
+    pub const fn from_seconds(seconds: i64) -> Self {
+        Self(seconds.rem_euclid(SECONDS_PER_DAY as i64) as u32)
+    }
+
+    pub const fn from_hm(hours: u32, minutes: u32) -> Self {
+        Self::from_seconds((hours * 60 * 60 + minutes * 60) as i64)
+    }
+
+    pub const fn seconds(self) -> u32 {
+        self.0
+    }
+
+    /// Time left until `other` comes, the whole day if `other` is now
+    pub const fn until(self, other: Self) -> Duration {
+        let seconds = (other.0 + SECONDS_PER_DAY - self.0) % SECONDS_PER_DAY;
+
+        Duration::from_secs(if seconds == 0 {
+            SECONDS_PER_DAY as u64
+        } else {
+            seconds as u64
+        })
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.0 / 3600, self.0 / 60 % 60)
+    }
+}
+
+/// Boundary of a [`ScheduleEntry`], either `"hh:mm"`, `"sunrise"` or `"sunset"`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ScheduleTime {
+    At(TimeOfDay),
+    Sunrise,
+    Sunset,
+}
+
+impl ScheduleTime {
+    /// Time of the day, [`None`] if the sun does not rise or set that day
+    pub const fn resolve(self, sun: &SunTimes) -> Option<TimeOfDay> {
+        match self {
+            Self::At(time) => Some(time),
+            Self::Sunrise => sun.sunrise,
+            Self::Sunset => sun.sunset,
+        }
+    }
+
+    pub const fn depends_on_sun(self) -> bool {
+        matches!(self, Self::Sunrise | Self::Sunset)
+    }
+}
+
+impl FromStr for ScheduleTime {
+    type Err = ParseScheduleTimeError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "sunrise" => return Ok(Self::Sunrise),
+            "sunset" => return Ok(Self::Sunset),
+            _ => {}
+        }
+
+        let invalid = || ParseScheduleTimeError(source.to_owned());
+
+        let (hours, minutes) = source.split_once(':').ok_or_else(invalid)?;
+        let hours = hours.parse::<u32>().map_err(|_| invalid())?;
+        let minutes = minutes.parse::<u32>().map_err(|_| invalid())?;
+
+        if hours > 24 || minutes >= 60 || (hours == 24 && minutes != 0) {
+            return Err(invalid());
+        }
+
+        Ok(Self::At(TimeOfDay::from_hm(hours, minutes)))
+    }
+}
+
+impl TryFrom<String> for ScheduleTime {
+    type Error = ParseScheduleTimeError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ScheduleTime> for String {
+    fn from(value: ScheduleTime) -> Self {
+        match value {
+            ScheduleTime::At(time) => time.to_string(),
+            ScheduleTime::Sunrise => "sunrise".to_owned(),
+            ScheduleTime::Sunset => "sunset".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+#[error("invalid time '{0}', expected 'hh:mm', 'sunrise' or 'sunset'")]
+pub struct ParseScheduleTimeError(String);
+
+/// Wallpaper shown from `from` until `to`, wrapping around midnight if `to` comes first
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    pub from: ScheduleTime,
+    pub to: ScheduleTime,
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub wallpaper_type: WallpaperType,
+    /// Monitor to show the wallpaper on, every monitor if [`None`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<String>,
+}
+
+impl ScheduleEntry {
+    pub fn applies_to(&self, monitor: &str) -> bool {
+        self.monitor.as_deref().is_none_or(|name| name == monitor)
+    }
+
+    /// Checks whether the wallpaper is shown at the time.
+    /// Entries with the same start and end are shown for the whole day
+    pub fn contains(&self, sun: &SunTimes, time: TimeOfDay) -> bool {
+        let (Some(from), Some(to)) = (self.from.resolve(sun), self.to.resolve(sun)) else {
+            return false;
+        };
+
+        match from.cmp(&to) {
+            Ordering::Less => from <= time && time < to,
+            Ordering::Greater => from <= time || time < to,
+            Ordering::Equal => true,
+        }
+    }
+
+    pub const fn depends_on_sun(&self) -> bool {
+        self.from.depends_on_sun() || self.to.depends_on_sun()
+    }
+}
+
+/// Index of the entry shown on the monitor at the time, later entries take precedence
+pub fn active_entry(
+    entries: &[ScheduleEntry],
+    sun: &SunTimes,
+    monitor: &str,
+    time: TimeOfDay,
+) -> Option<usize> {
+    entries
+        .iter()
+        .rposition(|entry| entry.applies_to(monitor) && entry.contains(sun, time))
+}
+
+/// Time left until any entry starts or ends, [`None`] if the schedule has no boundaries
+pub fn next_boundary(
+    entries: &[ScheduleEntry],
+    sun: &SunTimes,
+    time: TimeOfDay,
+) -> Option<Duration> {
+    entries
+        .iter()
+        .flat_map(|entry| [entry.from, entry.to])
+        .filter_map(|boundary| boundary.resolve(sun))
+        .map(|boundary| time.until(boundary))
+        .min()
+}
+
+/// Geographic location used to compute sunrise and sunset
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    /// Degrees north of the equator
+    pub latitude: f64,
+    /// Degrees east of Greenwich
+    pub longitude: f64,
+}
+
+/// Local time of sunrise and sunset, [`None`] on a polar day or night
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SunTimes {
+    pub sunrise: Option<TimeOfDay>,
+    pub sunset: Option<TimeOfDay>,
+}
+
+impl SunTimes {
+    /// Computes the sun times for the day, both are [`None`] if the location is unknown
+    pub fn new(location: Option<Location>, date: &LocalTime) -> Self {
+        let Some(Location {
+            latitude,
+            longitude,
+        }) = location
+        else {
+            return Self::default();
+        };
+
+        let gamma = TAU / 365.0 * (date.day_of_year as f64 - 1.0);
+
+        // minutes the solar noon is away from the noon of the mean solar time
+        let equation_of_time = 229.18
+            * (0.000075 + 0.001868 * gamma.cos()
+                - 0.032077 * gamma.sin()
+                - 0.014615 * (2.0 * gamma).cos()
+                - 0.040849 * (2.0 * gamma).sin());
+
+        let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+            - 0.006758 * (2.0 * gamma).cos()
+            + 0.000907 * (2.0 * gamma).sin()
+            - 0.002697 * (3.0 * gamma).cos()
+            + 0.00148 * (3.0 * gamma).sin();
+
+        let latitude = latitude.to_radians();
+
+        // NOTE: 90.833 degrees accounts for the refraction and the sun disk size
+        let cos_hour_angle = 90.833_f64.to_radians().cos() / (latitude.cos() * declination.cos())
+            - latitude.tan() * declination.tan();
+
+        if !(-1.0..=1.0).contains(&cos_hour_angle) {
+            return Self::default();
+        }
+
+        let hour_angle = cos_hour_angle.acos().to_degrees();
+
+        let local_time = |minutes_utc: f64| {
+            TimeOfDay::from_seconds((minutes_utc * 60.0).round() as i64 + date.utc_offset)
+        };
+
+        Self {
+            sunrise: Some(local_time(
+                720.0 - 4.0 * (longitude + hour_angle) - equation_of_time,
+            )),
+            sunset: Some(local_time(
+                720.0 - 4.0 * (longitude - hour_angle) - equation_of_time,
+            )),
+        }
+    }
+}
+
+/// Date and time in the local time zone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LocalTime {
+    /// Day of the year starting from 1
+    pub day_of_year: u32,
+    pub time: TimeOfDay,
+    /// Offset of the local time zone from UTC in seconds
+    pub utc_offset: i64,
+}
+
+impl LocalTime {
+    pub fn now() -> Self {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Self::from_unix_time(since_epoch.as_secs() as libc::time_t)
+    }
+
+    /// Converts the unix time using the time zone of the system
+    pub fn from_unix_time(time: libc::time_t) -> Self {
+        let mut tm = MaybeUninit::<libc::tm>::zeroed();
+
+        // NOTE: on failure `tm` stays zeroed, which is the midnight of January 1 in UTC
+        unsafe { libc::localtime_r(&raw const time, tm.as_mut_ptr()) };
+        let tm = unsafe { tm.assume_init() };
+
+        Self {
+            day_of_year: tm.tm_yday as u32 + 1,
+            time: TimeOfDay::from_seconds(
+                tm.tm_hour as i64 * 3600 + tm.tm_min as i64 * 60 + tm.tm_sec as i64,
+            ),
+            utc_offset: tm.tm_gmtoff,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hours: u32, minutes: u32) -> ScheduleTime {
+        ScheduleTime::At(TimeOfDay::from_hm(hours, minutes))
+    }
+
+    fn entry(from: ScheduleTime, to: ScheduleTime, monitor: Option<&str>) -> ScheduleEntry {
+        ScheduleEntry {
+            from,
+            to,
+            path: PathBuf::from("/wallpapers/day.png"),
+            wallpaper_type: WallpaperType::Image,
+            monitor: monitor.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn parse_schedule_time() {
+        assert_eq!("07:30".parse(), Ok(at(7, 30)));
+        assert_eq!("24:00".parse(), Ok(at(0, 0)));
+        assert_eq!("sunset".parse(), Ok(ScheduleTime::Sunset));
+        assert!("7".parse::<ScheduleTime>().is_err());
+        assert!("12:60".parse::<ScheduleTime>().is_err());
+        assert!("noon".parse::<ScheduleTime>().is_err());
+        assert_eq!(String::from(at(7, 5)), "07:05");
+    }
+
+    #[test]
+    fn active_entries() {
+        let sun = SunTimes {
+            sunrise: Some(TimeOfDay::from_hm(6, 0)),
+            sunset: Some(TimeOfDay::from_hm(20, 0)),
+        };
+
+        let entries = [
+            entry(ScheduleTime::Sunrise, ScheduleTime::Sunset, None),
+            entry(ScheduleTime::Sunset, ScheduleTime::Sunrise, None),
+            entry(at(12, 0), at(13, 0), Some("DP-1")),
+        ];
+
+        let noon = TimeOfDay::from_hm(12, 30);
+        let night = TimeOfDay::from_hm(2, 0);
+
+        assert_eq!(active_entry(&entries, &sun, "DP-1", noon), Some(2));
+        assert_eq!(active_entry(&entries, &sun, "HDMI-A-1", noon), Some(0));
+        assert_eq!(active_entry(&entries, &sun, "DP-1", night), Some(1));
+        assert_eq!(
+            next_boundary(&entries, &sun, noon),
+            Some(Duration::from_secs(30 * 60)),
+        );
+        assert_eq!(
+            next_boundary(&entries, &sun, night),
+            Some(Duration::from_secs(4 * 60 * 60)),
+        );
+
+        // NOTE: sunrise and sunset are unknown without a location
+        let sun = SunTimes::default();
+        assert_eq!(active_entry(&entries, &sun, "HDMI-A-1", noon), None);
+    }
+
+    #[test]
+    fn sunrise_and_sunset() {
+        let london = Location {
+            latitude: 51.5074,
+            longitude: -0.1278,
+        };
+
+        // June 21, british summer time
+        let summer = LocalTime {
+            day_of_year: 172,
+            time: TimeOfDay::MIDNIGHT,
+            utc_offset: 60 * 60,
+        };
+
+        let sun = SunTimes::new(Some(london), &summer);
+        let sunrise = sun.sunrise.unwrap().seconds() as i64;
+        let sunset = sun.sunset.unwrap().seconds() as i64;
+
+        // 04:43 and 21:21 with a few minutes of error
+        assert!((sunrise - (4 * 3600 + 43 * 60)).abs() < 5 * 60);
+        assert!((sunset - (21 * 3600 + 21 * 60)).abs() < 5 * 60);
+
+        let tromso = Location {
+            latitude: 69.6492,
+            longitude: 18.9553,
+        };
+
+        let winter = LocalTime {
+            day_of_year: 355,
+            time: TimeOfDay::MIDNIGHT,
+            utc_offset: 60 * 60,
+        };
+
+        assert_eq!(SunTimes::new(Some(tromso), &winter), SunTimes::default());
+        assert_eq!(SunTimes::new(None, &winter), SunTimes::default());
+    }
+
+    #[test]
+    fn time_until() {
+        let time = TimeOfDay::from_hm(23, 0);
+
+        assert_eq!(
+            time.until(TimeOfDay::from_hm(1, 0)),
+            Duration::from_secs(2 * 60 * 60),
+        );
+        assert_eq!(time.until(time), Duration::from_secs(24 * 60 * 60));
+    }
+}
@@ -0,0 +1,67 @@
+//! Daemon state reported by [`DaemonCommand::Status`](crate::DaemonCommand::Status)
+//! and [`DaemonCommand::Monitors`](crate::DaemonCommand::Monitors)
+
+use crate::{WallpaperType, power::PowerSource};
+use bincode::{Decode, Encode};
+use serde::Serialize;
+use std::{fmt, path::PathBuf};
+
+/// Monitor known to the daemon
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Encode, Decode, Serialize)]
+pub struct MonitorInfo {
+    pub name: String,
+    /// Size in pixels, [`None`] until the compositor configures the monitor
+    pub size: Option<[u32; 2]>,
+    /// Integer scale factor of the monitor
+    pub scale: i32,
+}
+
+/// Whether the wallpaper is playing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Encode, Decode, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// Wallpaper currently shown on a monitor
+#[derive(Clone, Debug, Default, PartialEq, Encode, Decode, Serialize)]
+pub struct WallpaperStatus {
+    pub monitor: String,
+    pub path: PathBuf,
+    #[serde(rename = "type")]
+    pub wallpaper_type: WallpaperType,
+    pub state: PlaybackState,
+    /// Names of the effects applied to the wallpaper
+    pub effects: Vec<String>,
+    /// Frames per second rendered by the daemon, zero if nothing is being rendered
+    pub fps: f32,
+    pub power: PowerPolicy,
+}
+
+/// Frame rate decision of the daemon given the power source,
+/// see [`PowerConfig`](crate::config::PowerConfig)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Encode, Decode, Serialize)]
+pub struct PowerPolicy {
+    pub source: PowerSource,
+    /// Frames per second limit, [`None`] to render at the full rate
+    pub max_fps: Option<u32>,
+    /// Videos and scenes are paused
+    pub paused: bool,
+}
+
+impl fmt::Display for PowerPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match self.source {
+            PowerSource::Ac => "ac",
+            PowerSource::Battery => "battery",
+        };
+
+        match (self.paused, self.max_fps) {
+            (true, _) => write!(f, "{source}, paused"),
+            (false, Some(fps)) => write!(f, "{source}, max {fps} fps"),
+            (false, None) => write!(f, "{source}"),
+        }
+    }
+}
@@ -1,9 +1,246 @@
 use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Eq, Ord, Hash, Encode, Decode)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    PartialOrd,
+    Eq,
+    Ord,
+    Hash,
+    Encode,
+    Decode,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
 pub enum WallpaperType {
     #[default]
     Video,
     Image,
     Scene,
 }
+
+/// How the image or the video is scaled to the monitor
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    PartialOrd,
+    Eq,
+    Ord,
+    Hash,
+    Encode,
+    Decode,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum FitMode {
+    /// Cover the whole monitor cropping the edges
+    #[default]
+    Fill,
+    /// Show the whole wallpaper with the background around it
+    Fit,
+    /// Cover the whole monitor ignoring the aspect ratio
+    Stretch,
+    /// Show the wallpaper unscaled in the middle of the monitor
+    Center,
+    /// Repeat the unscaled wallpaper starting from the top left corner
+    Tile,
+}
+
+impl FitMode {
+    pub const ALL: [Self; 5] = [
+        Self::Fill,
+        Self::Fit,
+        Self::Stretch,
+        Self::Center,
+        Self::Tile,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Fill => "fill",
+            Self::Fit => "fit",
+            Self::Stretch => "stretch",
+            Self::Center => "center",
+            Self::Tile => "tile",
+        }
+    }
+}
+
+impl fmt::Display for FitMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for FitMode {
+    type Err = ParseFitModeError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name() == source)
+            .ok_or_else(|| ParseFitModeError(source.to_owned()))
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+#[error("unknown fit mode '{0}', expected one of: fill, fit, stretch, center, tile")]
+pub struct ParseFitModeError(pub String);
+
+/// Color packed as `0xRRGGBBAA`, written as `#rrggbb` or `#rrggbbaa`
+#[derive(
+    Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Encode, Decode, Serialize, Deserialize,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color(pub u32);
+
+impl Color {
+    /// Only the color channels of the background are drawn, so this shows as black
+    pub const TRANSPARENT: Self = Self(0);
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::TRANSPARENT
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 & 0xff == 0xff {
+            write!(f, "#{:06x}", self.0 >> 8)
+        } else {
+            write!(f, "#{:08x}", self.0)
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let digits = source.strip_prefix('#').unwrap_or(source);
+
+        if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(ParseColorError(source.to_owned()));
+        }
+
+        let value = u32::from_str_radix(digits, 16).map_err(|_| ParseColorError(source.to_owned()));
+
+        match digits.len() {
+            6 => Ok(Self((value? << 8) | 0xff)),
+            8 => Ok(Self(value?)),
+            _ => Err(ParseColorError(source.to_owned())),
+        }
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = ParseColorError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Color> for String {
+    fn from(value: Color) -> Self {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+#[error("invalid color '{0}', expected '#rrggbb' or '#rrggbbaa'")]
+pub struct ParseColorError(pub String);
+
+/// How the wallpaper is placed on the monitor
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    PartialOrd,
+    Eq,
+    Ord,
+    Hash,
+    Encode,
+    Decode,
+    Serialize,
+    Deserialize,
+)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Placement {
+    pub fit: FitMode,
+    /// Color around the wallpaper and behind its transparent pixels
+    pub background: Color,
+    /// Show one wallpaper across all monitors laid out as in the compositor
+    pub span: bool,
+}
+
+impl Placement {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors() {
+        assert_eq!("#1e1e2e".parse(), Ok(Color(0x1e1e_2eff)));
+        assert_eq!("1E1E2E".parse(), Ok(Color(0x1e1e_2eff)));
+        assert_eq!("#1e1e2e80".parse(), Ok(Color(0x1e1e_2e80)));
+        assert_eq!(Color(0x1e1e_2eff).to_string(), "#1e1e2e");
+        assert_eq!(Color(0x1e1e_2e80).to_string(), "#1e1e2e80");
+        assert!("#1e1e2".parse::<Color>().is_err());
+        assert!("#+1e1e2e".parse::<Color>().is_err());
+        assert!("#gggggg".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn fit_modes() {
+        for mode in FitMode::ALL {
+            assert_eq!(mode.name().parse(), Ok(mode));
+        }
+
+        assert_eq!(
+            "cover".parse::<FitMode>(),
+            Err(ParseFitModeError("cover".to_owned()))
+        );
+    }
+
+    #[test]
+    fn placement() {
+        let placement = Placement {
+            fit: FitMode::Tile,
+            background: Color(0x1e1e_2eff),
+            span: true,
+        };
+
+        let contents = toml::to_string(&placement).unwrap();
+
+        assert_eq!(
+            contents,
+            "fit = \"tile\"\nbackground = \"#1e1e2e\"\nspan = true\n"
+        );
+        assert_eq!(toml::from_str::<Placement>(&contents).unwrap(), placement);
+        assert_eq!(
+            toml::from_str::<Placement>("").unwrap(),
+            Placement::default()
+        );
+    }
+}
@@ -10,6 +10,12 @@ use std::{any::Any, ptr::NonNull};
 pub trait App: Any + Send + Sync {
     fn populate_handler(&mut self, handler: &mut EventHandler<Self>);
 
+    /// Called once before the first frame
+    #[expect(unused_variables)]
+    fn init(&mut self, runtime: &mut Runtime) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
     fn frame(
         &mut self,
         runtime: &mut Runtime,
@@ -22,6 +28,12 @@ pub trait App: Any + Send + Sync {
 }
 
 pub trait ReusableApp: Any + Send + Sync {
+    fn init<'f>(
+        &'f mut self,
+        runtime: &'f mut Runtime,
+        futures: &'f mut ReusableBox,
+    ) -> ReusedBoxFuture<'f, ()>;
+
     fn frame<'f>(
         &'f mut self,
         runtime: &'f mut Runtime,
@@ -36,6 +48,14 @@ pub trait ReusableApp: Any + Send + Sync {
 }
 
 impl<A: App> ReusableApp for A {
+    fn init<'f>(
+        &'f mut self,
+        runtime: &'f mut Runtime,
+        futures: &'f mut ReusableBox,
+    ) -> ReusedBoxFuture<'f, ()> {
+        futures.store_future(App::init(self, runtime))
+    }
+
     fn frame<'f>(
         &'f mut self,
         runtime: &'f mut Runtime,
@@ -76,6 +96,10 @@ impl DynApp {
         unsafe { self.handler.execute_all(layer_ptr, runtime, event) }.await;
     }
 
+    pub async fn init(&mut self, runtime: &mut Runtime) {
+        self.app.init(runtime, &mut self.futures).await;
+    }
+
     pub async fn frame(&mut self, runtime: &mut Runtime) -> Result<FrameInfo, FrameError> {
         self.app.frame(runtime, &mut self.futures).await
     }
@@ -1,11 +1,13 @@
 use crate::{
-    effects::{Effect, Effects},
+    effects::{Effect, Effects, custom::CustomEffectConfig},
     gpu::Wgpu,
+    shaders::custom::{CustomShaderCache, CustomShaderKind},
     wayland::MonitorId,
 };
 use smallvec::SmallVec;
 use static_assertions::assert_obj_safe;
 use std::ops::Deref;
+use tracing::error;
 use waywe_ipc::config::Effects as BuiltinEffects;
 
 #[derive(Default)]
@@ -30,11 +32,20 @@ impl EffectsBuilder {
     pub fn add_builtins<'a>(
         &mut self,
         configs: impl IntoIterator<Item = &'a BuiltinEffects>,
+        custom_shaders: &mut CustomShaderCache,
     ) -> &mut Self {
         for config in configs {
             match config {
                 BuiltinEffects::Convolve(config) => _ = self.add(config.clone()),
                 BuiltinEffects::Blur(config) => _ = self.add(*config),
+                // NOTE: the shaders of the config are cached when it is applied,
+                // so only effects of profiles could be loaded and fail here
+                BuiltinEffects::Custom(config) => {
+                    match custom_shaders.load(config, CustomShaderKind::Effect) {
+                        Ok(shader) => _ = self.add(CustomEffectConfig { shader }),
+                        Err(error) => error!(%error, "failed to load custom effect"),
+                    }
+                }
             }
         }
 
@@ -0,0 +1,224 @@
+use crate::{
+    effects::{AppliedEffect, EFFECTS_TEXTURE_DESC, Effect, config::EffectConfig},
+    gpu::Wgpu,
+    shaders::{
+        ShaderDescriptor,
+        custom::{BINDING_FROM, BINDING_SAMPLER, BINDING_UNIFORMS, CustomShader, CustomUniforms},
+    },
+    wayland::MonitorId,
+};
+use glam::{UVec2, Vec2};
+use std::{mem, sync::Arc, time::Instant};
+
+const LABEL: &str = "custom-effect";
+
+/// Effect drawn by a [`CustomShader`]
+pub struct CustomEffectConfig {
+    pub shader: Arc<CustomShader>,
+}
+
+impl EffectConfig for CustomEffectConfig {
+    fn build_effect(&self, gpu: &Wgpu, monitor_id: MonitorId) -> Box<dyn Effect> {
+        Box::new(CustomEffect::new(gpu, monitor_id, &self.shader))
+    }
+}
+
+pub struct CustomEffect {
+    pub output: wgpu::TextureView,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub pipeline: wgpu::RenderPipeline,
+    pub sampler: wgpu::Sampler,
+    pub uniform_buffer: wgpu::Buffer,
+    pub uniforms: CustomUniforms,
+    pub start_time: Instant,
+}
+
+impl CustomEffect {
+    pub fn new(gpu: &Wgpu, monitor_id: MonitorId, shader: &CustomShader) -> Self {
+        let (size, format) = {
+            let surfaces = gpu.surfaces.read().unwrap();
+            let surface = &surfaces[&monitor_id];
+
+            (
+                wgpu::Extent3d {
+                    width: surface.config.width,
+                    height: surface.config.height,
+                    depth_or_array_layers: 1,
+                },
+                surface.format.remove_srgb_suffix(),
+            )
+        };
+
+        let output_texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(LABEL),
+            size,
+            format,
+            ..EFFECTS_TEXTURE_DESC
+        });
+        let output = output_texture.create_view(&Default::default());
+
+        let uniform_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(LABEL),
+            size: mem::size_of::<CustomUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout =
+            gpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some(LABEL),
+                    entries: &[
+                        wgpu::BindGroupLayoutEntry {
+                            binding: BINDING_FROM,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Texture {
+                                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                                view_dimension: wgpu::TextureViewDimension::D2,
+                                multisampled: false,
+                            },
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: BINDING_SAMPLER,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                            count: None,
+                        },
+                        wgpu::BindGroupLayoutEntry {
+                            binding: BINDING_UNIFORMS,
+                            visibility: wgpu::ShaderStages::FRAGMENT,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Uniform,
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        },
+                    ],
+                });
+
+        let pipeline_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(LABEL),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        gpu.require_shader::<FullscreenShader>();
+
+        let pipeline = gpu
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(LABEL),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &gpu.shader_cache.get::<FullscreenShader>().unwrap(),
+                    entry_point: Some("main"),
+                    compilation_options: Default::default(),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader.create_module(gpu),
+                    entry_point: Some("main"),
+                    compilation_options: Default::default(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: Default::default(),
+                depth_stencil: None,
+                multisample: Default::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        let sampler = gpu.device.create_sampler(&Default::default());
+
+        Self {
+            output,
+            bind_group_layout,
+            pipeline,
+            sampler,
+            uniform_buffer,
+            uniforms: CustomUniforms {
+                resolution: UVec2::new(size.width, size.height).as_vec2(),
+                ..shader.uniforms
+            },
+            start_time: Instant::now(),
+        }
+    }
+}
+
+impl Effect for CustomEffect {
+    fn apply(
+        &mut self,
+        gpu: &Wgpu,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+    ) -> AppliedEffect {
+        self.uniforms.time = self.start_time.elapsed().as_secs_f32();
+
+        gpu.queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&self.uniforms));
+
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(LABEL),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: BINDING_FROM,
+                    resource: wgpu::BindingResource::TextureView(input),
+                },
+                wgpu::BindGroupEntry {
+                    binding: BINDING_SAMPLER,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: BINDING_UNIFORMS,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(LABEL),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.output,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                    resolve_target: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
+        AppliedEffect::WithOutput(self.output.clone())
+    }
+
+    fn set_cursor(&mut self, position: Vec2) {
+        self.uniforms.cursor = position;
+    }
+}
+
+/// Triangle covering the whole screen without vertex buffers
+pub struct FullscreenShader;
+
+impl ShaderDescriptor for FullscreenShader {
+    fn shader_descriptor() -> wgpu::ShaderModuleDescriptor<'static> {
+        wgpu::include_wgsl!("../shaders/fullscreen.wgsl")
+    }
+}
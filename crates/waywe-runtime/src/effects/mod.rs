@@ -1,9 +1,11 @@
 pub mod blur;
 pub mod config;
 pub mod convolve;
+pub mod custom;
 
 use crate::gpu::Wgpu;
 use derive_more::{Deref, DerefMut};
+use glam::Vec2;
 use smallvec::SmallVec;
 use static_assertions::assert_obj_safe;
 
@@ -45,6 +47,13 @@ impl Effects {
         self.push(Box::new(effect));
     }
 
+    /// Moves the cursor to the position given in surface pixels
+    pub fn set_cursor(&mut self, position: Vec2) {
+        for effect in self.iter_mut() {
+            effect.set_cursor(position);
+        }
+    }
+
     pub fn render(
         &mut self,
         gpu: &Wgpu,
@@ -83,6 +92,9 @@ pub trait Effect: Send + Sync + 'static {
         encoder: &mut wgpu::CommandEncoder,
         input: &wgpu::TextureView,
     ) -> AppliedEffect;
+
+    /// Moves the cursor to the position given in surface pixels
+    fn set_cursor(&mut self, _position: Vec2) {}
 }
 assert_obj_safe!(Effect);
 
@@ -45,10 +45,13 @@ impl Wgpu {
             Err(error) => panic!("failed to request adapter: {error:?}"),
         };
 
-        let features = wgpu::Features::TEXTURE_FORMAT_NV12
+        let mut features = wgpu::Features::TEXTURE_FORMAT_NV12
             | wgpu::Features::PUSH_CONSTANTS
             | wgpu::Features::BGRA8UNORM_STORAGE
             | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+
+        // NOTE: compressed textures are optional, they are decompressed on the CPU otherwise
+        features |= adapter.features() & wgpu::Features::TEXTURE_COMPRESSION_BC;
         let memory_hints = wgpu::MemoryHints::Performance;
 
         let open_device = unsafe {
@@ -154,19 +157,19 @@ impl Wgpu {
         }
     }
 
+    /// Reconfigures the surface to the given size in physical pixels
     pub fn resize_surface(&self, monitor_id: MonitorId, size: UVec2) {
-        let surfaces = self.surfaces.read().unwrap();
+        let mut surfaces = self.surfaces.write().unwrap();
 
-        let Some(surface) = surfaces.get(&monitor_id) else {
+        let Some(surface) = surfaces.get_mut(&monitor_id) else {
             return;
         };
 
-        let surface_config = surface
-            .surface
-            .get_default_config(&self.adapter, size.x, size.y)
-            .unwrap();
+        // NOTE: keep the usages and view formats the surface was created with
+        surface.config.width = size.x;
+        surface.config.height = size.y;
 
-        surface.surface.configure(&self.device, &surface_config);
+        surface.surface.configure(&self.device, &surface.config);
     }
 
     pub fn unregister_surface(&self, monitor_id: MonitorId) {
@@ -222,7 +225,9 @@ fn create_surface(
             .unwrap()
     };
 
-    let screen_size = wayland.client_state.monitor_size(id).unwrap();
+    // NOTE: the surface is rendered in physical pixels
+    // and scaled down by the viewport, see `MonitorInfo::viewport`
+    let screen_size = wayland.client_state.monitor_physical_size(id).unwrap();
 
     let Some(format) = surface.get_capabilities(adapter).formats.first().copied() else {
         panic!("no surface format supported");
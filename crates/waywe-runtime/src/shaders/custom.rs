@@ -0,0 +1,499 @@
+//! Effect and transition shaders written by the user
+//!
+//! A custom shader is a fragment shader with the `main` entry point drawing the whole screen.
+//! It may use the following bindings of the group 0:
+//!
+//! - `0`: `texture_2d<f32>` with the input image of the effect or the old wallpaper
+//! - `1`: `texture_2d<f32>` with the new wallpaper, transitions only
+//! - `2`: `sampler` for the textures
+//! - `3`: uniform buffer with [`CustomUniforms`]
+//!
+//! The fragment shader receives the screen position in `-1.0..=1.0` as a `vec2<f32>` at
+//! location 0 and writes the color as a `vec4<f32>` to location 0.
+
+use crate::gpu::Wgpu;
+use bytemuck::{Pod, Zeroable};
+use glam::{Vec2, Vec4};
+use std::{
+    collections::HashMap,
+    fs, mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use thiserror::Error;
+use waywe_ipc::config::{ConfigError, CustomShaderConfig};
+use wgpu::naga::{
+    AddressSpace, Binding, Handle, ImageClass, ImageDimension, Interpolation, Module, Sampling,
+    Scalar, ScalarKind, ShaderStage, Type, TypeInner, VectorSize,
+    front::{glsl, wgsl},
+    valid::{Capabilities, ValidationFlags, Validator},
+};
+
+pub const BINDING_FROM: u32 = 0;
+pub const BINDING_TO: u32 = 1;
+pub const BINDING_SAMPLER: u32 = 2;
+pub const BINDING_UNIFORMS: u32 = 3;
+
+/// Uniform buffer available to custom shaders at [`BINDING_UNIFORMS`]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct CustomUniforms {
+    /// Seconds since the effect or the transition started
+    pub time: f32,
+    /// Eased progress of the transition in 0..=1, zero for effects
+    pub progress: f32,
+    /// Size of the monitor in pixels
+    pub resolution: Vec2,
+    /// Last known cursor position in pixels
+    pub cursor: Vec2,
+    pub _padding: Vec2,
+    /// `uniforms` from the config padded with zeros
+    pub params: [Vec4; 4],
+}
+
+impl CustomUniforms {
+    pub fn with_params(params: &[f32]) -> Self {
+        let mut result = Self::default();
+        let len = params.len().min(CustomShaderConfig::MAX_UNIFORMS);
+        bytemuck::cast_slice_mut::<Vec4, f32>(&mut result.params)[..len]
+            .copy_from_slice(&params[..len]);
+        result
+    }
+}
+
+/// What the custom shader is used for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CustomShaderKind {
+    Effect,
+    Transition,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum ShaderLanguage {
+    Wgsl,
+    Glsl,
+}
+
+/// Validated source of a custom shader
+#[derive(Clone, Debug)]
+pub struct CustomShader {
+    pub path: PathBuf,
+    pub uniforms: CustomUniforms,
+    source: String,
+    language: ShaderLanguage,
+}
+
+impl CustomShader {
+    /// Reads the shader and checks it against the interface of custom shaders
+    pub fn load(
+        config: &CustomShaderConfig,
+        kind: CustomShaderKind,
+    ) -> Result<Self, CustomShaderError> {
+        let path = config.full_path()?;
+
+        let result = Self::load_from(&path, &config.uniforms, kind);
+        result.map_err(|reason| CustomShaderError::Invalid { path, reason })
+    }
+
+    fn load_from(path: &Path, uniforms: &[f32], kind: CustomShaderKind) -> Result<Self, String> {
+        check_uniforms(uniforms)?;
+
+        let language = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("wgsl") => ShaderLanguage::Wgsl,
+            Some("glsl" | "frag") => ShaderLanguage::Glsl,
+            _ => return Err("expected a .wgsl, .glsl or .frag file".to_owned()),
+        };
+
+        let source = fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+        let module = match language {
+            ShaderLanguage::Wgsl => wgsl::parse_str(&source)
+                .map_err(|error| error.emit_to_string_with_path(&source, path))?,
+            ShaderLanguage::Glsl => glsl::Frontend::default()
+                .parse(&glsl::Options::from(ShaderStage::Fragment), &source)
+                .map_err(|error| error.emit_to_string(&source))?,
+        };
+
+        Validator::new(ValidationFlags::all(), Capabilities::default())
+            .validate(&module)
+            .map_err(|error| error.emit_to_string(&source))?;
+
+        check_interface(&module, kind)?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            uniforms: CustomUniforms::with_params(uniforms),
+            source,
+            language,
+        })
+    }
+
+    /// Compiles the shader, it is validated already so this does not fail
+    pub fn create_module(&self, gpu: &Wgpu) -> wgpu::ShaderModule {
+        let source = match self.language {
+            ShaderLanguage::Wgsl => wgpu::ShaderSource::Wgsl(self.source.as_str().into()),
+            ShaderLanguage::Glsl => wgpu::ShaderSource::Glsl {
+                shader: self.source.as_str().into(),
+                stage: ShaderStage::Fragment,
+                defines: &[],
+            },
+        };
+
+        gpu.device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: self.path.to_str(),
+                source,
+            })
+    }
+}
+
+/// Custom shaders read and validated once, shared by every wallpaper using them
+#[derive(Clone, Debug, Default)]
+pub struct CustomShaderCache {
+    shaders: HashMap<(PathBuf, CustomShaderKind), Arc<CustomShader>>,
+}
+
+impl CustomShaderCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached shader with the uniforms of the config or loads it.
+    /// The file is not read again until the cache is replaced
+    pub fn load(
+        &mut self,
+        config: &CustomShaderConfig,
+        kind: CustomShaderKind,
+    ) -> Result<Arc<CustomShader>, CustomShaderError> {
+        let path = config.full_path()?;
+
+        let Some(shader) = self.shaders.get(&(path.clone(), kind)) else {
+            let shader = Arc::new(CustomShader::load(config, kind)?);
+            self.shaders.insert((path, kind), Arc::clone(&shader));
+            return Ok(shader);
+        };
+
+        check_uniforms(&config.uniforms)
+            .map_err(|reason| CustomShaderError::Invalid { path, reason })?;
+
+        let uniforms = CustomUniforms::with_params(&config.uniforms);
+
+        if shader.uniforms == uniforms {
+            return Ok(Arc::clone(shader));
+        }
+
+        Ok(Arc::new(CustomShader {
+            uniforms,
+            ..CustomShader::clone(shader)
+        }))
+    }
+}
+
+fn check_uniforms(uniforms: &[f32]) -> Result<(), String> {
+    if uniforms.len() > CustomShaderConfig::MAX_UNIFORMS {
+        return Err(format!(
+            "at most {} uniforms are supported, got {}",
+            CustomShaderConfig::MAX_UNIFORMS,
+            uniforms.len(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that the shader uses only the bindings, inputs and outputs
+/// described in the [module docs](self)
+fn check_interface(module: &Module, kind: CustomShaderKind) -> Result<(), String> {
+    let Some(entry) = module
+        .entry_points
+        .iter()
+        .find(|entry| entry.stage == ShaderStage::Fragment && entry.name == "main")
+    else {
+        return Err("no fragment entry point named 'main'".to_owned());
+    };
+
+    for argument in &entry.function.arguments {
+        // NOTE: built-in inputs are provided by the pipeline itself
+        for (binding, ty) in io_bindings(module, argument.ty, argument.binding.as_ref()) {
+            let Binding::Location {
+                location,
+                interpolation,
+                sampling,
+                ..
+            } = *binding
+            else {
+                continue;
+            };
+
+            if location != 0 {
+                return Err(format!(
+                    "input at location {location} is not provided, \
+                     only the screen position at location 0 is"
+                ));
+            }
+
+            let is_valid = is_vector(&module.types[ty].inner, VectorSize::Bi)
+                && matches!(interpolation, None | Some(Interpolation::Perspective))
+                && matches!(sampling, None | Some(Sampling::Center));
+
+            if !is_valid {
+                return Err(
+                    "input at location 0 must be a perspective interpolated vec2<f32>".to_owned(),
+                );
+            }
+        }
+    }
+
+    let mut has_color = false;
+
+    if let Some(result) = &entry.function.result {
+        for (binding, ty) in io_bindings(module, result.ty, result.binding.as_ref()) {
+            let Binding::Location {
+                location,
+                blend_src,
+                ..
+            } = *binding
+            else {
+                continue;
+            };
+
+            if location != 0 || blend_src.is_some() {
+                return Err(format!(
+                    "output at location {location} has no color target, \
+                     only location 0 is drawn"
+                ));
+            }
+
+            if !is_vector(&module.types[ty].inner, VectorSize::Quad) {
+                return Err("output at location 0 must be a vec4<f32>".to_owned());
+            }
+
+            has_color = true;
+        }
+    }
+
+    if !has_color {
+        return Err("no vec4<f32> color output at location 0".to_owned());
+    }
+
+    for (_, variable) in module.global_variables.iter() {
+        let name = variable.name.as_deref().unwrap_or("<unnamed>");
+
+        if variable.space == AddressSpace::PushConstant {
+            return Err(format!("'{name}': push constants are not supported"));
+        }
+
+        let Some(binding) = &variable.binding else {
+            continue;
+        };
+
+        if binding.group != 0 {
+            return Err(format!("'{name}': only the group 0 is available"));
+        }
+
+        let inner = &module.types[variable.ty].inner;
+
+        let is_valid = match binding.binding {
+            BINDING_FROM => is_texture(inner),
+            BINDING_TO => kind == CustomShaderKind::Transition && is_texture(inner),
+            BINDING_SAMPLER => matches!(inner, TypeInner::Sampler { comparison: false }),
+            BINDING_UNIFORMS => {
+                variable.space == AddressSpace::Uniform
+                    && inner.size(module.to_ctx()) as usize <= mem::size_of::<CustomUniforms>()
+            }
+            _ => false,
+        };
+
+        if !is_valid {
+            return Err(format!(
+                "'{name}': binding {} does not match the custom shader interface",
+                binding.binding,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Bindings of an entry point argument or result, structures are flattened
+fn io_bindings<'m>(
+    module: &'m Module,
+    ty: Handle<Type>,
+    binding: Option<&'m Binding>,
+) -> Vec<(&'m Binding, Handle<Type>)> {
+    if let Some(binding) = binding {
+        return vec![(binding, ty)];
+    }
+
+    match &module.types[ty].inner {
+        TypeInner::Struct { members, .. } => members
+            .iter()
+            .filter_map(|member| Some((member.binding.as_ref()?, member.ty)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_vector(inner: &TypeInner, size: VectorSize) -> bool {
+    *inner
+        == TypeInner::Vector {
+            size,
+            scalar: Scalar::F32,
+        }
+}
+
+fn is_texture(inner: &TypeInner) -> bool {
+    matches!(
+        inner,
+        TypeInner::Image {
+            dim: ImageDimension::D2,
+            arrayed: false,
+            class: ImageClass::Sampled {
+                kind: ScalarKind::Float,
+                multi: false,
+            },
+        }
+    )
+}
+
+#[derive(Debug, Error)]
+pub enum CustomShaderError {
+    #[error(transparent)]
+    Path(#[from] ConfigError),
+    #[error("'{}': {reason}", path.display())]
+    Invalid { path: PathBuf, reason: String },
+}
+
+impl CustomShaderError {
+    /// Path to the shader if it is known
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Path(..) => None,
+            Self::Invalid { path, .. } => Some(path),
+        }
+    }
+
+    /// Reason of the failure without the path
+    pub fn reason(&self) -> String {
+        match self {
+            Self::Path(error) => error.to_string(),
+            Self::Invalid { reason, .. } => reason.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/shaders")
+            .join(name)
+    }
+
+    fn parse(source: &str) -> Module {
+        let module = wgsl::parse_str(source).unwrap();
+
+        Validator::new(ValidationFlags::all(), Capabilities::default())
+            .validate(&module)
+            .unwrap();
+
+        module
+    }
+
+    #[test]
+    fn load_valid_shader() {
+        let path = fixture("valid-effect.wgsl");
+
+        for kind in [CustomShaderKind::Effect, CustomShaderKind::Transition] {
+            let shader = CustomShader::load_from(&path, &[1.0, 2.0], kind).unwrap();
+
+            assert_eq!(shader.language, ShaderLanguage::Wgsl);
+            assert_eq!(shader.uniforms.params[0], Vec4::new(1.0, 2.0, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn load_wrong_binding() {
+        let error = CustomShader::load_from(
+            &fixture("wrong-binding.wgsl"),
+            &[],
+            CustomShaderKind::Transition,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error,
+            "'image_sampler': binding 1 does not match the custom shader interface"
+        );
+    }
+
+    #[test]
+    fn load_missing_output() {
+        let error = CustomShader::load_from(
+            &fixture("missing-output.wgsl"),
+            &[],
+            CustomShaderKind::Effect,
+        )
+        .unwrap_err();
+
+        assert_eq!(error, "no vec4<f32> color output at location 0");
+    }
+
+    #[test]
+    fn load_unsupported() {
+        let path = fixture("valid-effect.wgsl");
+        let uniforms = [0.0; CustomShaderConfig::MAX_UNIFORMS + 1];
+
+        assert!(CustomShader::load_from(&path, &uniforms, CustomShaderKind::Effect).is_err());
+        assert!(
+            CustomShader::load_from(&path.with_extension("hlsl"), &[], CustomShaderKind::Effect)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn next_wallpaper_of_transitions_only() {
+        let module = parse(
+            "
+            @group(0) @binding(1) var next: texture_2d<f32>;
+            @group(0) @binding(2) var next_sampler: sampler;
+
+            @fragment
+            fn main(@location(0) position: vec2<f32>) -> @location(0) vec4<f32> {
+                return textureSample(next, next_sampler, 0.5 * position + 0.5);
+            }
+            ",
+        );
+
+        assert!(check_interface(&module, CustomShaderKind::Transition).is_ok());
+        assert!(check_interface(&module, CustomShaderKind::Effect).is_err());
+    }
+
+    #[test]
+    fn unprovided_inputs() {
+        let module = parse(
+            "
+            @fragment
+            fn main(@location(1) position: vec2<f32>) -> @location(0) vec4<f32> {
+                return vec4(position, 0.0, 1.0);
+            }
+            ",
+        );
+
+        let error = check_interface(&module, CustomShaderKind::Effect).unwrap_err();
+        assert!(error.starts_with("input at location 1 is not provided"));
+
+        let module = parse(
+            "
+            @fragment
+            fn main(@location(0) @interpolate(flat) position: vec2<f32>) -> @location(0) vec4<f32> {
+                return vec4(position, 0.0, 1.0);
+            }
+            ",
+        );
+
+        assert!(check_interface(&module, CustomShaderKind::Effect).is_err());
+    }
+}
@@ -0,0 +1,15 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) position: vec2f,
+}
+
+// NOTE: a single triangle covering the whole screen
+@vertex
+fn main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let position = vec2f(f32(index & 1u) * 4.0 - 1.0, f32(index >> 1u) * 4.0 - 1.0);
+
+    var output: VertexOutput;
+    output.clip_position = vec4f(position, 0.0, 1.0);
+    output.position = position;
+    return output;
+}
@@ -14,6 +14,8 @@ pub struct Timer {
     pub frame_index: usize,
     pub block_start: Option<Instant>,
     pub block_duration: Duration,
+    /// The idle event loop wakes up at this time, see [`Timer::wake_up_at`]
+    pub wake_up_time: Option<Instant>,
 }
 
 impl Timer {
@@ -34,6 +36,7 @@ impl Default for Timer {
             frame_index: usize::MAX,
             block_start: None,
             block_duration: Duration::ZERO,
+            wake_up_time: None,
         }
     }
 }
@@ -75,6 +78,23 @@ impl Timer {
         self.block_duration
     }
 
+    /// Wakes up the idle event loop at `time` or earlier if requested so before.
+    /// Requests are forgotten before each frame, so the app should renew them every frame
+    pub fn wake_up_at(&mut self, time: Instant) {
+        let wake_up_time = self.wake_up_time.get_or_insert(time);
+        *wake_up_time = (*wake_up_time).min(time);
+    }
+
+    pub fn clear_wake_up(&mut self) {
+        self.wake_up_time = None;
+    }
+
+    /// Time the idle event loop is allowed to sleep for, [`None`] to sleep until an event
+    pub fn time_until_wake_up(&self) -> Option<Duration> {
+        self.wake_up_time
+            .map(|time| time.saturating_duration_since(Instant::now()))
+    }
+
     pub fn last_frame_duration(&self) -> Duration {
         self.duration_since_last_frame
             .saturating_sub(self.block_duration)
@@ -1,29 +1,39 @@
 use crate::event::EventEmitter;
-use glam::UVec2;
+use glam::{IVec2, UVec2};
 use raw_window_handle::{
     HasDisplayHandle as _, RawDisplayHandle, RawWindowHandle, WaylandWindowHandle,
 };
 use std::{
     collections::{BTreeMap, HashMap},
     ffi::CStr,
+    mem,
     pin::Pin,
     sync::{
         Arc, Mutex, RwLock,
         atomic::{AtomicBool, Ordering::*},
     },
+    time::{Duration, Instant},
 };
 use wayland_client::{
     interface::{
-        WlCompositorCreateRegionRequest, WlCompositorCreateSurfaceRequest, WlOutputNameEvent,
-        WlPointerEvent, WlRegionAddRequest, WlRegionDestroyRequest, WlRegistryEvent,
-        WlRegistryGlobalEvent, WlRegistryGlobalRemoveEvent, WlSeatCapabilitiesEvent,
-        WlSeatCapability, WlSeatGetPointerRequest, WlSurfaceCommitRequest,
+        ExtIdleNotificationDestroyRequest, ExtIdleNotificationEvent,
+        ExtIdleNotifierGetIdleNotificationRequest, WlCallbackDoneEvent,
+        WlCompositorCreateRegionRequest, WlCompositorCreateSurfaceRequest, WlOutputGeometryEvent,
+        WlOutputNameEvent, WlOutputScaleEvent, WlPointerEvent, WlRegionAddRequest,
+        WlRegionDestroyRequest, WlRegistryEvent, WlRegistryGlobalEvent,
+        WlRegistryGlobalRemoveEvent, WlSeatCapabilitiesEvent, WlSeatCapability,
+        WlSeatGetPointerRequest, WlSurfaceCommitRequest, WlSurfaceFrameRequest,
         WlSurfaceSetBufferScaleRequest, WlSurfaceSetOpaqueRegionRequest,
+        WpFractionalScaleDestroyRequest, WpFractionalScaleManagerGetFractionalScaleRequest,
+        WpFractionalScalePreferredScaleEvent, WpViewportDestroyRequest,
+        WpViewportSetDestinationRequest, WpViewporterGetViewportRequest,
         ZwlrLayerShellGetLayerSurfaceRequest, ZwlrLayerShellLayer,
         ZwlrLayerSurfaceAckConfigureRequest, ZwlrLayerSurfaceAnchor,
         ZwlrLayerSurfaceConfigureEvent, ZwlrLayerSurfaceKeyboardInteractivity,
         ZwlrLayerSurfaceSetAnchorRequest, ZwlrLayerSurfaceSetExclusiveZoneRequest,
         ZwlrLayerSurfaceSetKeyboardInteractivityRequest, ZwlrLayerSurfaceSetMarginRequest,
+        ZxdgOutputDestroyRequest, ZxdgOutputLogicalPositionEvent, ZxdgOutputLogicalSizeEvent,
+        ZxdgOutputManagerGetXdgOutputRequest,
     },
     object::{HasObjectType, WlObjectId, WlObjectType},
     sys::{
@@ -40,11 +50,31 @@ use wayland_client::{
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum WaylandEvent {
-    ResizeRequested { monitor_id: MonitorId, size: UVec2 },
-    MonitorPlugged { id: MonitorId },
-    MonitorUnplugged { id: MonitorId },
+    /// The surface of the monitor changed its size or its scale
+    ResizeRequested {
+        monitor_id: MonitorId,
+        /// New size in physical pixels, see [`MonitorInfo::physical_size`]
+        size: UVec2,
+    },
+    MonitorPlugged {
+        id: MonitorId,
+    },
+    MonitorUnplugged {
+        id: MonitorId,
+    },
     // TODO(hack3rmann): implement approach from <https://github.com/cjacker/wl-find-cursor/blob/main/main.c>
-    CursorMoved { position: UVec2 },
+    CursorMoved {
+        position: UVec2,
+    },
+    /// The user did not use any input device for the time given to [`Wayland::notify_idle`]
+    SessionIdle,
+    /// The user is active again after [`WaylandEvent::SessionIdle`]
+    SessionResumed,
+    /// The compositor shows the surface again after [`ClientState::is_surface_hidden`]
+    /// reported it as hidden
+    SurfaceShown {
+        monitor_id: MonitorId,
+    },
 }
 
 pub type MonitorId = WlObjectId;
@@ -52,17 +82,76 @@ pub type MonitorMap<T> = BTreeMap<MonitorId, T>;
 
 #[derive(Default, Debug)]
 pub struct MonitorInfo {
+    /// Size of the surface in logical pixels
     pub size: Option<UVec2>,
     pub name: Option<Arc<str>>,
+    /// Integer scale factor reported by the output
+    pub scale: Option<i32>,
+    /// Position of the output in the compositor space in logical pixels, taken from
+    /// `zxdg_output_v1` or from `wl_output.geometry` if the compositor lacks xdg-output
+    pub position: Option<IVec2>,
+    /// Size of the output in the compositor space, [`None`] without xdg-output
+    pub logical_size: Option<UVec2>,
+    /// Scale preferred by the compositor for the surface in 120ths,
+    /// [`None`] until it is sent or if `wp_fractional_scale_v1` is not supported
+    pub preferred_scale: Option<u32>,
     pub output: WlObjectHandle<Output>,
     pub surface: WlObjectHandle<Surface>,
     pub layer_surface: WlObjectHandle<LayerSurface>,
+    pub xdg_output: Option<WlObjectHandle<XdgOutput>>,
+    /// Maps the buffer of the physical size to the logical size of the surface
+    pub viewport: Option<WlObjectHandle<Viewport>>,
+    pub fractional_scale: Option<WlObjectHandle<FractionalScale>>,
+    /// Frame callback requested by the last [`Wayland::request_frame`]
+    pub frame_callback: Option<WlObjectHandle<FrameCallback>>,
+    /// Time of the frame callback request, [`None`] once the compositor is done with it
+    pub frame_requested_at: Option<Instant>,
+    /// The compositor did not draw the surface for a while, see [`ClientState::is_surface_hidden`]
+    pub hidden: bool,
+}
+
+impl MonitorInfo {
+    /// Denominator of [`MonitorInfo::preferred_scale`]
+    pub const SCALE_DENOMINATOR: u32 = 120;
+
+    /// Size of the surface buffer, the logical size multiplied by the preferred scale
+    pub fn physical_size(&self) -> Option<UVec2> {
+        let size = self.size?;
+
+        let Some(scale) = self.preferred_scale else {
+            return Some(size);
+        };
+
+        // NOTE: the protocol asks to round halfway away from zero
+        let scale_axis =
+            |length: u32| (length * scale + Self::SCALE_DENOMINATOR / 2) / Self::SCALE_DENOMINATOR;
+
+        Some(UVec2::new(scale_axis(size.x), scale_axis(size.y)))
+    }
+
+    /// Preferred scale of the surface, `1.0` if unknown
+    pub fn scale_factor(&self) -> f64 {
+        self.preferred_scale.map_or(1.0, |scale| {
+            f64::from(scale) / f64::from(Self::SCALE_DENOMINATOR)
+        })
+    }
 }
 
 #[derive(Default, Debug, Clone, Copy)]
 pub struct Globals {
     pub compositor: WlObjectHandle<Compositor>,
     pub layer_shell: WlObjectHandle<LayerShell>,
+    pub seat: WlObjectHandle<Seat>,
+    /// `ext_idle_notifier_v1` global, [`None`] if the compositor does not support it
+    pub idle_notifier: Option<WlObjectHandle<IdleNotifier>>,
+    /// `wp_fractional_scale_manager_v1` and `wp_viewporter` globals,
+    /// [`None`] if the compositor does not support either of them
+    pub fractional_scaling: Option<(
+        WlObjectHandle<FractionalScaleManager>,
+        WlObjectHandle<Viewporter>,
+    )>,
+    /// `zxdg_output_manager_v1` global, [`None`] if the compositor does not support it
+    pub xdg_output_manager: Option<WlObjectHandle<XdgOutputManager>>,
 }
 
 pub struct ClientState {
@@ -71,6 +160,7 @@ pub struct ClientState {
     pub monitor_names: RwLock<HashMap<Arc<str>, MonitorId>>,
     pub globals: Option<Globals>,
     pub resize_requested: AtomicBool,
+    pub idle_notification: Mutex<Option<WlObjectHandle<IdleNotification>>>,
 }
 
 impl ClientState {
@@ -81,6 +171,7 @@ impl ClientState {
             monitor_names: RwLock::new(HashMap::default()),
             globals: None,
             resize_requested: AtomicBool::new(false),
+            idle_notification: Mutex::new(None),
         }
     }
 
@@ -92,6 +183,15 @@ impl ClientState {
             .and_then(|info| info.size)
     }
 
+    /// Size of the monitor surface in physical pixels, see [`MonitorInfo::physical_size`]
+    pub fn monitor_physical_size(&self, id: MonitorId) -> Option<UVec2> {
+        self.monitors
+            .read()
+            .unwrap()
+            .get(&id)
+            .and_then(MonitorInfo::physical_size)
+    }
+
     pub fn monitor_name(&self, id: MonitorId) -> Option<Arc<str>> {
         let monitors = self.monitors.read().unwrap();
         let monitor = monitors.get(&id).unwrap();
@@ -107,6 +207,26 @@ impl ClientState {
         let size = self.monitor_size(id)?;
         Some(size.x as f32 / size.y as f32)
     }
+
+    /// Checks whether the compositor has not been drawing the surface of the monitor
+    /// for longer than `timeout`, e.g. because a fullscreen window covers it
+    ///
+    /// Once reported, [`WaylandEvent::SurfaceShown`] is emitted when the surface is drawn again
+    pub fn is_surface_hidden(&self, id: MonitorId, timeout: Duration) -> bool {
+        let mut monitors = self.monitors.write().unwrap();
+
+        let Some(monitor) = monitors.get_mut(&id) else {
+            return false;
+        };
+
+        if let Some(time) = monitor.frame_requested_at
+            && time.elapsed() > timeout
+        {
+            monitor.hidden = true;
+        }
+
+        monitor.hidden
+    }
 }
 
 #[derive(Default)]
@@ -201,6 +321,222 @@ impl Dispatch for Pointer {
     }
 }
 
+/// Frame callback of the monitor surface, see [`Wayland::request_frame`]
+pub struct FrameCallback {
+    pub monitor_id: MonitorId,
+}
+
+impl HasObjectType for FrameCallback {
+    const OBJECT_TYPE: WlObjectType = WlObjectType::Callback;
+}
+
+impl Dispatch for FrameCallback {
+    type State = ClientState;
+
+    fn dispatch(
+        &mut self,
+        state: &Self::State,
+        _storage: &mut WlObjectStorage<Self::State>,
+        message: WlMessage<'_>,
+    ) {
+        let Some(WlCallbackDoneEvent { .. }) = message.as_event() else {
+            return;
+        };
+
+        let was_hidden = {
+            let mut monitors = state.monitors.write().unwrap();
+
+            let Some(monitor) = monitors.get_mut(&self.monitor_id) else {
+                return;
+            };
+
+            monitor.frame_requested_at = None;
+            mem::take(&mut monitor.hidden)
+        };
+
+        if was_hidden {
+            let mut events = state.events.lock().unwrap();
+            events
+                .emit(WaylandEvent::SurfaceShown {
+                    monitor_id: self.monitor_id,
+                })
+                .unwrap();
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct IdleNotifier;
+
+impl HasObjectType for IdleNotifier {
+    const OBJECT_TYPE: WlObjectType = WlObjectType::ExtIdleNotifierV1;
+}
+
+impl Dispatch for IdleNotifier {
+    type State = ClientState;
+    const ALLOW_EMPTY_DISPATCH: bool = true;
+}
+
+#[derive(Default)]
+pub struct IdleNotification;
+
+impl HasObjectType for IdleNotification {
+    const OBJECT_TYPE: WlObjectType = WlObjectType::ExtIdleNotificationV1;
+}
+
+impl Dispatch for IdleNotification {
+    type State = ClientState;
+
+    fn dispatch(
+        &mut self,
+        state: &Self::State,
+        _storage: &mut WlObjectStorage<Self::State>,
+        message: WlMessage<'_>,
+    ) {
+        let Some(event) = message.as_event::<ExtIdleNotificationEvent>() else {
+            return;
+        };
+
+        let event = match event {
+            ExtIdleNotificationEvent::Idled(..) => WaylandEvent::SessionIdle,
+            ExtIdleNotificationEvent::Resumed(..) => WaylandEvent::SessionResumed,
+        };
+
+        let mut events = state.events.lock().unwrap();
+        events.emit(event).unwrap();
+    }
+}
+
+#[derive(Default)]
+pub struct FractionalScaleManager;
+
+impl HasObjectType for FractionalScaleManager {
+    const OBJECT_TYPE: WlObjectType = WlObjectType::WpFractionalScaleManagerV1;
+}
+
+impl Dispatch for FractionalScaleManager {
+    type State = ClientState;
+    const ALLOW_EMPTY_DISPATCH: bool = true;
+}
+
+/// Preferred scale of the monitor surface, see [`MonitorInfo::preferred_scale`]
+pub struct FractionalScale {
+    pub monitor_id: MonitorId,
+}
+
+impl HasObjectType for FractionalScale {
+    const OBJECT_TYPE: WlObjectType = WlObjectType::WpFractionalScaleV1;
+}
+
+impl Dispatch for FractionalScale {
+    type State = ClientState;
+
+    fn dispatch(
+        &mut self,
+        state: &Self::State,
+        _storage: &mut WlObjectStorage<Self::State>,
+        message: WlMessage<'_>,
+    ) {
+        let Some(WpFractionalScalePreferredScaleEvent { scale }) = message.as_event() else {
+            return;
+        };
+
+        let mut monitors = state.monitors.write().unwrap();
+
+        let Some(monitor) = monitors.get_mut(&self.monitor_id) else {
+            return;
+        };
+
+        let prev_size = monitor.physical_size();
+        monitor.preferred_scale = Some(scale);
+
+        // NOTE: the scale is sent before the first configure,
+        // the surface is created with the right size in that case
+        if let Some(size) = monitor.physical_size()
+            && prev_size != Some(size)
+        {
+            let mut events = state.events.lock().unwrap();
+            events
+                .emit(WaylandEvent::ResizeRequested {
+                    monitor_id: self.monitor_id,
+                    size,
+                })
+                .unwrap();
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Viewporter;
+
+impl HasObjectType for Viewporter {
+    const OBJECT_TYPE: WlObjectType = WlObjectType::WpViewporter;
+}
+
+impl Dispatch for Viewporter {
+    type State = ClientState;
+    const ALLOW_EMPTY_DISPATCH: bool = true;
+}
+
+#[derive(Default)]
+pub struct Viewport;
+
+impl HasObjectType for Viewport {
+    const OBJECT_TYPE: WlObjectType = WlObjectType::WpViewport;
+}
+
+impl Dispatch for Viewport {
+    type State = ClientState;
+    const ALLOW_EMPTY_DISPATCH: bool = true;
+}
+
+#[derive(Default)]
+pub struct XdgOutputManager;
+
+impl HasObjectType for XdgOutputManager {
+    const OBJECT_TYPE: WlObjectType = WlObjectType::ZxdgOutputManagerV1;
+}
+
+impl Dispatch for XdgOutputManager {
+    type State = ClientState;
+    const ALLOW_EMPTY_DISPATCH: bool = true;
+}
+
+/// Logical position and size of the monitor output, see [`MonitorInfo::position`]
+pub struct XdgOutput {
+    pub monitor_id: MonitorId,
+}
+
+impl HasObjectType for XdgOutput {
+    const OBJECT_TYPE: WlObjectType = WlObjectType::ZxdgOutputV1;
+}
+
+impl Dispatch for XdgOutput {
+    type State = ClientState;
+
+    fn dispatch(
+        &mut self,
+        state: &Self::State,
+        _storage: &mut WlObjectStorage<Self::State>,
+        message: WlMessage<'_>,
+    ) {
+        let mut monitors = state.monitors.write().unwrap();
+
+        let Some(monitor) = monitors.get_mut(&self.monitor_id) else {
+            return;
+        };
+
+        if let Some(ZxdgOutputLogicalPositionEvent { x, y }) = message.as_event() {
+            monitor.position = Some(IVec2::new(x, y));
+            return;
+        }
+
+        if let Some(ZxdgOutputLogicalSizeEvent { width, height }) = message.as_event() {
+            monitor.logical_size = Some(IVec2::new(width, height).max(IVec2::ZERO).as_uvec2());
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct LayerShell;
 
@@ -262,7 +598,7 @@ impl Dispatch for LayerSurface {
             Release,
         );
 
-        {
+        let viewport = {
             let mut monitors = state.monitors.write().unwrap();
             let monitor = monitors.get_mut(&self.monitor_id).unwrap();
 
@@ -270,13 +606,15 @@ impl Dispatch for LayerSurface {
             // and size is changed indeed
             match monitor.size {
                 Some(prev_size) if prev_size != size => {
+                    monitor.size = Some(size);
+
                     state
                         .events
                         .lock()
                         .unwrap()
                         .emit(WaylandEvent::ResizeRequested {
                             monitor_id: self.monitor_id,
-                            size,
+                            size: monitor.physical_size().unwrap(),
                         })
                         .unwrap();
                 }
@@ -292,7 +630,8 @@ impl Dispatch for LayerSurface {
             }
 
             monitor.size = Some(size);
-        }
+            monitor.viewport
+        };
 
         let mut buf = WlStackMessageBuffer::new();
 
@@ -304,6 +643,17 @@ impl Dispatch for LayerSurface {
 
         let mut storage = Pin::new(storage);
 
+        if let Some(viewport) = viewport {
+            viewport.request(
+                &mut buf,
+                &storage.as_ref(),
+                WpViewportSetDestinationRequest {
+                    width: width.cast_signed(),
+                    height: height.cast_signed(),
+                },
+            );
+        }
+
         let region: WlObjectHandle<Region> = self.compositor.create_object(
             &mut buf,
             storage.as_mut(),
@@ -372,6 +722,26 @@ impl Dispatch for Output {
         _storage: &mut WlObjectStorage<Self::State>,
         message: WlMessage<'_>,
     ) {
+        if let Some(WlOutputScaleEvent { factor }) = message.as_event() {
+            let mut monitors = state.monitors.write().unwrap();
+            let monitor = monitors.get_mut(&self.monitor_id).unwrap();
+            monitor.scale = Some(factor);
+            return;
+        }
+
+        if let Some(WlOutputGeometryEvent { x, y, .. }) = message.as_event() {
+            let mut monitors = state.monitors.write().unwrap();
+            let monitor = monitors.get_mut(&self.monitor_id).unwrap();
+
+            // NOTE: wlroots compositors always send 0,0 here, the position from
+            // xdg-output is the only reliable one if it is available
+            if monitor.xdg_output.is_none() {
+                monitor.position = Some(IVec2::new(x, y));
+            }
+
+            return;
+        }
+
         let Some(WlOutputNameEvent { name }) = message.as_event() else {
             return;
         };
@@ -469,8 +839,45 @@ pub fn handle_output(
         WlSurfaceSetBufferScaleRequest { scale: 1 },
     );
 
+    // NOTE: the buffer is kept in physical pixels with the buffer scale of 1
+    // and the viewport maps it onto the logical size of the surface
+    let (viewport, fractional_scale) = match globals.fractional_scaling {
+        Some((fractional_scale_manager, viewporter)) => {
+            let viewport: WlObjectHandle<Viewport> = viewporter.create_object(
+                &mut buf,
+                storage.as_mut(),
+                WpViewporterGetViewportRequest {
+                    surface: surface.id(),
+                },
+            );
+
+            let fractional_scale = fractional_scale_manager.create_object_with(
+                &mut buf,
+                storage.as_mut(),
+                WpFractionalScaleManagerGetFractionalScaleRequest {
+                    surface: surface.id(),
+                },
+                move |_| FractionalScale { monitor_id },
+            );
+
+            (Some(viewport), Some(fractional_scale))
+        }
+        None => (None, None),
+    };
+
     surface.request(&mut buf, &storage, WlSurfaceCommitRequest);
 
+    let xdg_output = globals.xdg_output_manager.map(|xdg_output_manager| {
+        xdg_output_manager.create_object_with(
+            &mut buf,
+            storage.as_mut(),
+            ZxdgOutputManagerGetXdgOutputRequest {
+                output: output.id(),
+            },
+            move |_| XdgOutput { monitor_id },
+        )
+    });
+
     let mut monitors = state.monitors.write().unwrap();
 
     monitors.insert(
@@ -481,6 +888,16 @@ pub fn handle_output(
             layer_surface,
             size: None,
             name: None,
+            scale: None,
+            position: None,
+            logical_size: None,
+            xdg_output,
+            preferred_scale: None,
+            viewport,
+            fractional_scale,
+            frame_callback: None,
+            frame_requested_at: None,
+            hidden: false,
         },
     );
 }
@@ -528,6 +945,27 @@ pub(crate) fn handle_global_remove(
     storage.release(info.surface).unwrap();
     storage.release(info.layer_surface).unwrap();
 
+    let mut buf = WlStackMessageBuffer::new();
+
+    if let Some(viewport) = info.viewport {
+        viewport.request(&mut buf, storage, WpViewportDestroyRequest);
+        storage.release(viewport).unwrap();
+    }
+
+    if let Some(fractional_scale) = info.fractional_scale {
+        fractional_scale.request(&mut buf, storage, WpFractionalScaleDestroyRequest);
+        storage.release(fractional_scale).unwrap();
+    }
+
+    if let Some(callback) = info.frame_callback {
+        storage.release(callback).unwrap();
+    }
+
+    if let Some(xdg_output) = info.xdg_output {
+        xdg_output.request(&mut buf, storage, ZxdgOutputDestroyRequest);
+        storage.release(xdg_output).unwrap();
+    }
+
     {
         let mut events = state.events.lock().unwrap();
         events
@@ -610,11 +1048,39 @@ impl Wayland {
             .bind::<LayerShell>(&mut buf, storage.as_mut())
             .unwrap();
 
-        let _seat = registry.bind::<Seat>(&mut buf, storage.as_mut()).unwrap();
+        let seat = registry.bind::<Seat>(&mut buf, storage.as_mut()).unwrap();
+
+        let idle_notifier = registry.bind::<IdleNotifier>(&mut buf, storage.as_mut());
+
+        // NOTE: either global is useless without the other one, so neither is bound
+        // unless both are advertised
+        let supports_fractional_scaling = [
+            WlObjectType::WpFractionalScaleManagerV1,
+            WlObjectType::WpViewporter,
+        ]
+        .into_iter()
+        .all(|ty| storage.object_data(registry).count_of(ty) != 0);
+
+        let fractional_scaling = supports_fractional_scaling.then(|| {
+            (
+                registry
+                    .bind::<FractionalScaleManager>(&mut buf, storage.as_mut())
+                    .unwrap(),
+                registry
+                    .bind::<Viewporter>(&mut buf, storage.as_mut())
+                    .unwrap(),
+            )
+        });
+
+        let xdg_output_manager = registry.bind::<XdgOutputManager>(&mut buf, storage.as_mut());
 
         client_state.globals = Some(Globals {
             compositor,
             layer_shell,
+            seat,
+            idle_notifier,
+            fractional_scaling,
+            xdg_output_manager,
         });
 
         let n_outputs = storage.object_data(registry).count_of(WlObjectType::Output);
@@ -639,4 +1105,82 @@ impl Wayland {
     pub fn raw_display_handle(&self) -> RawDisplayHandle {
         self.display.display_handle().unwrap().as_raw()
     }
+
+    /// Requests a frame callback to be sent with the next commit of the monitor surface.
+    /// Does nothing while the previous callback is not done yet
+    ///
+    /// Should be called before presenting the frame
+    pub fn request_frame(&self, monitor_id: MonitorId) {
+        let mut main_queue = self.main_queue.write().unwrap();
+        let mut storage = main_queue.as_mut().storage_mut();
+        let mut monitors = self.client_state.monitors.write().unwrap();
+
+        let Some(monitor) = monitors.get_mut(&monitor_id) else {
+            return;
+        };
+
+        if monitor.frame_requested_at.is_some() {
+            return;
+        }
+
+        // NOTE: the callback can not be released in its own dispatch
+        if let Some(callback) = monitor.frame_callback.take() {
+            storage.as_mut().release(callback).unwrap();
+        }
+
+        let mut buf = WlStackMessageBuffer::new();
+
+        let callback = monitor.surface.create_object_with(
+            &mut buf,
+            storage.as_mut(),
+            WlSurfaceFrameRequest,
+            |_| FrameCallback { monitor_id },
+        );
+
+        monitor.frame_callback = Some(callback);
+        monitor.frame_requested_at = Some(Instant::now());
+    }
+
+    /// Emits [`WaylandEvent::SessionIdle`] once the user is idle for `timeout`
+    /// and [`WaylandEvent::SessionResumed`] once the user is back.
+    /// Replaces the previously set timeout, [`None`] stops the notifications
+    ///
+    /// Does nothing if the compositor does not support `ext_idle_notifier_v1`
+    pub fn notify_idle(&self, timeout: Option<Duration>) {
+        let Some(globals) = self.client_state.globals else {
+            return;
+        };
+
+        let Some(idle_notifier) = globals.idle_notifier else {
+            return;
+        };
+
+        let mut main_queue = self.main_queue.write().unwrap();
+        let mut storage = main_queue.as_mut().storage_mut();
+        let mut notification = self.client_state.idle_notification.lock().unwrap();
+        let mut buf = WlStackMessageBuffer::new();
+
+        if let Some(previous) = notification.take() {
+            previous.request(
+                &mut buf,
+                &storage.as_ref(),
+                ExtIdleNotificationDestroyRequest,
+            );
+
+            storage.as_mut().release(previous).unwrap();
+        }
+
+        let Some(timeout) = timeout else {
+            return;
+        };
+
+        *notification = Some(idle_notifier.create_object(
+            &mut buf,
+            storage.as_mut(),
+            ExtIdleNotifierGetIdleNotificationRequest {
+                timeout: u32::try_from(timeout.as_millis()).unwrap_or(u32::MAX),
+                seat: globals.seat.id(),
+            },
+        ));
+    }
 }
@@ -0,0 +1,13 @@
+@group(0) @binding(0) var image: texture_2d<f32>;
+@group(0) @binding(2) var image_sampler: sampler;
+
+struct Output {
+    // only the depth is written, there is no color at the location 0
+    @builtin(frag_depth) depth: f32,
+}
+
+@fragment
+fn main(@location(0) position: vec2<f32>) -> Output {
+    let color = textureSample(image, image_sampler, 0.5 * position + 0.5);
+    return Output(color.a);
+}
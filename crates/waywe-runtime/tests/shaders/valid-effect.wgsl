@@ -0,0 +1,19 @@
+@group(0) @binding(0) var image: texture_2d<f32>;
+@group(0) @binding(2) var image_sampler: sampler;
+
+struct Uniforms {
+    time: f32,
+    progress: f32,
+    resolution: vec2<f32>,
+    cursor: vec2<f32>,
+}
+
+@group(0) @binding(3) var<uniform> uniforms: Uniforms;
+
+@fragment
+fn main(@location(0) position: vec2<f32>) -> @location(0) vec4<f32> {
+    let uv = vec2(0.5 * position.x + 0.5, 0.5 - 0.5 * position.y);
+    let color = textureSample(image, image_sampler, uv);
+
+    return vec4(color.rgb * (0.75 + 0.25 * sin(uniforms.time)), color.a);
+}
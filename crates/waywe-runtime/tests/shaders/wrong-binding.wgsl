@@ -0,0 +1,8 @@
+@group(0) @binding(0) var image: texture_2d<f32>;
+// the sampler is expected at the binding 2
+@group(0) @binding(1) var image_sampler: sampler;
+
+@fragment
+fn main(@location(0) position: vec2<f32>) -> @location(0) vec4<f32> {
+    return textureSample(image, image_sampler, 0.5 * position + 0.5);
+}
@@ -0,0 +1,332 @@
+//! Sprite-sheet animations.
+//!
+//! This module plays animated Wallpaper Engine `.tex` textures (gif containers)
+//! by switching the [`ImageMaterial`] of a mesh at the recorded frame durations.
+//!
+//! # Core Types
+//!
+//! - [`AnimatedImage`]: Decoded animation frames
+//! - [`SpriteAnimation`]: Component playing an animation on a mesh
+//!
+//! # Systems
+//!
+//! - [`advance_sprite_animations`]: Switches animation frames using [`Time`]
+//!
+//! # Plugins
+//!
+//! - [`AnimationPlugin`]: Adds animation functionality to a wallpaper
+
+use crate::{
+    Update,
+    asset_server::{AssetHandle, AssetLoadError, AssetServerLoadPlugin, Load},
+    assets::{Asset, Assets, AssetsPlugin},
+    image::{Image, ImageLoadError, ImageMaterial},
+    mesh::MeshMaterial,
+    plugin::Plugin,
+    time::Time,
+    wallpaper::Wallpaper,
+};
+use bevy_ecs::prelude::*;
+use extractor::texture::TexReader;
+use glam::UVec2;
+use smallvec::SmallVec;
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+    time::Duration,
+};
+
+/// Plugin for sprite-sheet animations.
+///
+/// Adds the [`AnimatedImage`] asset and the system advancing [`SpriteAnimation`]s.
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, wallpaper: &mut Wallpaper) {
+        wallpaper.add_plugins((
+            AssetsPlugin::<AnimatedImage>::new(),
+            AssetServerLoadPlugin::<AnimatedImage>::new(),
+        ));
+
+        wallpaper
+            .main
+            .add_systems(Update, advance_sprite_animations);
+    }
+}
+
+/// Single frame of an [`AnimatedImage`].
+#[derive(Debug, Clone)]
+pub struct AnimatedImageFrame {
+    /// Frame pixels.
+    pub image: Image,
+    /// Time for which the frame is shown.
+    pub duration: Duration,
+}
+
+/// Animation asset containing every frame cut from the sprite sheet.
+#[derive(Debug, Clone, Default)]
+pub struct AnimatedImage {
+    /// Frames in playback order.
+    pub frames: Vec<AnimatedImageFrame>,
+}
+
+impl AnimatedImage {
+    /// Decode a `.tex` texture.
+    ///
+    /// Textures without a gif container result in a single frame shown forever.
+    pub fn from_tex(src: &mut impl Read) -> Result<Self, ImageLoadError> {
+        let mut reader = TexReader::new(src)
+            .read_header()?
+            .read_image_container_meta()?
+            .read_images()?;
+
+        let header = reader.header();
+        let images = reader.take_images().unwrap_or_default();
+
+        if !reader.contains_gif() {
+            let image = images.into_iter().next().ok_or(ImageLoadError::NoImage)?;
+            let size = UVec2::new(header.image_width as u32, header.image_height as u32);
+
+            return Ok(Self {
+                frames: vec![AnimatedImageFrame {
+                    image: Image::from_tex_image(image, Some(size))?,
+                    duration: Duration::MAX,
+                }],
+            });
+        }
+
+        let sheets = images
+            .into_iter()
+            .map(|image| Image::from_tex_image(image, None)?.into_rgba8())
+            .collect::<Result<Vec<_>, ImageLoadError>>()?;
+
+        let frames_meta = reader
+            .read_gif_container_meta()?
+            .read_gif_frames_meta()?
+            .take_gif_frames_meta()
+            .unwrap_or_default();
+
+        let frames = frames_meta
+            .iter()
+            .map(|meta| {
+                let sheet = usize::try_from(meta.image_id)
+                    .ok()
+                    .and_then(|id| sheets.get(id))
+                    .ok_or(ImageLoadError::NoImage)?;
+
+                Ok(AnimatedImageFrame {
+                    image: Image::from(meta.rect().cut(sheet).ok_or(ImageLoadError::NoImage)?),
+                    duration: meta.duration(),
+                })
+            })
+            .collect::<Result<Vec<_>, ImageLoadError>>()?;
+
+        if frames.is_empty() {
+            return Err(ImageLoadError::NoImage);
+        }
+
+        Ok(Self { frames })
+    }
+
+    /// Load an animation from a `.tex` texture.
+    pub fn open(path: &Path) -> Result<Self, ImageLoadError> {
+        let mut file = BufReader::new(File::open(path)?);
+        Self::from_tex(&mut file)
+    }
+}
+
+impl Asset for AnimatedImage {}
+
+impl Load for AnimatedImage {
+    fn load(path: &Path) -> Self
+    where
+        Self: Sized,
+    {
+        Self::open(path).unwrap()
+    }
+
+    fn load_from_bytes(_path: &Path, mut bytes: &[u8]) -> Result<Self, AssetLoadError>
+    where
+        Self: Sized,
+    {
+        Ok(Self::from_tex(&mut bytes)?)
+    }
+}
+
+/// Frame of a [`SpriteAnimation`] ready for rendering.
+#[derive(Debug, Clone)]
+pub struct SpriteAnimationFrame {
+    /// Material displaying the frame.
+    pub material: AssetHandle<ImageMaterial>,
+    /// Time for which the frame is shown.
+    pub duration: Duration,
+}
+
+/// Component that plays an animation by switching [`MeshMaterial`] frames.
+///
+/// The entity is expected to be spawned with [`SpriteAnimation::current_material`]
+/// as its [`MeshMaterial`].
+#[derive(Component, Debug, Clone)]
+pub struct SpriteAnimation {
+    /// Frames in playback order.
+    pub frames: SmallVec<[SpriteAnimationFrame; 8]>,
+    /// Index of the frame being shown.
+    pub current: usize,
+    /// Time the current frame has been shown for.
+    pub elapsed: Duration,
+}
+
+impl SpriteAnimation {
+    /// Create an animation, adding every frame as an image asset.
+    pub fn new(
+        animation: AnimatedImage,
+        images: &mut Assets<Image>,
+        materials: &mut Assets<ImageMaterial>,
+    ) -> Self {
+        let frames = animation
+            .frames
+            .into_iter()
+            .map(|frame| SpriteAnimationFrame {
+                material: materials.add(ImageMaterial {
+                    image: images.add(frame.image),
+                }),
+                duration: frame.duration,
+            })
+            .collect();
+
+        Self {
+            frames,
+            current: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Material of the frame being shown.
+    pub fn current_material(&self) -> Option<&AssetHandle<ImageMaterial>> {
+        self.frames.get(self.current).map(|frame| &frame.material)
+    }
+
+    /// Advance the animation by `delta`.
+    ///
+    /// Returns `true` if the shown frame has changed.
+    pub fn advance(&mut self, delta: Duration) -> bool {
+        let total = self
+            .frames
+            .iter()
+            .map(|frame| frame.duration)
+            .fold(Duration::ZERO, Duration::saturating_add);
+
+        if self.frames.len() <= 1 || total.is_zero() {
+            return false;
+        }
+
+        let prev = self.current;
+
+        // Whole loops end up on the same frame, so they are skipped at once
+        let elapsed = self.elapsed.saturating_add(delta).as_nanos() % total.as_nanos();
+        self.elapsed = Duration::from_nanos(elapsed as u64);
+
+        while self.elapsed >= self.frames[self.current].duration {
+            self.elapsed -= self.frames[self.current].duration;
+            self.current = (self.current + 1) % self.frames.len();
+        }
+
+        prev != self.current
+    }
+}
+
+/// System that switches animation frames.
+///
+/// This system advances every [`SpriteAnimation`] by the frame delta time and
+/// updates the [`MeshMaterial`] of the entity when the frame changes.
+pub fn advance_sprite_animations(
+    mut animations: Query<(&mut SpriteAnimation, &mut MeshMaterial<ImageMaterial>)>,
+    time: Res<Time>,
+) {
+    for (mut animation, mut material) in &mut animations {
+        if !animation.advance(time.delta) {
+            continue;
+        }
+
+        if let Some(frame) = animation.current_material() {
+            material.0 = frame.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_server::AssetServer;
+
+    fn sprite_animation(frame_millis: &[u64]) -> SpriteAnimation {
+        let server = AssetServer::default();
+        let mut images = server.make_assets::<Image>();
+        let mut materials = server.make_assets::<ImageMaterial>();
+
+        let frames = frame_millis
+            .iter()
+            .map(|&millis| AnimatedImageFrame {
+                image: Image::new_white_1x1(),
+                duration: Duration::from_millis(millis),
+            })
+            .collect();
+
+        SpriteAnimation::new(AnimatedImage { frames }, &mut images, &mut materials)
+    }
+
+    #[test]
+    fn advance_within_frame() {
+        let mut animation = sprite_animation(&[100, 100]);
+
+        assert!(!animation.advance(Duration::from_millis(60)));
+        assert_eq!(animation.current, 0);
+        assert_eq!(animation.elapsed, Duration::from_millis(60));
+
+        assert!(animation.advance(Duration::from_millis(60)));
+        assert_eq!(animation.current, 1);
+        assert_eq!(animation.elapsed, Duration::from_millis(20));
+    }
+
+    #[test]
+    fn advance_wraps_around() {
+        let mut animation = sprite_animation(&[100, 50, 30]);
+
+        assert!(animation.advance(Duration::from_millis(150)));
+        assert_eq!(animation.current, 2);
+
+        assert!(animation.advance(Duration::from_millis(40)));
+        assert_eq!(animation.current, 0);
+        assert_eq!(animation.elapsed, Duration::from_millis(10));
+    }
+
+    #[test]
+    fn advance_spans_several_frames() {
+        let mut animation = sprite_animation(&[100, 50, 30]);
+
+        // two whole loops of 180ms, then 100 + 20 into the second frame
+        assert!(animation.advance(Duration::from_millis(2 * 180 + 120)));
+        assert_eq!(animation.current, 1);
+        assert_eq!(animation.elapsed, Duration::from_millis(20));
+
+        // whole loops land on the same frame
+        assert!(!animation.advance(Duration::from_secs(180)));
+        assert_eq!(animation.current, 1);
+        assert_eq!(animation.elapsed, Duration::from_millis(20));
+    }
+
+    #[test]
+    fn advance_without_enough_frames() {
+        let mut animation = sprite_animation(&[]);
+
+        assert!(!animation.advance(Duration::from_secs(1)));
+        assert_eq!(animation.current, 0);
+        assert!(animation.current_material().is_none());
+
+        let mut animation = sprite_animation(&[100]);
+
+        assert!(!animation.advance(Duration::from_secs(1)));
+        assert!(animation.current_material().is_some());
+    }
+}
@@ -2,6 +2,7 @@ use super::wallpaper::Wallpaper;
 use crate::{
     PostStartup, PreUpdate,
     assets::{Asset, Assets, AssetsExtract},
+    image::ImageLoadError,
     plugin::Plugin,
     render::SceneExtract,
 };
@@ -11,6 +12,7 @@ use bevy_ecs::{
 };
 use box_into_inner::IntoInner;
 use crossbeam::channel::Sender;
+use extractor::package::{MappedPackageArchive, PackageArchive, PackageExtractError};
 use smallvec::SmallVec;
 use std::{
     any::{Any, TypeId},
@@ -25,6 +27,8 @@ use std::{
     },
     thread::{self, JoinHandle},
 };
+use thiserror::Error;
+use tracing::warn;
 
 pub struct AssetServerPlugin;
 
@@ -183,6 +187,30 @@ pub struct AssetServerInner {
     loaded_assets: Mutex<HashMap<AssetKey, DynamicAsset>>,
     jobs: Mutex<HashMap<AssetKey, JoinHandle<DynamicAsset>>>,
     drop_senders: RwLock<HashMap<TypeId, Sender<AssetDropEvent>>>,
+    packages: RwLock<Vec<MountedPackage>>,
+}
+
+/// `scene.pkg` mounted into the [`AssetServer`] virtual filesystem.
+#[derive(Debug, Clone)]
+pub struct MountedPackage {
+    /// Directory the package contents appear in.
+    pub mount_point: PathBuf,
+    pub archive: Arc<MappedPackageArchive>,
+}
+
+impl MountedPackage {
+    /// Name of the package entry for the `path`, if the entry exists.
+    pub fn entry_name(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.mount_point).ok()?;
+
+        let name = relative
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect::<Option<Vec<_>>>()?
+            .join("/");
+
+        self.archive.contains(&name).then_some(name)
+    }
 }
 
 impl AssetServerInner {
@@ -194,9 +222,44 @@ impl AssetServerInner {
             loaded_assets: Mutex::default(),
             jobs: Mutex::default(),
             drop_senders: RwLock::default(),
+            packages: RwLock::default(),
         }
     }
 
+    /// Mounts `scene.pkg` at `mount_point`, so that assets under it are loaded from the package.
+    ///
+    /// Paths missing in the package are still loaded from the filesystem.
+    /// Packages mounted later take precedence.
+    pub fn mount_package(
+        &self,
+        mount_point: impl Into<PathBuf>,
+        package: &Path,
+    ) -> Result<(), PackageExtractError> {
+        let archive = PackageArchive::map(package)?;
+        self.mount(mount_point, Arc::new(archive));
+        Ok(())
+    }
+
+    /// Mounts an already opened package at `mount_point`, see [`AssetServerInner::mount_package`].
+    pub fn mount(&self, mount_point: impl Into<PathBuf>, archive: Arc<MappedPackageArchive>) {
+        let mut packages = self.packages.write().unwrap();
+
+        packages.push(MountedPackage {
+            mount_point: mount_point.into(),
+            archive,
+        });
+    }
+
+    /// Finds the package containing the `path` and the name of the entry in it.
+    pub fn resolve(&self, path: &Path) -> Option<(Arc<MappedPackageArchive>, String)> {
+        let packages = self.packages.read().unwrap();
+
+        packages.iter().rev().find_map(|package| {
+            let name = package.entry_name(path)?;
+            Some((Arc::clone(&package.archive), name))
+        })
+    }
+
     pub fn wait_all_jobs(&self) {
         let mut jobs = self.jobs.lock().unwrap();
         let mut assets = self.loaded_assets.lock().unwrap();
@@ -238,6 +301,7 @@ impl AssetServerInner {
     pub fn load<A: Asset + Load>(&self, path: impl Into<PathBuf>) -> AssetHandle<A> {
         let path = path.into();
         let id = self.id_generator.next_id();
+        let package = self.resolve(&path);
 
         {
             let mut jobs = self.jobs.lock().unwrap();
@@ -247,9 +311,29 @@ impl AssetServerInner {
                     path: path.clone(),
                     type_id: TypeId::of::<A>(),
                 },
-                thread::spawn(move || DynamicAsset {
-                    id,
-                    data: Box::new(A::load(&path)),
+                thread::spawn(move || {
+                    let packaged = package.map(|(archive, name)| {
+                        let bytes = archive.get(&name).ok_or(AssetLoadError::OutOfBounds)?;
+                        A::load_from_bytes(&path, bytes)
+                    });
+
+                    let asset = match packaged {
+                        Some(Ok(asset)) => asset,
+                        Some(Err(error)) => {
+                            warn!(
+                                ?path,
+                                %error,
+                                "failed to load the packaged asset, trying the filesystem"
+                            );
+                            A::load(&path)
+                        }
+                        None => A::load(&path),
+                    };
+
+                    DynamicAsset {
+                        id,
+                        data: Box::new(asset),
+                    }
                 }),
             );
         }
@@ -441,4 +525,135 @@ pub trait Load: Any + Send {
     fn load(path: &Path) -> Self
     where
         Self: Sized;
+
+    /// Load the asset from the contents of a file in a mounted package.
+    ///
+    /// `path` is the virtual path of the file, it may be used to guess the format.
+    /// The contents are borrowed from the mapped package. By default assets
+    /// can not be loaded from memory, so the file is loaded from the filesystem.
+    fn load_from_bytes(_path: &Path, _bytes: &[u8]) -> Result<Self, AssetLoadError>
+    where
+        Self: Sized,
+    {
+        Err(AssetLoadError::Unsupported)
+    }
+}
+
+/// Error that may occur while loading an asset from a mounted package.
+#[derive(Debug, Error)]
+pub enum AssetLoadError {
+    #[error("the asset can only be loaded from the filesystem")]
+    Unsupported,
+    #[error("package entry lies outside of the package")]
+    OutOfBounds,
+    #[error(transparent)]
+    Image(#[from] ImageLoadError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::{Image, ImageData};
+    use dxt::DxtFormat;
+    use extractor::package::PackageWriter;
+    use std::fs;
+    use tempfile::NamedTempFile;
+
+    /// Solid red `8x8` texture with the full `DXT5` mipmap chain
+    const DXT5_TEXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/red-8x8-dxt5.tex");
+
+    fn load_image(server: &AssetServer, path: impl Into<PathBuf>) -> Image {
+        let mut images = server.make_assets::<Image>();
+        server.register_assets(&images);
+
+        let handle = server.load::<Image>(path);
+        server.wait_all_jobs();
+        server.populate_assets(&mut images);
+
+        images.get(handle.id()).cloned().unwrap()
+    }
+
+    #[test]
+    fn load_dxt5_texture() {
+        let server = AssetServer::default();
+        let image = load_image(&server, DXT5_TEXTURE);
+
+        assert_eq!(image.size(), glam::UVec2::new(8, 8));
+
+        let ImageData::Dxt(mipmaps) = &image.data else {
+            panic!("dxt texture is decompressed");
+        };
+
+        let sizes = mipmaps
+            .iter()
+            .map(|mipmap| (mipmap.width, mipmap.height))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [(8, 8), (4, 4), (2, 2), (1, 1)]);
+        assert!(
+            mipmaps
+                .iter()
+                .all(|mipmap| mipmap.format == DxtFormat::Dxt5)
+        );
+
+        let pixels = image.into_rgba8().unwrap();
+        assert!(pixels.pixels().all(|pixel| pixel.0 == [255, 0, 0, 255]));
+    }
+
+    fn mount_files(
+        server: &AssetServer,
+        mount_point: &str,
+        files: &[(&str, &[u8])],
+    ) -> NamedTempFile {
+        let mut writer = PackageWriter::new();
+
+        for &(name, data) in files {
+            writer.add_file(name, data.to_vec());
+        }
+
+        let mut package = NamedTempFile::new().unwrap();
+        writer.write(&mut package).unwrap();
+        server.mount_package(mount_point, package.path()).unwrap();
+
+        package
+    }
+
+    #[test]
+    fn resolve_package_paths() {
+        let server = AssetServer::default();
+        let _project = mount_files(
+            &server,
+            "/project",
+            &[("materials/logo.tex", b"project"), ("scene.json", b"{}")],
+        );
+        let _materials = mount_files(&server, "/project/materials", &[("logo.tex", b"materials")]);
+
+        // packages mounted later take precedence
+        let (archive, name) = server
+            .resolve(Path::new("/project/materials/logo.tex"))
+            .unwrap();
+        assert_eq!(name, "logo.tex");
+        assert_eq!(archive.get(&name), Some(&b"materials"[..]));
+
+        let (archive, name) = server.resolve(Path::new("/project/scene.json")).unwrap();
+        assert_eq!(name, "scene.json");
+        assert_eq!(archive.get(&name), Some(&b"{}"[..]));
+
+        assert!(server.resolve(Path::new("/project/missing.json")).is_none());
+        assert!(server.resolve(Path::new("/other/scene.json")).is_none());
+        assert!(server.resolve(Path::new("/project")).is_none());
+    }
+
+    #[test]
+    fn load_dxt5_texture_from_package() {
+        let texture = fs::read(DXT5_TEXTURE).unwrap();
+
+        let server = AssetServer::default();
+        let _package = mount_files(&server, "/project", &[("materials/red.tex", &texture)]);
+
+        // the path exists only in the package
+        let image = load_image(&server, "/project/materials/red.tex");
+
+        assert_eq!(image.size(), glam::UVec2::new(8, 8));
+        assert!(matches!(image.data, ImageData::Dxt(_)));
+    }
 }
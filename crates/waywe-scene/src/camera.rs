@@ -1 +1,76 @@
+//! Camera used to project the scene onto the monitor.
+//!
+//! The world is laid out so that the horizontal axis spans `-1..1` and the
+//! vertical axis spans `-aspect_ratio..aspect_ratio` for a [`Camera`] with
+//! zoom of `1`.
+//!
+//! # Core Types
+//!
+//! - [`Camera`]: Orthographic camera resource
+//!
+//! # Plugins
+//!
+//! - [`CameraPlugin`]: Adds the camera to a wallpaper
 
+use crate::{extract::Extract, plugin::Plugin, render::SceneExtract, wallpaper::Wallpaper};
+use bevy_ecs::prelude::*;
+use glam::{Mat4, Vec2};
+
+/// Plugin for camera functionality.
+///
+/// Inserts [`Camera`] into both worlds and keeps the render one in sync.
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, wallpaper: &mut Wallpaper) {
+        wallpaper.main.init_resource::<Camera>();
+
+        wallpaper
+            .render
+            .init_resource::<Camera>()
+            .add_systems(SceneExtract, extract_camera);
+    }
+}
+
+/// Orthographic camera resource.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    /// Point of the world in the center of the screen.
+    pub translation: Vec2,
+    /// Zoom factor, values greater than `1` zoom in.
+    pub zoom: f32,
+}
+
+impl Camera {
+    /// Camera which shows `-1..1` horizontally.
+    pub const DEFAULT: Self = Self {
+        translation: Vec2::ZERO,
+        zoom: 1.0,
+    };
+
+    /// Computes the view-projection matrix for the given monitor aspect ratio (height/width).
+    pub fn view(self, aspect_ratio: f32) -> Mat4 {
+        let half_width = 1.0 / self.zoom;
+        let half_height = half_width * aspect_ratio;
+
+        Mat4::orthographic_rh(
+            -half_width,
+            half_width,
+            -half_height,
+            half_height,
+            -10.0,
+            10.0,
+        ) * Mat4::from_translation(-self.translation.extend(0.0))
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// System to copy the main world [`Camera`] into the render world.
+pub fn extract_camera(camera: Extract<Res<Camera>>, mut render_camera: ResMut<Camera>) {
+    *render_camera = **camera;
+}
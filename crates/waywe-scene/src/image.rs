@@ -5,7 +5,10 @@
 //!
 //! # Components
 //!
-//! - [`Image`]: Raw image data
+//! - [`Image`]: Raw image data, loaded from ordinary image files or
+//!   Wallpaper Engine `.tex` textures. `DXT` compressed textures are kept
+//!   compressed and uploaded as `BC` textures if the GPU supports them,
+//!   their padding is cut off by scaling texture coordinates
 //! - [`ImageMaterial`]: Material that displays an image
 //! - [`RenderImage`]: GPU-ready image data
 //!
@@ -15,7 +18,7 @@
 
 use super::wallpaper::Wallpaper;
 use crate::{
-    asset_server::{AssetHandle, AssetServerLoadPlugin, Load},
+    asset_server::{AssetHandle, AssetLoadError, AssetServerLoadPlugin, Load},
     assets::{
         Asset, Assets, AssetsExtract, AssetsPlugin, RefAssets, RefAssetsDependencyPlugin,
         RenderAsset, RenderAssetExtractError, RenderAssets, RenderAssetsPlugin,
@@ -29,8 +32,16 @@ use bevy_ecs::{
     prelude::*,
     system::{StaticSystemParam, SystemParamItem, lifetimeless::SRes},
 };
-use derive_more::{Deref, DerefMut};
-use std::path::Path;
+use dxt::DxtFormat;
+use extractor::texture::{DxtTexMipmap, TexExtractError, TexImage, TexReader};
+use glam::{UVec2, Vec2};
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+use thiserror::Error;
+use tracing::debug;
 use waywe_runtime::{gpu::Wgpu, shaders::ShaderDescriptor};
 use wgpu::util::DeviceExt;
 
@@ -62,10 +73,21 @@ impl Plugin for ImagePlugin {
 }
 
 /// Image asset containing raw pixel data.
-#[derive(Debug, Deref, DerefMut)]
+#[derive(Debug, Clone)]
 pub struct Image {
     /// The underlying image data.
-    pub image: image::RgbaImage,
+    pub data: ImageData,
+    /// Visible part of a padded texture starting at the top left corner.
+    pub crop: Option<UVec2>,
+}
+
+/// Pixel data of an [`Image`].
+#[derive(Debug, Clone)]
+pub enum ImageData {
+    /// Uncompressed pixels.
+    Rgba8(image::RgbaImage),
+    /// `DXT` compressed mipmaps, from the largest to the smallest.
+    Dxt(Vec<DxtTexMipmap>),
 }
 
 impl Image {
@@ -74,8 +96,201 @@ impl Image {
         let mut image = image::RgbaImage::new(1, 1);
         image.get_pixel_mut(0, 0).0 = [255; 4];
 
-        Self { image }
+        Self::from(image)
+    }
+
+    /// Size of the visible part of the image in pixels.
+    pub fn size(&self) -> UVec2 {
+        self.crop.unwrap_or_else(|| self.texture_size())
+    }
+
+    /// Size of the stored pixels including the padding.
+    pub fn texture_size(&self) -> UVec2 {
+        match &self.data {
+            ImageData::Rgba8(image) => UVec2::new(image.width(), image.height()),
+            ImageData::Dxt(mipmaps) => mipmaps.first().map_or(UVec2::ZERO, |mipmap| {
+                UVec2::new(mipmap.width, mipmap.height)
+            }),
+        }
+    }
+
+    /// Decode the largest mipmap of the first image of a `.tex` texture.
+    ///
+    /// Texture padding is cropped away or cut off by [`Image::crop`], so
+    /// the resulting image has the size of the original picture.
+    pub fn from_tex(src: &mut impl Read) -> Result<Self, ImageLoadError> {
+        let mut reader = TexReader::new(src)
+            .read_header()?
+            .read_image_container_meta()?
+            .read_images()?;
+
+        let header = reader.header();
+
+        let image = reader
+            .take_images()
+            .and_then(|images| images.into_iter().next())
+            .ok_or(ImageLoadError::NoImage)?;
+
+        // NOTE: gif textures are sprite sheets, their frames are cropped later
+        let crop = (!reader.contains_gif())
+            .then(|| UVec2::new(header.image_width as u32, header.image_height as u32));
+
+        Self::from_tex_image(image, crop)
+    }
+
+    /// Convert the largest mipmap of a `.tex` image.
+    ///
+    /// The image is cropped to the `crop` size if it is given. `DXT` blocks can
+    /// not be cropped, so padded `DXT` textures keep the padding and only
+    /// remember the visible size in [`Image::crop`].
+    pub fn from_tex_image(image: TexImage, crop: Option<UVec2>) -> Result<Self, ImageLoadError> {
+        let mut image = if image.dxt_format().is_some() {
+            let mut mipmaps = image.into_dxt()?.ok_or(ImageLoadError::NoImage)?;
+
+            // NOTE: smaller mipmaps are optional, a broken chain still has a usable base
+            if !is_valid_mip_chain(&mipmaps) {
+                mipmaps.truncate(1);
+            }
+
+            if is_valid_mip_chain(&mipmaps) {
+                let size = UVec2::new(mipmaps[0].width, mipmaps[0].height);
+
+                return Ok(Self {
+                    data: ImageData::Dxt(mipmaps),
+                    crop: crop.filter(|&crop| crop != size && crop.cmple(size).all()),
+                });
+            }
+
+            debug!(?crop, "decompressing dxt texture unfit for upload");
+
+            let mipmap = mipmaps.into_iter().next().ok_or(ImageLoadError::NoImage)?;
+            mipmap.decompress()?
+        } else {
+            let mipmap = image
+                .decompress()?
+                .mipmaps
+                .into_iter()
+                .next()
+                .ok_or(ImageLoadError::NoImage)?;
+
+            mipmap.data.into_rgba8()?
+        };
+
+        if let Some(UVec2 {
+            x: width,
+            y: height,
+        }) = crop
+            && (width, height) != image.dimensions()
+            && width <= image.width()
+            && height <= image.height()
+        {
+            image = ::image::imageops::crop_imm(&image, 0, 0, width, height).to_image();
+        }
+
+        Ok(Self::from(image))
+    }
+
+    /// Part of the texture coordinates covering the visible part of the image.
+    pub fn uv_scale(&self) -> Vec2 {
+        self.size().as_vec2() / self.texture_size().max(UVec2::ONE).as_vec2()
+    }
+
+    /// Convert into `RGBA8888` pixels, decompressing `DXT` data on the CPU.
+    ///
+    /// The padding of the texture is cropped away.
+    pub fn into_rgba8(self) -> Result<image::RgbaImage, ImageLoadError> {
+        let image = match self.data {
+            ImageData::Rgba8(image) => image,
+            ImageData::Dxt(mipmaps) => {
+                let mipmap = mipmaps.into_iter().next().ok_or(ImageLoadError::NoImage)?;
+                mipmap.decompress()?
+            }
+        };
+
+        Ok(match self.crop {
+            Some(UVec2 {
+                x: width,
+                y: height,
+            }) if (width, height) != image.dimensions() => {
+                ::image::imageops::crop_imm(&image, 0, 0, width, height).to_image()
+            }
+            _ => image,
+        })
+    }
+
+    /// Decode an image from the contents of an ordinary image file or a `.tex` texture.
+    ///
+    /// `path` is only used to check the file extension.
+    pub fn from_bytes(path: &Path, mut bytes: &[u8]) -> Result<Self, ImageLoadError> {
+        if path.extension().is_some_and(|extension| extension == "tex") {
+            return Self::from_tex(&mut bytes);
+        }
+
+        let image = ::image::load_from_memory(bytes)?.into_rgba8();
+
+        Ok(Self::from(image))
+    }
+
+    /// Load an image from an ordinary image file or a `.tex` texture.
+    pub fn open(path: &Path) -> Result<Self, ImageLoadError> {
+        if path.extension().is_some_and(|extension| extension == "tex") {
+            let mut file = BufReader::new(File::open(path)?);
+            return Self::from_tex(&mut file);
+        }
+
+        let image = ::image::ImageReader::open(path)?
+            .with_guessed_format()?
+            .decode()?
+            .into_rgba8();
+
+        Ok(Self::from(image))
+    }
+}
+
+impl From<image::RgbaImage> for Image {
+    fn from(image: image::RgbaImage) -> Self {
+        Self {
+            data: ImageData::Rgba8(image),
+            crop: None,
+        }
+    }
+}
+
+/// Checks that every mipmap is half the size of the previous one
+/// and the data can be uploaded as a `BC` texture.
+fn is_valid_mip_chain(mipmaps: &[DxtTexMipmap]) -> bool {
+    let Some(base) = mipmaps.first() else {
+        return false;
+    };
+
+    // `BC` textures require the size of the largest mipmap to be divisible by the block size
+    if base.width == 0
+        || base.height == 0
+        || !base.width.is_multiple_of(4)
+        || !base.height.is_multiple_of(4)
+    {
+        return false;
     }
+
+    mipmaps.iter().enumerate().all(|(level, mipmap)| {
+        mipmap.format == base.format
+            && mipmap.width == (base.width >> level).max(1)
+            && mipmap.height == (base.height >> level).max(1)
+            && mipmap.is_complete()
+    })
+}
+
+/// Error that may occur while loading an [`Image`].
+#[derive(Debug, Error)]
+pub enum ImageLoadError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Image(#[from] ::image::ImageError),
+    #[error(transparent)]
+    Tex(#[from] TexExtractError),
+    #[error("texture contains no image")]
+    NoImage,
 }
 
 impl Load for Image {
@@ -83,13 +298,14 @@ impl Load for Image {
     where
         Self: Sized,
     {
-        let image = ::image::ImageReader::open(path)
-            .unwrap()
-            .decode()
-            .unwrap()
-            .into_rgba8();
+        Self::open(path).unwrap()
+    }
 
-        Self { image }
+    fn load_from_bytes(path: &Path, bytes: &[u8]) -> Result<Self, AssetLoadError>
+    where
+        Self: Sized,
+    {
+        Ok(Self::from_bytes(path, bytes)?)
     }
 }
 
@@ -145,6 +361,7 @@ impl ImagePipeline {
             label: Some("image-material"),
             min_filter: wgpu::FilterMode::Linear,
             mag_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
             ..Default::default()
         });
 
@@ -210,11 +427,45 @@ pub struct RenderImage {
     pub texture: wgpu::Texture,
     /// A view of the texture for rendering.
     pub view: wgpu::TextureView,
+    /// Texture coordinates scale cutting off the padding, see [`Image::uv_scale`].
+    pub uv_scale: Vec2,
 }
 
 impl RenderImage {
     /// Create a new render image from image data.
+    ///
+    /// `DXT` compressed images are decompressed on the CPU if the GPU
+    /// does not support `BC` texture compression.
     pub fn new(image: &Image, gpu: &Wgpu) -> Self {
+        match &image.data {
+            ImageData::Rgba8(pixels) => Self {
+                uv_scale: image.uv_scale(),
+                ..Self::from_rgba8(pixels, gpu)
+            },
+            ImageData::Dxt(mipmaps)
+                if gpu
+                    .device
+                    .features()
+                    .contains(wgpu::Features::TEXTURE_COMPRESSION_BC) =>
+            {
+                Self {
+                    uv_scale: image.uv_scale(),
+                    ..Self::from_dxt(mipmaps, gpu)
+                }
+            }
+            ImageData::Dxt(_) => {
+                let image = image
+                    .clone()
+                    .into_rgba8()
+                    .expect("dxt mipmaps are validated on load");
+
+                Self::from_rgba8(&image, gpu)
+            }
+        }
+    }
+
+    /// Create a new render image from `RGBA8888` pixels.
+    pub fn from_rgba8(image: &image::RgbaImage, gpu: &Wgpu) -> Self {
         let texture = gpu.device.create_texture_with_data(
             &gpu.queue,
             &wgpu::TextureDescriptor {
@@ -237,7 +488,58 @@ impl RenderImage {
 
         let view = texture.create_view(&Default::default());
 
-        Self { texture, view }
+        Self {
+            texture,
+            view,
+            uv_scale: Vec2::ONE,
+        }
+    }
+
+    /// Create a new render image uploading all `DXT` mipmaps as is.
+    ///
+    /// Requires [`wgpu::Features::TEXTURE_COMPRESSION_BC`].
+    pub fn from_dxt(mipmaps: &[DxtTexMipmap], gpu: &Wgpu) -> Self {
+        let base = &mipmaps[0];
+
+        let format = match base.format {
+            DxtFormat::Dxt1 => wgpu::TextureFormat::Bc1RgbaUnorm,
+            DxtFormat::Dxt3 => wgpu::TextureFormat::Bc2RgbaUnorm,
+            DxtFormat::Dxt5 => wgpu::TextureFormat::Bc3RgbaUnorm,
+        };
+
+        // mipmaps of the only layer are stored one after another
+        let data = mipmaps
+            .iter()
+            .flat_map(|mipmap| mipmap.data.iter().copied())
+            .collect::<Vec<u8>>();
+
+        let texture = gpu.device.create_texture_with_data(
+            &gpu.queue,
+            &wgpu::TextureDescriptor {
+                label: ImageMaterial::LABEL,
+                size: wgpu::Extent3d {
+                    width: base.width,
+                    height: base.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: mipmaps.len() as u32,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &data,
+        );
+
+        let view = texture.create_view(&Default::default());
+
+        Self {
+            texture,
+            view,
+            uv_scale: Vec2::ONE,
+        }
     }
 }
 
@@ -282,6 +584,16 @@ impl AsBindGroup for ImageMaterial {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         })
     }
@@ -292,7 +604,14 @@ impl AsBindGroup for ImageMaterial {
         device: &wgpu::Device,
         layout: &wgpu::BindGroupLayout,
     ) -> wgpu::BindGroup {
-        let view = &image_assets.get(self.image.id()).unwrap().view;
+        let image = image_assets.get(self.image.id()).unwrap();
+
+        // NOTE: `vec2` is padded to 16 bytes in a uniform block
+        let uv_scale = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Self::LABEL,
+            contents: bytemuck::bytes_of(&image.uv_scale.extend(0.0).extend(0.0)),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
 
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Self::LABEL,
@@ -300,13 +619,122 @@ impl AsBindGroup for ImageMaterial {
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(view),
+                    resource: wgpu::BindingResource::TextureView(&image.view),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&pipeline.sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uv_scale.as_entire_binding(),
+                },
             ],
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use extractor::texture::{TexFormat, TexWriter};
+
+    fn dxt_mipmap(width: u32, height: u32) -> DxtTexMipmap {
+        DxtTexMipmap {
+            width,
+            height,
+            data: vec![0; DxtFormat::Dxt5.compressed_size(width as usize, height as usize)],
+            format: DxtFormat::Dxt5,
+        }
+    }
+
+    fn dxt5_tex_image(width: u32, height: u32) -> TexImage {
+        let pixels = image::RgbaImage::from_pixel(width, height, image::Rgba([0, 0, 255, 255]));
+
+        let mut tex = Vec::new();
+        TexWriter::new(TexFormat::Dxt5)
+            .write_image(&pixels, &mut tex)
+            .unwrap();
+
+        TexReader::new(&mut tex.as_slice())
+            .read_header()
+            .unwrap()
+            .read_image_container_meta()
+            .unwrap()
+            .read_images()
+            .unwrap()
+            .take_images()
+            .unwrap()
+            .remove(0)
+    }
+
+    #[test]
+    fn valid_mip_chain() {
+        let chain = [8, 4, 2, 1].map(|size| dxt_mipmap(size, size));
+        assert!(is_valid_mip_chain(&chain));
+        assert!(is_valid_mip_chain(&chain[..1]));
+        assert!(is_valid_mip_chain(&[dxt_mipmap(16, 4), dxt_mipmap(8, 2)]));
+    }
+
+    #[test]
+    fn invalid_mip_chain() {
+        assert!(!is_valid_mip_chain(&[]));
+
+        // not divisible by the block size
+        assert!(!is_valid_mip_chain(&[dxt_mipmap(6, 6)]));
+
+        // not half the size of the previous level
+        assert!(!is_valid_mip_chain(&[dxt_mipmap(8, 8), dxt_mipmap(2, 2)]));
+
+        let mut truncated = dxt_mipmap(8, 8);
+        truncated.data.pop();
+        assert!(!is_valid_mip_chain(&[truncated]));
+
+        let mut other_format = dxt_mipmap(4, 4);
+        other_format.format = DxtFormat::Dxt1;
+        assert!(!is_valid_mip_chain(&[dxt_mipmap(8, 8), other_format]));
+    }
+
+    #[test]
+    fn padded_dxt_texture_stays_compressed() {
+        let image = Image::from_tex_image(dxt5_tex_image(8, 8), Some(UVec2::new(6, 5))).unwrap();
+
+        let ImageData::Dxt(mipmaps) = &image.data else {
+            panic!("padded dxt texture is decompressed");
+        };
+
+        assert_eq!(mipmaps.len(), 4);
+        assert_eq!(image.size(), UVec2::new(6, 5));
+        assert_eq!(image.texture_size(), UVec2::new(8, 8));
+        assert_eq!(image.uv_scale(), Vec2::new(0.75, 0.625));
+
+        let pixels = image.into_rgba8().unwrap();
+        assert_eq!(pixels.dimensions(), (6, 5));
+        assert!(pixels.pixels().all(|pixel| pixel.0 == [0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn unpadded_dxt_texture() {
+        let image = Image::from_tex_image(dxt5_tex_image(8, 8), Some(UVec2::new(8, 8))).unwrap();
+
+        assert!(matches!(image.data, ImageData::Dxt(_)));
+        assert_eq!(image.crop, None);
+        assert_eq!(image.uv_scale(), Vec2::ONE);
+
+        // the crop can not be larger than the texture
+        let image = Image::from_tex_image(dxt5_tex_image(8, 8), Some(UVec2::new(9, 8))).unwrap();
+        assert_eq!(image.crop, None);
+    }
+
+    #[test]
+    fn unaligned_dxt_texture_is_decompressed() {
+        let image = Image::from_tex_image(dxt5_tex_image(6, 6), Some(UVec2::new(5, 3))).unwrap();
+
+        let ImageData::Rgba8(pixels) = &image.data else {
+            panic!("unaligned dxt texture is uploaded compressed");
+        };
+
+        assert_eq!(pixels.dimensions(), (5, 3));
+        assert_eq!(image.uv_scale(), Vec2::ONE);
+    }
+}
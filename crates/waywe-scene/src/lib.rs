@@ -22,6 +22,7 @@
 //! - [`Image`](image::Image), [`ImageMaterial`](image::ImageMaterial): Load and display images
 //! - [`Mesh`](mesh::Mesh): Define geometry for rendering
 //! - [`Video`]: Play video content as textures
+//! - [`SpriteAnimation`](animation::SpriteAnimation): Play animated `.tex` textures
 //! - [`Material`](material::Material): Define how surfaces are rendered
 //!
 //! # Creating a Wallpaper
@@ -84,6 +85,7 @@
 
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
+pub mod animation;
 pub mod asset_server;
 pub mod assets;
 pub mod camera;
@@ -116,8 +118,10 @@ pub use glam;
 pub mod prelude {
     pub use crate::{
         FrameRateSetting, Monitor, Startup, Update,
+        animation::{AnimatedImage, SpriteAnimation},
         asset_server::{AssetHandle, AssetServer},
         assets::Assets,
+        camera::Camera,
         cursor::Cursor,
         image::{Image, ImageMaterial},
         mesh::{Mesh, Mesh3d, MeshMaterial, Vertex},
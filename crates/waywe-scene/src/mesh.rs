@@ -27,6 +27,7 @@ use crate::{
         RefAssetsRefDependencyPlugin, RenderAsset, RenderAssetExtractError, RenderAssets,
         RenderAssetsPlugin,
     },
+    camera::Camera,
     extract::Extract,
     image::ImageMaterial,
     material::{Material, MaterialSet, RenderMaterial, RenderMaterialId},
@@ -80,6 +81,12 @@ impl Plugin for MeshPlugin {
                     extract_objects::<VideoMaterial>
                         .after(AssetsExtract::MainToRender)
                         .after(MaterialSet::ExtractRender),
+                    extract_material_changes::<ImageMaterial>
+                        .after(extract_objects::<ImageMaterial>)
+                        .after(MaterialSet::ExtractRender),
+                    extract_material_changes::<VideoMaterial>
+                        .after(extract_objects::<VideoMaterial>)
+                        .after(MaterialSet::ExtractRender),
                     despawn_removed_entities,
                 ),
             )
@@ -332,6 +339,42 @@ pub fn extract_objects<M: Material>(
     }
 }
 
+/// System to extract material changes of already extracted mesh objects.
+pub fn extract_material_changes<M: Material>(
+    mut commands: Commands,
+    entity_map: Res<EntityMap>,
+    monitor: Res<Monitor>,
+    mesh_query: Extract<Query<(Entity, Ref<Mesh3d>, &MeshMaterial<M>), Changed<MeshMaterial<M>>>>,
+    gpu: Res<RenderGpu>,
+    materials: Res<RefAssets<RenderMaterial>>,
+    mut pipelines: ResMut<RefAssets<MeshPipeline>>,
+) {
+    for (id, mesh, MeshMaterial(material)) in &mesh_query {
+        // Newly spawned objects are handled by `extract_objects`
+        if mesh.is_changed() {
+            continue;
+        }
+
+        let Some(&render_id) = entity_map.get(&id) else {
+            continue;
+        };
+
+        let Some(render_material) = materials.get(material.id()) else {
+            continue;
+        };
+
+        pipelines.insert_with(material.id(), || {
+            MeshPipeline::new(&gpu, monitor.id, render_material)
+        });
+
+        let Ok(mut render_entity) = commands.get_entity(render_id) else {
+            continue;
+        };
+
+        render_entity.insert(RenderMaterialId(material.id()));
+    }
+}
+
 pub fn despawn_removed_entities(
     mut commands: Commands,
     mut despawned: Extract<RemovedComponents<Mesh3d>>,
@@ -360,6 +403,7 @@ pub fn render_meshes(
     mut encoder: ResMut<CommandEncoder>,
     time: Res<Time>,
     monitor: Res<Monitor>,
+    camera: Res<Camera>,
     surface_view: Res<SurfaceView>,
 ) {
     let mesh_handles = mesh_handles
@@ -376,9 +420,7 @@ pub fn render_meshes(
             .iter()
             .chunk_by(|&(RenderMeshId(id), _)| id);
 
-        let aspect_ratio = monitor.aspect_ratio();
-        let camera_view =
-            Mat4::orthographic_rh(-1.0, 1.0, -aspect_ratio, aspect_ratio, -10.0, 10.0);
+        let camera_view = camera.view(monitor.aspect_ratio());
 
         let pipeline = pipelines.get(material_id).unwrap();
         let material = materials.get(material_id).unwrap();
@@ -14,6 +14,8 @@
 //! - [`VideoPlugin`]: Play video content
 //! - [`MaterialPlugin`]: Define surface appearance
 //! - [`CursorPlugin`]: Handle cursor interaction
+//! - [`CameraPlugin`]: Project the scene onto the monitor
+//! - [`AnimationPlugin`]: Play sprite-sheet animations
 //!
 //! These are combined in [`DefaultPlugins`] for convenience.
 //!
@@ -40,9 +42,10 @@
 //! ```
 
 use crate::{
-    asset_server::AssetServerPlugin, clear_screen::ClearScreenPlugin, cursor::CursorPlugin,
-    image::ImagePlugin, material::MaterialPlugin, mesh::MeshPlugin, sprite::SpritePlugin,
-    transform::TransformPlugin, video::VideoPlugin, wallpaper::Wallpaper,
+    animation::AnimationPlugin, asset_server::AssetServerPlugin, camera::CameraPlugin,
+    clear_screen::ClearScreenPlugin, cursor::CursorPlugin, image::ImagePlugin,
+    material::MaterialPlugin, mesh::MeshPlugin, sprite::SpritePlugin, transform::TransformPlugin,
+    video::VideoPlugin, wallpaper::Wallpaper,
 };
 use bitflags::bitflags;
 use static_assertions::assert_obj_safe;
@@ -131,13 +134,17 @@ define_plugin_group! {
     /// - [`VideoPlugin`]: Video playback
     /// - [`MaterialPlugin`]: Surface appearance
     /// - [`CursorPlugin`]: Cursor interaction
+    /// - [`CameraPlugin`]: Scene projection
+    /// - [`AnimationPlugin`]: Sprite-sheet animations
     #[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
     pub struct DefaultPlugins(
         TransformPlugin,
         CursorPlugin,
+        CameraPlugin,
         AssetServerPlugin,
         MaterialPlugin,
         ImagePlugin,
+        AnimationPlugin,
         VideoPlugin,
         MeshPlugin,
         ClearScreenPlugin,
@@ -9,11 +9,16 @@ layout(push_constant) uniform struct PushConst {
 layout(set = 0, binding = 0) uniform texture2D image;
 layout(set = 0, binding = 1) uniform sampler image_sampler;
 
+// scale of texture coordinates cutting off the padding of the texture
+layout(set = 0, binding = 2) uniform ImageParams {
+    vec2 uv_scale;
+} params;
+
 in vec2 uv;
 out vec4 surface_color;
 
 void main() {
-    vec4 sample_color = texture(sampler2D(image, image_sampler), uv);
+    vec4 sample_color = texture(sampler2D(image, image_sampler), uv * params.uv_scale);
     surface_color = sample_color;
     surface_color.rgb = vec3(
         pow(surface_color.r, 1.0 / 0.4545),
@@ -102,8 +102,7 @@ pub fn extract_sprites(
         let size = match &sprite.texture {
             SpriteTexture::Image(image) => {
                 let image_id = assets.image_materials.get(image.id()).unwrap().image.id();
-                let image = &assets.images.get(image_id).unwrap().image;
-                UVec2::new(image.width(), image.height())
+                assets.images.get(image_id).unwrap().size()
             }
             SpriteTexture::Video(video) => {
                 let video_id = assets.video_materials.get(video.id()).unwrap().video.id();
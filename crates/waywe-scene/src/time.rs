@@ -37,6 +37,12 @@ impl Time {
         self.elapsed += delta;
         self.prev = now;
     }
+
+    /// Starts counting the next delta from now, so the time
+    /// passed while the wallpaper was paused is skipped.
+    pub fn resume(&mut self) {
+        self.prev = Instant::now();
+    }
 }
 
 impl Default for Time {
@@ -1,5 +1,10 @@
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
+use waywe_ipc::{
+    command::ProfileCommand,
+    playlist::parse_interval,
+    wallpaper::{Color, FitMode},
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -10,13 +15,53 @@ pub struct Args {
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
-    /// Set s image/video as a wallpaper
+    /// Set s image/video/scene as a wallpaper
     Show {
         /// Monitor to set wallpaper on
         #[arg(short, long)]
         monitor: Option<String>,
-        /// Path to an image or a video
-        path: PathBuf,
+        /// Path to an image, a video or a Wallpaper Engine scene project
+        #[arg(required_unless_present = "workshop")]
+        path: Option<PathBuf>,
+        /// Id of an installed Wallpaper Engine workshop item
+        #[arg(short, long, conflicts_with = "path")]
+        workshop: Option<u64>,
+        /// How images and videos are scaled: fill (default), fit, stretch, center or tile
+        #[arg(long, conflicts_with = "workshop")]
+        fit: Option<FitMode>,
+        /// Color around the wallpaper and behind its transparent pixels, e.g. '#1e1e2e', black by default
+        #[arg(long, conflicts_with = "workshop")]
+        background: Option<Color>,
+        /// Show one image or video across all monitors as they are laid out
+        #[arg(long, conflicts_with_all = ["monitor", "workshop"])]
+        span: bool,
+    },
+    /// Cycle through images and videos as wallpapers
+    Playlist {
+        /// Monitor to show the playlist on
+        #[arg(short, long)]
+        monitor: Option<String>,
+        /// Wallpapers or directories with them, in the order they are shown
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// How long each wallpaper is shown, e.g. `30s`, `10m` or `1h`
+        #[arg(short, long, default_value = "10m", value_parser = parse_interval)]
+        interval: Duration,
+        /// Show the wallpapers in random order
+        #[arg(short, long)]
+        shuffle: bool,
+    },
+    /// Show the next wallpaper of the playlist
+    Next {
+        /// Monitor to switch the wallpaper on
+        #[arg(short, long)]
+        monitor: Option<String>,
+    },
+    /// Show the previous wallpaper of the playlist
+    Prev {
+        /// Monitor to switch the wallpaper on
+        #[arg(short, long)]
+        monitor: Option<String>,
     },
     /// Start the daemon process
     Start,
@@ -26,6 +71,21 @@ pub enum Command {
         #[arg(short, long)]
         monitor: Option<String>,
     },
+    /// Show wallpapers running on the monitors
+    Status {
+        /// Monitor to show the wallpaper status of
+        #[arg(short, long)]
+        monitor: Option<String>,
+        /// Print the status as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// List monitors known to the daemon
+    Monitors {
+        /// Print the monitors as json
+        #[arg(long)]
+        json: bool,
+    },
     /// Create a preview for the wallpaper
     Preview {
         /// Monitor to set wallpaper on
@@ -34,10 +94,56 @@ pub enum Command {
         /// Where to store the preview
         out: PathBuf,
     },
-    /// Pause/Resume current wallpaper
+    /// Print daemon events as json lines as they happen
+    Watch,
+    /// List installed Wallpaper Engine workshop items
+    ListWorkshop,
+    /// Manage named profiles stored in ~/.config/waywe/profiles
+    Profile {
+        #[command(subcommand)]
+        command: ProfileSubcommand,
+    },
+    /// Read ~/.config/waywe/config.toml again and apply it to the running wallpapers
+    Reload,
+    /// Pause current wallpaper
     Pause {
         /// Monitor to pause the wallpaper on
         #[arg(short, long)]
         monitor: Option<String>,
     },
+    /// Resume paused wallpaper
+    Resume {
+        /// Monitor to resume the wallpaper on
+        #[arg(short, long)]
+        monitor: Option<String>,
+    },
+    /// Pause wallpapers if any of them is running, resume them otherwise
+    Toggle {
+        /// Monitor to toggle the wallpaper on
+        #[arg(short, long)]
+        monitor: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileSubcommand {
+    /// Save wallpapers of every monitor under the name
+    Save { name: String },
+    /// Show wallpapers of the profile
+    Load { name: String },
+    /// List saved profiles
+    List,
+    /// Delete the profile
+    Delete { name: String },
+}
+
+impl From<ProfileSubcommand> for ProfileCommand {
+    fn from(value: ProfileSubcommand) -> Self {
+        match value {
+            ProfileSubcommand::Save { name } => Self::Save { name },
+            ProfileSubcommand::Load { name } => Self::Load { name },
+            ProfileSubcommand::List => Self::List,
+            ProfileSubcommand::Delete { name } => Self::Delete { name },
+        }
+    }
 }
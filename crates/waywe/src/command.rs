@@ -1,13 +1,19 @@
 use file_format::{FileFormat, Kind};
 use image::{DynamicImage, ImageError, ImageReader, RgbImage};
+use project_parser::{
+    LocateError, locate_we_assets,
+    project::{PROJECT_FILE_NAME, Project, ProjectError, ProjectType},
+    workshop::workshop_items,
+};
 use std::{
     ffi::CStr,
-    io,
+    fs, io,
     path::{Path, PathBuf},
     process::{self, Stdio},
+    time::Duration,
 };
 use thiserror::Error;
-use tracing::error;
+use tracing::{error, warn};
 use transmute_extra::pathbuf_into_cstring;
 use video::{
     BackendError, Codec, CodecContext, FormatContext, Frame, MediaType, ScalerFlags, ScalerFormat,
@@ -15,7 +21,11 @@ use video::{
 };
 use waywe_ipc::{
     DaemonCommand, WallpaperType,
-    profile::{SetupProfile, SetupProfileError},
+    command::ProfileCommand,
+    playlist::{Playlist, PlaylistEntry},
+    profile::{Profile, SetupProfileError},
+    status::{MonitorInfo, PlaybackState, WallpaperStatus},
+    wallpaper::Placement,
 };
 
 #[derive(Debug, Error)]
@@ -34,19 +44,79 @@ pub enum ExecuteError {
     Image(#[from] ImageError),
     #[error("video '{path}' is invalid")]
     InvalidVideo { path: PathBuf },
+    #[error(transparent)]
+    Project(#[from] ProjectError),
+    #[error("unsupported project type '{0:?}'")]
+    UnsupportedProject(ProjectType),
+    #[error("project '{path}' has no preview")]
+    NoPreview { path: PathBuf },
+    #[error(transparent)]
+    Locate(#[from] LocateError),
+    #[error("workshop item {0} is not installed")]
+    NoWorkshopItem(u64),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("no wallpapers to play")]
+    EmptyPlaylist,
+    #[error("playlist of {0} wallpapers is longer than {max}", max = Playlist::MAX_ENTRIES)]
+    PlaylistTooLong(usize),
+    #[error("--fit, --background and --span apply only to images and videos")]
+    ScenePlacement,
 }
 
-pub fn execute_current(monitor_name: Option<&str>) -> Result<(), ExecuteError> {
-    let mut profile = SetupProfile::read()?;
-
-    let Some(info) = (match monitor_name {
-        Some(name) => profile.monitors.remove(name),
-        None => profile.monitors.into_values().next(),
-    }) else {
+/// Prints the path to the wallpaper given the daemon reply to [`DaemonCommand::Status`]
+pub fn execute_current(statuses: &[WallpaperStatus]) -> Result<(), ExecuteError> {
+    let Some(status) = statuses.first() else {
         return Err(ExecuteError::NoWallpaper);
     };
 
-    println!("{}", info.path.display());
+    println!("{}", status.path.display());
+
+    Ok(())
+}
+
+/// Prints the daemon reply to [`DaemonCommand::Status`], one wallpaper per line
+pub fn execute_status(statuses: &[WallpaperStatus], json: bool) -> Result<(), ExecuteError> {
+    if json {
+        println!("{}", serde_json::to_string(statuses)?);
+        return Ok(());
+    }
+
+    for status in statuses {
+        let state = match status.state {
+            PlaybackState::Running => "running",
+            PlaybackState::Paused => "paused",
+        };
+
+        println!(
+            "{}\t{state}\t{:?}\t{:.1} fps\t{}\t[{}]\t{}",
+            status.monitor,
+            status.wallpaper_type,
+            status.fps,
+            status.power,
+            status.effects.join(", "),
+            status.path.display(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints the daemon reply to [`DaemonCommand::Monitors`], one monitor per line
+pub fn execute_monitors(monitors: &[MonitorInfo], json: bool) -> Result<(), ExecuteError> {
+    if json {
+        println!("{}", serde_json::to_string(monitors)?);
+        return Ok(());
+    }
+
+    for monitor in monitors {
+        let size = match monitor.size {
+            Some([width, height]) => format!("{width}x{height}"),
+            None => "unconfigured".to_owned(),
+        };
+
+        println!("{}\t{size}\tscale {}", monitor.name, monitor.scale);
+    }
 
     Ok(())
 }
@@ -63,7 +133,7 @@ pub fn execute_start() {
 }
 
 pub fn execute_preview(result_path: &Path, monitor_name: Option<&str>) -> Result<(), ExecuteError> {
-    let mut profile = SetupProfile::read()?;
+    let mut profile = Profile::read_session()?;
 
     let Some(info) = (match monitor_name {
         Some(name) => profile.monitors.remove(name),
@@ -73,7 +143,17 @@ pub fn execute_preview(result_path: &Path, monitor_name: Option<&str>) -> Result
     };
 
     let image = match info.wallpaper_type {
-        WallpaperType::Scene => todo!("scene"),
+        WallpaperType::Scene => {
+            let project = Project::read(&info.path)?;
+
+            let Some(preview) = project.preview else {
+                return Err(ExecuteError::NoPreview { path: info.path });
+            };
+
+            ImageReader::open(info.path.join(preview))?
+                .with_guessed_format()?
+                .decode()?
+        }
         WallpaperType::Video => {
             let c_path = pathbuf_into_cstring(info.path.clone());
 
@@ -145,21 +225,169 @@ pub fn execute_preview(result_path: &Path, monitor_name: Option<&str>) -> Result
     Ok(())
 }
 
+/// Sets the wallpaper, `placement` is `None` if the user left it to the default one
 pub fn execute_show(
     path: &Path,
     monitor_name: Option<String>,
+    placement: Option<Placement>,
 ) -> Result<DaemonCommand, ExecuteError> {
+    let wallpaper = resolve_wallpaper(path)?;
+
+    // NOTE: scenes are laid out by their own cameras
+    if wallpaper.wallpaper_type == WallpaperType::Scene && placement.is_some() {
+        return Err(ExecuteError::ScenePlacement);
+    }
+
+    Ok(set_wallpaper(
+        wallpaper,
+        monitor_name,
+        placement.unwrap_or_default(),
+    ))
+}
+
+pub fn execute_show_workshop(
+    id: u64,
+    monitor_name: Option<String>,
+) -> Result<DaemonCommand, ExecuteError> {
+    let project_directory = locate_we_assets()?.join(id.to_string());
+
+    if !project_directory.is_dir() {
+        return Err(ExecuteError::NoWorkshopItem(id));
+    }
+
+    Ok(set_wallpaper(
+        resolve_project(&project_directory)?,
+        monitor_name,
+        Placement::default(),
+    ))
+}
+
+/// Makes a playlist of the wallpapers, directories are replaced with the wallpapers in them
+///
+/// Files of the directories which are not wallpapers are skipped
+pub fn execute_playlist(
+    paths: &[PathBuf],
+    interval: Duration,
+    shuffle: bool,
+    monitor_name: Option<String>,
+) -> Result<DaemonCommand, ExecuteError> {
+    let mut entries = vec![];
+
+    for path in paths {
+        if !path.is_dir() || path.join(PROJECT_FILE_NAME).is_file() {
+            entries.push(resolve_wallpaper(path)?);
+            continue;
+        }
+
+        let mut files = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        files.sort_unstable();
+
+        for file in files {
+            match resolve_wallpaper(&file) {
+                Ok(entry) => entries.push(entry),
+                Err(error) => warn!(?file, %error, "skipping file of the playlist"),
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(ExecuteError::EmptyPlaylist);
+    }
+
+    if entries.len() > Playlist::MAX_ENTRIES {
+        return Err(ExecuteError::PlaylistTooLong(entries.len()));
+    }
+
+    Ok(DaemonCommand::SetPlaylist {
+        playlist: Playlist::new(entries, interval, shuffle),
+        monitor: monitor_name,
+    })
+}
+
+pub fn execute_next(monitor_name: Option<String>) -> Result<DaemonCommand, ExecuteError> {
+    Ok(DaemonCommand::Next {
+        monitor: monitor_name,
+    })
+}
+
+pub fn execute_prev(monitor_name: Option<String>) -> Result<DaemonCommand, ExecuteError> {
+    Ok(DaemonCommand::Prev {
+        monitor: monitor_name,
+    })
+}
+
+fn set_wallpaper(
+    wallpaper: PlaylistEntry,
+    monitor_name: Option<String>,
+    placement: Placement,
+) -> DaemonCommand {
+    let PlaylistEntry {
+        path,
+        wallpaper_type,
+    } = wallpaper;
+
+    match wallpaper_type {
+        WallpaperType::Video => DaemonCommand::SetVideo {
+            path,
+            monitor: monitor_name,
+            placement,
+        },
+        WallpaperType::Image => DaemonCommand::SetImage {
+            path,
+            monitor: monitor_name,
+            placement,
+        },
+        WallpaperType::Scene => DaemonCommand::SetScene {
+            path,
+            monitor: monitor_name,
+        },
+    }
+}
+
+/// Absolute path and type of the image, the video or the project
+fn resolve_wallpaper(path: &Path) -> Result<PlaylistEntry, ExecuteError> {
+    if let Some(project_directory) = project_directory(path) {
+        return resolve_project(&project_directory);
+    }
+
+    resolve_file(path)
+}
+
+fn resolve_project(project_directory: &Path) -> Result<PlaylistEntry, ExecuteError> {
+    let project = Project::read(project_directory)?;
+
+    if !is_project_playable(&project) {
+        return Err(ExecuteError::UnsupportedProject(project.ty));
+    }
+
+    match (project.ty, project.file) {
+        (ProjectType::Video, Some(file)) => resolve_file(&project_directory.join(file)),
+        _ => Ok(PlaylistEntry {
+            path: project_directory.canonicalize()?,
+            wallpaper_type: WallpaperType::Scene,
+        }),
+    }
+}
+
+fn resolve_file(path: &Path) -> Result<PlaylistEntry, ExecuteError> {
     let file_kind = FileFormat::from_file(path)?.kind();
 
     Ok(match file_kind {
         Kind::Image => {
+            // NOTE: decoding every image of a large folder takes too long,
+            // the daemon reports images it can not decode
             let reader = ImageReader::open(path)?.with_guessed_format()?;
-            let _image = reader.decode()?;
-            let absolute_path = path.canonicalize()?;
 
-            DaemonCommand::SetImage {
-                path: absolute_path,
-                monitor: monitor_name,
+            if reader.format().is_none() {
+                return Err(ExecuteError::UnsupportedFileFormat(file_kind));
+            }
+
+            PlaylistEntry {
+                path: path.canonicalize()?,
+                wallpaper_type: WallpaperType::Image,
             }
         }
         Kind::Video => {
@@ -171,21 +399,98 @@ pub fn execute_show(
                 });
             }
 
-            DaemonCommand::SetVideo {
+            PlaylistEntry {
                 path: absolute_path,
-                monitor: monitor_name,
+                wallpaper_type: WallpaperType::Video,
             }
         }
         _ => return Err(ExecuteError::UnsupportedFileFormat(file_kind)),
     })
 }
 
+pub fn execute_list_workshop() -> Result<(), ExecuteError> {
+    for item in workshop_items()? {
+        let item = match item {
+            Ok(item) => item,
+            Err(error) => {
+                error!(?error, "failed to read workshop item");
+                continue;
+            }
+        };
+
+        let support = if is_project_playable(&item.project) {
+            "playable"
+        } else {
+            "unsupported"
+        };
+
+        println!(
+            "{}\t{:?}\t{support}\t{}",
+            item.id, item.project.ty, item.project.title,
+        );
+    }
+
+    Ok(())
+}
+
+pub fn execute_profile(command: ProfileCommand) -> Result<DaemonCommand, ExecuteError> {
+    Ok(DaemonCommand::Profile(command))
+}
+
+/// Prints the daemon reply to [`ProfileCommand::List`], one profile per line
+pub fn execute_profile_list(names: &[String]) {
+    for name in names {
+        println!("{name}");
+    }
+}
+
+pub fn execute_reload() -> Result<DaemonCommand, ExecuteError> {
+    Ok(DaemonCommand::Reload)
+}
+
 pub fn execute_pause(monitor_name: Option<String>) -> Result<DaemonCommand, ExecuteError> {
     Ok(DaemonCommand::Pause {
         monitor: monitor_name,
     })
 }
 
+pub fn execute_resume(monitor_name: Option<String>) -> Result<DaemonCommand, ExecuteError> {
+    Ok(DaemonCommand::Resume {
+        monitor: monitor_name,
+    })
+}
+
+pub fn execute_toggle(monitor_name: Option<String>) -> Result<DaemonCommand, ExecuteError> {
+    Ok(DaemonCommand::Toggle {
+        monitor: monitor_name,
+    })
+}
+
+/// Checks whether waywe can play the project
+fn is_project_playable(project: &Project) -> bool {
+    match project.ty {
+        ProjectType::Scene => true,
+        ProjectType::Video => project.file.is_some(),
+        ProjectType::Web | ProjectType::Application | ProjectType::Unknown => false,
+    }
+}
+
+/// Wallpaper Engine project directory given a path to the directory or to a file in it
+fn project_directory(path: &Path) -> Option<PathBuf> {
+    if path.is_dir() {
+        return Some(path.to_owned());
+    }
+
+    match path.file_name()?.to_str()? {
+        PROJECT_FILE_NAME | "scene.pkg" => match path.parent()? {
+            // a bare file name has an empty parent
+            parent if parent.as_os_str().is_empty() => Some(PathBuf::from(".")),
+            parent => Some(parent.to_owned()),
+        },
+        _ => None,
+    }
+}
+
 fn is_video_path_valid(path: PathBuf) -> bool {
     if !path.exists() {
         error!(?path, "file does not exist");
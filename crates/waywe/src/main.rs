@@ -3,47 +3,184 @@ pub mod command;
 
 use crate::{
     args::{Args, Command},
-    command::{execute_current, execute_pause, execute_preview, execute_show, execute_start},
+    command::{
+        execute_current, execute_list_workshop, execute_monitors, execute_next, execute_pause,
+        execute_playlist, execute_prev, execute_preview, execute_profile, execute_profile_list,
+        execute_reload, execute_resume, execute_show, execute_show_workshop, execute_start,
+        execute_status, execute_toggle,
+    },
 };
 use anyhow::{Context as _, bail};
 use clap::Parser as _;
 use rustix::io::Errno;
-use waywe_ipc::{DaemonCommand, IpcSocket, ipc::Client};
+use std::process::ExitCode;
+use waywe_ipc::{
+    DaemonCommand, DaemonEvent, DaemonResponse, IpcSocket, RecvError, ipc::Client,
+    wallpaper::Placement,
+};
 
-fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<ExitCode> {
     tracing_subscriber::fmt::init();
     video::init();
 
     let daemon_command = match Args::parse().command {
         Command::Preview { out, monitor } => {
             execute_preview(&out, monitor.as_deref())?;
-            return Ok(());
+            return Ok(ExitCode::SUCCESS);
         }
         Command::Current { monitor } => {
-            execute_current(monitor.as_deref())?;
-            return Ok(());
+            let response = request(DaemonCommand::Status { monitor })?;
+
+            if let DaemonResponse::Status(statuses) = &response {
+                execute_current(statuses)?;
+            }
+
+            return Ok(report(&response));
+        }
+        Command::Status { monitor, json } => {
+            let response = request(DaemonCommand::Status { monitor })?;
+
+            if let DaemonResponse::Status(statuses) = &response {
+                execute_status(statuses, json)?;
+            }
+
+            return Ok(report(&response));
+        }
+        Command::Monitors { json } => {
+            let response = request(DaemonCommand::Monitors)?;
+
+            if let DaemonResponse::Monitors(monitors) = &response {
+                execute_monitors(monitors, json)?;
+            }
+
+            return Ok(report(&response));
+        }
+        Command::Watch => {
+            watch()?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Command::ListWorkshop => {
+            execute_list_workshop()?;
+            return Ok(ExitCode::SUCCESS);
         }
         Command::Start => {
             execute_start();
-            return Ok(());
+            return Ok(ExitCode::SUCCESS);
         }
-        Command::Show { path, monitor } => execute_show(&path, monitor)?,
+        Command::Show {
+            path: Some(path),
+            monitor,
+            fit,
+            background,
+            span,
+            ..
+        } => execute_show(
+            &path,
+            monitor,
+            (fit.is_some() || background.is_some() || span).then(|| Placement {
+                fit: fit.unwrap_or_default(),
+                background: background.unwrap_or_default(),
+                span,
+            }),
+        )?,
+        Command::Show {
+            path: None,
+            workshop: Some(id),
+            monitor,
+            ..
+        } => execute_show_workshop(id, monitor)?,
+        Command::Show {
+            path: None,
+            workshop: None,
+            ..
+        } => unreachable!("either path or workshop id is required"),
+        Command::Playlist {
+            monitor,
+            paths,
+            interval,
+            shuffle,
+        } => execute_playlist(&paths, interval, shuffle, monitor)?,
+        Command::Next { monitor } => execute_next(monitor)?,
+        Command::Prev { monitor } => execute_prev(monitor)?,
         Command::Pause { monitor } => execute_pause(monitor)?,
+        Command::Resume { monitor } => execute_resume(monitor)?,
+        Command::Toggle { monitor } => execute_toggle(monitor)?,
+        Command::Profile { command } => execute_profile(command.into())?,
+        Command::Reload => execute_reload()?,
     };
 
-    let socket = match IpcSocket::<Client, DaemonCommand>::connect() {
-        Ok(socket) => socket,
+    let response = request(daemon_command)?;
+
+    if let DaemonResponse::Profiles(names) = &response {
+        execute_profile_list(names);
+    }
+
+    Ok(report(&response))
+}
+
+fn connect() -> anyhow::Result<IpcSocket<Client, DaemonCommand>> {
+    match IpcSocket::connect() {
+        Ok(socket) => Ok(socket),
         Err(Errno::CONNREFUSED) => {
             bail!("no waywe-daemon is running");
         }
         Err(error) => {
             bail!("failed to connect to waywe-daemon: {error}");
         }
-    };
+    }
+}
+
+/// Sends the command to the daemon and waits for its reply
+fn request(command: DaemonCommand) -> anyhow::Result<DaemonResponse> {
+    connect()?
+        .request::<DaemonResponse>(command)
+        .context("failed to send a command to the daemon")
+}
+
+/// Prints daemon events as json lines until the daemon stops
+fn watch() -> anyhow::Result<()> {
+    let socket = connect()?;
+
+    let response = socket
+        .request::<DaemonResponse>(DaemonCommand::Subscribe)
+        .context("failed to subscribe to the daemon events")?;
 
-    socket
-        .send(daemon_command)
-        .context("failed to set a command to the daemon")?;
+    if !response.is_ok() {
+        bail!("{response}");
+    }
+
+    loop {
+        let event = match socket.recv_blocking::<DaemonEvent>() {
+            Ok(event) => event,
+            Err(RecvError::Closed) => return Ok(()),
+            Err(error) => return Err(error).context("failed to receive a daemon event"),
+        };
+
+        println!("{}", serde_json::to_string(&event)?);
+    }
+}
+
+/// Prints the error reported by the daemon, if any, and returns the exit code of the cli
+fn report(response: &DaemonResponse) -> ExitCode {
+    if !response.is_ok() {
+        eprintln!("error: {response}");
+    }
+
+    response_exit_code(response)
+}
 
-    Ok(())
+/// Exit code of the cli given the daemon response
+fn response_exit_code(response: &DaemonResponse) -> ExitCode {
+    match response {
+        DaemonResponse::Ok
+        | DaemonResponse::Status(..)
+        | DaemonResponse::Monitors(..)
+        | DaemonResponse::Profiles(..) => ExitCode::SUCCESS,
+        DaemonResponse::UnknownMonitor { .. } => ExitCode::from(2),
+        DaemonResponse::DecodeFailed { .. } => ExitCode::from(3),
+        DaemonResponse::ProfileFailed { .. } => ExitCode::from(4),
+        DaemonResponse::NoPlaylist => ExitCode::from(5),
+        DaemonResponse::ConfigFailed { .. } => ExitCode::from(6),
+        DaemonResponse::ShaderFailed { .. } => ExitCode::from(7),
+    }
 }